@@ -9,6 +9,8 @@ criterion::criterion_group! {
         .measurement_time(std::time::Duration::from_secs(1));
     targets =
         sampling_river_evaluation,
+        sampling_river_lookup,
+        ranking_river_hands,
         sampling_river_equity,
         sampling_river_observation,
         converting_turn_isomorphism,
@@ -17,7 +19,9 @@ criterion::criterion_group! {
         collecting_turn_histogram,
         computing_optimal_transport_variation,
         computing_optimal_transport_heuristic,
+        computing_optimal_transport_heuristic_parallel,
         computing_optimal_transport_sinkhorns,
+        querying_strategy_server,
 }
 
 fn sampling_river_evaluation(c: &mut criterion::Criterion) {
@@ -27,12 +31,36 @@ fn sampling_river_evaluation(c: &mut criterion::Criterion) {
     });
 }
 
+fn sampling_river_lookup(c: &mut criterion::Criterion) {
+    let table = LookupEvaluator::shared();
+    c.bench_function("evaluate a 7-card Hand (lookup)", |b| {
+        let hand = Hand::from(Observation::from(Street::Rive));
+        b.iter(|| table.strength(hand))
+    });
+}
+
 fn sampling_river_observation(c: &mut criterion::Criterion) {
     c.bench_function("collect a 7-card River Observation", |b| {
         b.iter(|| Observation::from(Street::Rive))
     });
 }
 
+/// rank and suit counting dominates here, so compare
+/// runs with and without --features simd
+fn ranking_river_hands(c: &mut criterion::Criterion) {
+    c.bench_function("rank 1024 7-card Hands", |b| {
+        let hands = (0..1024)
+            .map(|_| Hand::from(Observation::from(Street::Rive)))
+            .collect::<Vec<Hand>>();
+        b.iter(|| {
+            hands
+                .iter()
+                .map(|hand| Evaluator::from(*hand).find_ranking())
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
 fn sampling_river_equity(c: &mut criterion::Criterion) {
     let observation = Observation::from(Street::Rive);
     c.bench_function("calculate River equity", |b| {
@@ -85,6 +113,40 @@ fn computing_optimal_transport_heuristic(c: &mut criterion::Criterion) {
     });
 }
 
+fn computing_optimal_transport_heuristic_parallel(c: &mut criterion::Criterion) {
+    use rand::Rng;
+    let ref mut rng = robopoker::rng();
+    let basis = Abstraction::basis(Street::Turn);
+    let metric = Metric::from(
+        basis
+            .iter()
+            .flat_map(|x| basis.iter().map(move |y| (x, y)))
+            .filter(|(x, y)| x > y)
+            .map(|(x, y)| (Pair::from((x, y)), rng.gen::<f32>()))
+            .collect::<std::collections::BTreeMap<_, _>>(),
+    );
+    let mut histogram = || {
+        (0..1024)
+            .map(|_| basis[rng.gen_range(0..basis.len())])
+            .fold(Histogram::default(), |h, a| h.increment(a))
+    };
+    let (h1, h2) = (histogram(), histogram());
+    for parallel in [false, true] {
+        let name = match parallel {
+            false => "compute optimal transport (greedy, 144 serial)",
+            true => "compute optimal transport (greedy, 144 parallel)",
+        };
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                Heuristic::from((&h1, &h2, &metric))
+                    .with_parallel(parallel)
+                    .minimize()
+                    .cost()
+            })
+        });
+    }
+}
+
 fn computing_optimal_transport_sinkhorns(c: &mut criterion::Criterion) {
     let (metric, h1, h2, _) = EMD::random().inner();
     c.bench_function("compute optimal transport (entropy regularized)", |b| {
@@ -101,17 +163,56 @@ fn computing_optimal_transport_sinkhorns(c: &mut criterion::Criterion) {
      */
 }
 
+fn querying_strategy_server(c: &mut criterion::Criterion) {
+    const N: usize = 1024;
+    let spots = (0..N).map(|_| Spot::random()).collect::<Vec<Spot>>();
+    let encoding = Encoding::from(
+        spots
+            .iter()
+            .map(|spot| spot.game())
+            .map(|game| (Isomorphism::from(Observation::from(&game)), game.street()))
+            .map(|(iso, street)| (iso, Abstraction::from((street, 0))))
+            .collect::<BTreeMap<Isomorphism, Abstraction>>(),
+    );
+    let average = Average::from(
+        spots
+            .iter()
+            .map(|spot| (encoding.bucket(spot), encoding.edges(spot)))
+            .map(|(bucket, edges)| (bucket, edges.into_iter().map(|e| (e, 1.)).collect()))
+            .collect::<BTreeMap<Bucket, BTreeMap<Edge, f32>>>(),
+    );
+    let server = StrategyServer::from((average, encoding));
+    let mut i = 0;
+    c.bench_function("query a strategy server at a Spot", |b| {
+        b.iter(|| {
+            i = (i + 1) % spots.len();
+            server.query(&spots[i])
+        })
+    });
+}
+
 use robopoker::cards::evaluator::Evaluator;
 use robopoker::cards::hand::Hand;
 use robopoker::cards::isomorphism::Isomorphism;
+use robopoker::cards::lookup::LookupEvaluator;
 use robopoker::cards::observation::Observation;
 use robopoker::cards::observations::ObservationIterator;
 use robopoker::cards::street::Street;
 use robopoker::cards::strength::Strength;
+use robopoker::clustering::abstraction::Abstraction;
 use robopoker::clustering::emd::EMD;
 use robopoker::clustering::equity::Equity;
 use robopoker::clustering::heuristic::Heuristic;
 use robopoker::clustering::histogram::Histogram;
+use robopoker::clustering::metric::Metric;
+use robopoker::clustering::pair::Pair;
 use robopoker::clustering::sinkhorn::Sinkhorn;
+use robopoker::mccfr::average::Average;
+use robopoker::mccfr::bucket::Bucket;
+use robopoker::mccfr::edge::Edge;
+use robopoker::mccfr::sampler::Encoding;
+use robopoker::mccfr::server::StrategyServer;
+use robopoker::mccfr::spot::Spot;
 use robopoker::transport::coupling::Coupling;
 use robopoker::Arbitrary;
+use std::collections::BTreeMap;
@@ -1,12 +1,20 @@
+use super::cache::Cache;
+use super::error::ApiError;
+use super::report::ModelReport;
+use super::report::StreetReport;
+use super::summary::SpotSummary;
 use crate::cards::isomorphism::Isomorphism;
 use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
+use crate::clustering::config::ClusterConfig;
 use crate::clustering::histogram::Histogram;
 use crate::clustering::metric::Metric;
 use crate::clustering::pair::Pair;
 use crate::clustering::sinkhorn::Sinkhorn;
+use crate::clustering::store::Store;
 use crate::transport::coupling::Coupling;
+use crate::Chips;
 use crate::Energy;
 use crate::Probability;
 use std::collections::BTreeMap;
@@ -14,40 +22,132 @@ use std::sync::Arc;
 use tokio_postgres::Client;
 use tokio_postgres::Error as E;
 
-pub struct API(Arc<Client>);
+/// the ClusterConfig is the one the database was trained under,
+/// which decides e.g. whether its stored metrics are sparse.
+pub struct API(Arc<Client>, Arc<Cache>, ClusterConfig);
 
 impl API {
     pub async fn new() -> Self {
         log::info!("connecting to db (API)");
-        let (client, connection) = tokio_postgres::Config::default()
-            .port(5432)
-            .host("localhost")
-            .user("postgres")
-            .dbname("robopoker")
-            .password("postgrespassword")
+        Self::connect(Self::config()).await
+    }
+    /// connect to whichever database the config points at
+    pub async fn connect(config: tokio_postgres::Config) -> Self {
+        let (client, connection) = config
             .connect(tokio_postgres::NoTls)
             .await
             .expect("db connection");
         tokio::spawn(connection);
-        Self(Arc::new(client))
+        Self::from(client)
+    }
+    /// the usual libpq PGHOST, PGPORT, PGUSER, PGPASSWORD and
+    /// PGDATABASE environment variables, each falling back to
+    /// the local development database when unset
+    pub fn config() -> tokio_postgres::Config {
+        Self::config_from(|key| std::env::var(key).ok())
+    }
+    /// config() with variables read from lookup rather than
+    /// the environment
+    pub fn config_from(lookup: impl Fn(&str) -> Option<String>) -> tokio_postgres::Config {
+        let var = |key: &str, default: &str| lookup(key).unwrap_or(default.to_string());
+        let port = var("PGPORT", "5432")
+            .parse::<u16>()
+            .expect("PGPORT is a port number");
+        tokio_postgres::Config::default()
+            .host(var("PGHOST", "localhost"))
+            .port(port)
+            .user(var("PGUSER", "postgres"))
+            .password(var("PGPASSWORD", "postgrespassword"))
+            .dbname(var("PGDATABASE", "robopoker"))
+            .to_owned()
+    }
+    /// distances and comparisons only make sense within a street
+    fn matching(left: Street, right: Street) -> Result<Street, ApiError> {
+        match left == right {
+            true => Ok(left),
+            false => Err(ApiError::StreetMismatch { left, right }),
+        }
+    }
+    /// transport between histograms whose supports aren't fully
+    /// connected by the metric would silently price the gaps as
+    /// far apart, so report the first such pair instead
+    fn covered(metric: &Metric, hx: &Histogram, hy: &Histogram) -> Result<(), ApiError> {
+        match metric.missing(hx, hy) {
+            Some((x, y)) => Err(ApiError::MissingDistance(x, y)),
+            None => Ok(()),
+        }
+    }
+
+    /// keep up to capacity encoder lookups in memory, so that
+    /// repeated observations skip the database entirely
+    pub fn with_encode_cache(self, capacity: usize) -> Self {
+        let cache = Cache::default().with_encodings(capacity);
+        Self(self.0, Arc::new(cache), self.2)
+    }
+    /// read the database as trained under this config
+    pub fn with_config(mut self, config: ClusterConfig) -> Self {
+        self.2 = config;
+        self
     }
 
     // global lookups
     pub async fn encode(&self, obs: Observation) -> Result<Abstraction, E> {
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
+        if let Some(abs) = self.1.encoding(iso) {
+            return Ok(abs);
+        }
         const SQL: &'static str = r#"
             SELECT abs
             FROM encoder
             WHERE obs = $1
         "#;
-        Ok(self
+        let abs = self
             .0
             .query_one(SQL, &[&iso])
             .await?
-            .get::<_, i64>(0)
-            .into())
+            .try_get::<_, Abstraction>(0)?;
+        self.1.put_encoding(iso, abs);
+        Ok(abs)
+    }
+    /// learned metric for the street, served from the shared cache.
+    /// every so often we first check whether the database has been
+    /// re-trained, in which case the cache is dropped and refilled.
+    pub async fn metric(&self, street: Street) -> Result<Arc<Metric>, E> {
+        if self.1.due() {
+            match self.version().await {
+                Ok(version) => self.1.observe(version),
+                Err(e) => log::warn!("version check failed, serving cached metric: {}", e),
+            }
+        }
+        match self.1.get(street) {
+            Some(metric) => Ok(metric),
+            None => self.refresh_metric(street).await,
+        }
     }
-    pub async fn metric(&self, street: Street) -> Result<Metric, E> {
+    /// pull the metric from the database and replace whatever was cached
+    pub async fn refresh_metric(&self, street: Street) -> Result<Arc<Metric>, E> {
+        let metric = Arc::new(self.query_metric(street).await?);
+        self.1.put(street, metric.clone());
+        Ok(metric)
+    }
+    /// stamped at upload, changes whenever the database is re-trained.
+    /// databases uploaded before versioning have no version table,
+    /// so we call them version 0 and leave creating it to upload.
+    pub async fn version(&self) -> Result<i64, E> {
+        const SQL: &'static str = r#"
+            SELECT COALESCE(MAX(version), 0)
+            FROM version
+        "#;
+        match self.0.query_one(SQL, &[]).await {
+            Ok(row) => Ok(row.get::<_, i64>(0)),
+            Err(e) if e.code() == Some(&tokio_postgres::error::SqlState::UNDEFINED_TABLE) => {
+                log::info!("no version table, treating as version 0");
+                Ok(0)
+            }
+            Err(e) => Err(e),
+        }
+    }
+    async fn query_metric(&self, street: Street) -> Result<Metric, E> {
         let street = street as i16;
         const SQL: &'static str = r#"
             SELECT
@@ -62,15 +162,28 @@ impl API {
                 a1.street   = $1 AND
                 a1.abs     != a2.abs;
         "#;
-        Ok(self
-            .0
-            .query(SQL, &[&street])
-            .await?
-            .iter()
-            .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
-            .map(|(xor, distance)| (Pair::from(xor), distance))
-            .collect::<BTreeMap<Pair, Energy>>()
-            .into())
+        Ok(Metric::from(
+            self.0
+                .query(SQL, &[&street])
+                .await?
+                .iter()
+                .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
+                .map(|(xor, distance)| (Pair::from(xor), distance))
+                .collect::<BTreeMap<Pair, Energy>>(),
+        )
+        .with_far(self.2.far()))
+    }
+    /// 2D classical MDS embedding of the street's abstractions
+    /// under its learned metric, see Metric::embedding
+    pub async fn embedding(
+        &self,
+        street: Street,
+    ) -> Result<Vec<(Abstraction, (f32, f32))>, ApiError> {
+        if street == Street::Pref {
+            return Err(ApiError::NotFound);
+        }
+        let ref basis = self.abstractions_on(street).await?;
+        Ok(self.metric(street).await?.embedding(basis))
     }
     pub async fn basis(&self, street: Street) -> Result<Vec<Abstraction>, E> {
         let street = street as i16;
@@ -80,14 +193,101 @@ impl API {
             JOIN abstraction a1 ON a2.street = a1.street
             WHERE a1.abs = $1;
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&street])
             .await?
             .iter()
-            .map(|row| row.get::<_, i64>(0))
-            .map(Abstraction::from)
-            .collect())
+            .map(|row| row.try_get::<_, Abstraction>(0))
+            .collect()
+    }
+    pub async fn abstractions_on(&self, street: Street) -> Result<Vec<Abstraction>, E> {
+        let street = street as i16;
+        const SQL: &'static str = r#"
+            SELECT abs
+            FROM abstraction
+            WHERE street = $1
+            ORDER BY abs;
+        "#;
+        self.0
+            .query(SQL, &[&street])
+            .await?
+            .iter()
+            .map(|row| row.try_get::<_, Abstraction>(0))
+            .collect()
+    }
+    pub async fn bucket_sizes(&self, street: Street) -> Result<Vec<(Abstraction, usize)>, E> {
+        let street = street as i16;
+        const SQL: &'static str = r#"
+            SELECT e.abs, COUNT(*) AS n
+            FROM encoder e
+            JOIN abstraction a ON a.abs = e.abs
+            WHERE a.street = $1
+            GROUP BY e.abs
+            ORDER BY n DESC;
+        "#;
+        self.0
+            .query(SQL, &[&street])
+            .await?
+            .iter()
+            .map(|row| Ok((row.try_get::<_, Abstraction>(0)?, row.get::<_, i64>(1))))
+            .map(|row| row.map(|(abs, n)| (abs, n as usize)))
+            .collect()
+    }
+
+    pub async fn abs_centrality_mean(&self, street: Street) -> Result<Probability, E> {
+        let street = street as i16;
+        const SQL: &'static str = r#"
+            SELECT COALESCE(AVG(centrality), 0)::REAL
+            FROM abstraction
+            WHERE street = $1
+        "#;
+        Ok(self.0.query_one(SQL, &[&street]).await?.get::<_, f32>(0))
+    }
+
+    // model health
+    /// per-street coverage and bucket statistics, with every
+    /// street and every component query issued concurrently
+    pub async fn model_report(&self) -> Result<ModelReport, E> {
+        let (pref, flop, turn, rive) = tokio::try_join!(
+            self.street_report(Street::Pref),
+            self.street_report(Street::Flop),
+            self.street_report(Street::Turn),
+            self.street_report(Street::Rive),
+        )?;
+        Ok(ModelReport(vec![pref, flop, turn, rive]))
+    }
+    async fn street_report(&self, street: Street) -> Result<StreetReport, E> {
+        let (buckets, centrality) =
+            tokio::try_join!(self.bucket_sizes(street), self.abs_centrality_mean(street))?;
+        let buckets = buckets.into_iter().map(|(_, n)| n).collect();
+        Ok(StreetReport::from((street, buckets, centrality)))
+    }
+
+    // spot explorer
+    /// bundle of equity, price, and neighborhood for a hero
+    /// observation facing to_call into pot, with stack behind.
+    /// every component query is issued concurrently.
+    pub async fn spot_summary(
+        &self,
+        obs: Observation,
+        pot: Chips,
+        to_call: Chips,
+        stack: Chips,
+    ) -> Result<SpotSummary, E> {
+        let (abstraction, equity, nearby, similar) = tokio::try_join!(
+            self.encode(obs),
+            self.obs_equity(obs),
+            self.obs_nearby(obs),
+            self.obs_similar(obs),
+        )?;
+        Ok(SpotSummary {
+            observation: obs,
+            abstraction,
+            equity,
+            pot_odds: SpotSummary::pot_odds(pot, to_call, stack),
+            nearby,
+            similar,
+        })
     }
 
     // equity calculations
@@ -106,7 +306,7 @@ impl API {
             .into())
     }
     pub async fn obs_equity(&self, obs: Observation) -> Result<Probability, E> {
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
         let sql = if obs.street() == Street::Rive {
             r#"
                 SELECT equity
@@ -129,12 +329,56 @@ impl API {
             .get::<_, f32>(0)
             .into())
     }
+    /// obs_equity() for a batch, in the order asked. observations
+    /// missing from the encoder come back as None.
+    pub async fn obs_equity_many(
+        &self,
+        obs: &[Observation],
+    ) -> Result<Vec<Option<Probability>>, E> {
+        let isos = obs
+            .iter()
+            .map(|o| (o.street() == Street::Rive, Isomorphism::key(*o)))
+            .collect::<Vec<(bool, i64)>>();
+        let rivers = isos.iter().filter(|(r, _)| *r).map(|(_, i)| *i);
+        let others = isos.iter().filter(|(r, _)| !*r).map(|(_, i)| *i);
+        let rivers = rivers.collect::<Vec<i64>>();
+        let others = others.collect::<Vec<i64>>();
+        const RIVER: &'static str = r#"
+            SELECT obs, equity
+            FROM encoder
+            WHERE obs = ANY($1)
+        "#;
+        const OTHER: &'static str = r#"
+            SELECT e.obs, SUM(t.dx * a.equity)
+            FROM transitions t
+            JOIN encoder     e ON e.abs = t.prev
+            JOIN abstraction a ON a.abs = t.next
+            WHERE e.obs = ANY($1)
+            GROUP BY e.obs
+        "#;
+        let river = async { self.0.query(RIVER, &[&rivers]).await };
+        let other = async { self.0.query(OTHER, &[&others]).await };
+        let (rivers, others) = tokio::try_join!(river, other)?;
+        let equities = rivers
+            .iter()
+            .chain(others.iter())
+            .map(|row| (row.get::<_, i64>(0), row.get::<_, f32>(1)))
+            .collect::<BTreeMap<i64, Probability>>();
+        Ok(isos
+            .iter()
+            .map(|(_, iso)| equities.get(iso).copied())
+            .collect())
+    }
 
     // distance calculations
-    pub async fn abs_distance(&self, abs1: Abstraction, abs2: Abstraction) -> Result<Energy, E> {
-        if abs1.street() != abs2.street() {
-            return Err(E::__private_api_timeout());
-        }
+    /// a pair with no metric row, from a sparse metric or a
+    /// partial build, is a MissingDistance rather than a DB error
+    pub async fn abs_distance(
+        &self,
+        abs1: Abstraction,
+        abs2: Abstraction,
+    ) -> Result<Energy, ApiError> {
+        Self::matching(abs1.street(), abs2.street())?;
         if abs1 == abs2 {
             return Ok(0 as Energy);
         }
@@ -144,19 +388,63 @@ impl API {
             FROM metric m
             WHERE $1 = m.xor;
         "#;
-        Ok(self.0.query_one(SQL, &[&xor]).await?.get::<_, Energy>(0))
+        self.0
+            .query_opt(SQL, &[&xor])
+            .await?
+            .map(|row| row.get::<_, Energy>(0))
+            .ok_or(ApiError::MissingDistance(abs1, abs2))
     }
-    pub async fn obs_distance(&self, obs1: Observation, obs2: Observation) -> Result<Energy, E> {
+    pub async fn obs_distance(
+        &self,
+        obs1: Observation,
+        obs2: Observation,
+    ) -> Result<Energy, ApiError> {
         // dob Kd8s~6dJsAc QhQs~QdQcAc
-        if obs1.street() != obs2.street() {
-            return Err(E::__private_api_timeout());
-        }
+        Self::matching(obs1.street(), obs2.street())?;
+        let (ref hx, ref hy, ref metric) = tokio::try_join!(
+            self.obs_histogram(obs1),
+            self.obs_histogram(obs2),
+            self.metric(obs1.street().next())
+        )?;
+        Self::covered(metric, hx, hy)?;
+        Ok(metric.emd_f64(hx, hy))
+    }
+    pub async fn obs_explain(
+        &self,
+        obs1: Observation,
+        obs2: Observation,
+    ) -> Result<Vec<(Abstraction, Probability)>, ApiError> {
+        // attribute transport cost to each next-street abstraction,
+        // then report the mass deltas of the costliest few
+        Self::matching(obs1.street(), obs2.street())?;
         let (ref hx, ref hy, ref metric) = tokio::try_join!(
             self.obs_histogram(obs1),
             self.obs_histogram(obs2),
             self.metric(obs1.street().next())
         )?;
-        Ok(Sinkhorn::from((hx, hy, metric)).minimize().cost())
+        Self::covered(metric, hx, hy)?;
+        let ref plan = Sinkhorn::from((hx, hy, metric.as_ref())).minimize();
+        let outgoing = |x: &Abstraction| hy.support().map(|y| plan.flow(x, y)).sum::<Energy>();
+        let incoming = |y: &Abstraction| hx.support().map(|x| plan.flow(x, y)).sum::<Energy>();
+        let mut explanation = hx
+            .support()
+            .chain(hy.support())
+            .copied()
+            .collect::<std::collections::BTreeSet<Abstraction>>()
+            .into_iter()
+            .map(|a| (a, hx.density(&a) - hy.density(&a)))
+            .map(|(a, dx)| match (hx.density(&a) > 0., hy.density(&a) > 0.) {
+                (true, true) => (a, dx, outgoing(&a) + incoming(&a)),
+                (true, false) => (a, dx, outgoing(&a)),
+                (false, _) => (a, dx, incoming(&a)),
+            })
+            .collect::<Vec<(Abstraction, Probability, Energy)>>();
+        explanation.sort_by(|(_, _, x), (_, _, y)| y.partial_cmp(x).unwrap());
+        Ok(explanation
+            .into_iter()
+            .take(5)
+            .map(|(a, dx, _)| (a, dx))
+            .collect())
     }
 
     // population lookups
@@ -170,7 +458,7 @@ impl API {
         Ok(self.0.query_one(SQL, &[&abs]).await?.get::<_, i32>(0) as usize)
     }
     pub async fn obs_population(&self, obs: Observation) -> Result<usize, E> {
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
         const SQL: &'static str = r#"
             SELECT population
             FROM abstraction
@@ -196,7 +484,7 @@ impl API {
             .into())
     }
     pub async fn obs_centrality(&self, obs: Observation) -> Result<Probability, E> {
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
         const SQL: &'static str = r#"
             SELECT centrality
             FROM abstraction
@@ -212,7 +500,44 @@ impl API {
     }
 
     // histogram aggregation via join
-    pub async fn abs_histogram(&self, abs: Abstraction) -> Result<Histogram, E> {
+    /// read the stored histogram blob if the upload built one,
+    /// otherwise fall back to aggregating the transitions table.
+    pub async fn abs_histogram(&self, abs: Abstraction) -> Result<Histogram, ApiError> {
+        match self.abs_histogram_stored(abs).await {
+            Err(ApiError::NotFound) => Ok(self.abs_histogram_aggregated(abs).await?),
+            result => result,
+        }
+    }
+    /// NotFound only when no blob was uploaded for this abstraction.
+    /// a blob that doesn't decode is Corrupt, not a reason to fall back.
+    async fn abs_histogram_stored(&self, abs: Abstraction) -> Result<Histogram, ApiError> {
+        let idx = i64::from(abs);
+        let mass = abs.street().n_children() as f32;
+        const SQL: &'static str = r#"
+            SELECT blob
+            FROM histogram
+            WHERE abs = $1
+        "#;
+        let blob = self
+            .0
+            .query_opt(SQL, &[&idx])
+            .await?
+            .map(|row| row.get::<_, Vec<u8>>(0))
+            .ok_or(ApiError::NotFound)?;
+        let mut records = blob.chunks_exact(12);
+        if !records.remainder().is_empty() {
+            return Err(ApiError::Corrupt(format!("histogram blob for {}", abs)));
+        }
+        records.try_fold(Histogram::default(), |mut h, record| {
+            let (next, dx) = record.split_at(8);
+            let next = i64::from_be_bytes(next.try_into().expect("8 bytes"));
+            let next = Abstraction::try_from_i64(next).map_err(ApiError::Corrupt)?;
+            let dx = f32::from_be_bytes(dx.try_into().expect("4 bytes"));
+            h.set(next, (dx * mass).round() as usize);
+            Ok(h)
+        })
+    }
+    async fn abs_histogram_aggregated(&self, abs: Abstraction) -> Result<Histogram, E> {
         let idx = i64::from(abs);
         let mass = abs.street().n_children() as f32;
         const SQL: &'static str = r#"
@@ -220,22 +545,49 @@ impl API {
             FROM transitions
             WHERE prev = $1
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&idx])
             .await?
             .iter()
-            .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
-            .map(|(next, dx)| (next, (dx * mass).round() as usize))
-            .map(|(next, dx)| (Abstraction::from(next), dx))
-            .fold(Histogram::default(), |mut h, (next, dx)| {
-                h.set(next, dx);
-                h
-            }))
+            .try_fold(Histogram::default(), |mut h, row| {
+                let next = row.try_get::<_, Abstraction>(0)?;
+                let dx = row.get::<_, Energy>(1);
+                h.set(next, (dx * mass).round() as usize);
+                Ok(h)
+            })
+    }
+    /// probability that prev transitions into next, read from a
+    /// single transitions row. a missing row is an impossible
+    /// transition rather than an error.
+    pub async fn transition_prob(
+        &self,
+        prev: Abstraction,
+        next: Abstraction,
+    ) -> Result<Probability, ApiError> {
+        if prev.street() == Street::Rive || prev.street().next() != next.street() {
+            return Err(ApiError::StreetMismatch {
+                left: prev.street(),
+                right: next.street(),
+            });
+        }
+        let prev = i64::from(prev);
+        let next = i64::from(next);
+        const SQL: &'static str = r#"
+            SELECT dx
+            FROM transitions
+            WHERE prev = $1
+            AND   next = $2
+        "#;
+        Ok(self
+            .0
+            .query_opt(SQL, &[&prev, &next])
+            .await?
+            .map(|row| row.get::<_, Probability>(0))
+            .unwrap_or(0.))
     }
     pub async fn obs_histogram(&self, obs: Observation) -> Result<Histogram, E> {
         // Kd8s~6dJsAc
-        let idx = i64::from(Observation::from(Isomorphism::from(obs)));
+        let idx = Isomorphism::key(obs);
         let mass = obs.street().n_children() as f32;
         const SQL: &'static str = r#"
             SELECT next, dx
@@ -243,24 +595,22 @@ impl API {
             JOIN encoder ON encoder.abs = transitions.prev
             WHERE encoder.obs = $1
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&idx])
             .await?
             .iter()
-            .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
-            .map(|(next, dx)| (next, (dx * mass).round() as usize))
-            .map(|(next, dx)| (Abstraction::from(next), dx))
-            .fold(Histogram::default(), |mut h, (next, dx)| {
-                h.set(next, dx);
-                h
-            }))
+            .try_fold(Histogram::default(), |mut h, row| {
+                let next = row.try_get::<_, Abstraction>(0)?;
+                let dx = row.get::<_, Energy>(1);
+                h.set(next, (dx * mass).round() as usize);
+                Ok(h)
+            })
     }
 
     // observation similarity lookups
     pub async fn obs_similar(&self, obs: Observation) -> Result<Vec<Observation>, E> {
         // 8d8s~6dJs7c
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
         const SQL: &'static str = r#"
             SELECT obs
             FROM encoder
@@ -273,14 +623,12 @@ impl API {
             ORDER BY RANDOM()
             LIMIT 5;
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&iso])
             .await?
             .iter()
-            .map(|row| row.get::<_, i64>(0))
-            .map(Observation::from)
-            .collect())
+            .map(|row| row.try_get::<_, Observation>(0))
+            .collect()
     }
     pub async fn abs_similar(&self, abs: Abstraction) -> Result<Vec<Observation>, E> {
         let abs = i64::from(abs);
@@ -291,17 +639,17 @@ impl API {
             ORDER BY RANDOM()
             LIMIT 5;
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&abs])
             .await?
             .iter()
-            .map(|row| row.get::<_, i64>(0))
-            .map(Observation::from)
-            .collect())
+            .map(|row| row.try_get::<_, Observation>(0))
+            .collect()
     }
 
     // proximity lookups
+    // both inner join on the metric, so pairs without a row are
+    // skipped and a sparse metric just yields fewer neighbors
     pub async fn abs_nearby(&self, abs: Abstraction) -> Result<Vec<(Abstraction, Energy)>, E> {
         let abs = i64::from(abs);
         const SQL: &'static str = r#"
@@ -315,17 +663,15 @@ impl API {
             ORDER BY m.dx ASC
             LIMIT 5;
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&abs])
             .await?
             .iter()
-            .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
-            .map(|(abs, distance)| (Abstraction::from(abs), distance))
-            .collect())
+            .map(|row| Ok((row.try_get::<_, Abstraction>(0)?, row.get::<_, Energy>(1))))
+            .collect()
     }
     pub async fn obs_nearby(&self, obs: Observation) -> Result<Vec<(Abstraction, Energy)>, E> {
-        let iso = i64::from(Observation::from(Isomorphism::from(obs)));
+        let iso = Isomorphism::key(obs);
         const SQL: &'static str = r#"
             SELECT a1.abs, m.dx
             FROM encoder e
@@ -338,19 +684,65 @@ impl API {
             ORDER BY m.dx ASC
             LIMIT 5;
         "#;
-        Ok(self
-            .0
+        self.0
             .query(SQL, &[&iso])
             .await?
             .iter()
-            .map(|row| (row.get::<_, i64>(0), row.get::<_, Energy>(1)))
-            .map(|(abs, distance)| (Abstraction::from(abs), distance))
-            .collect())
+            .map(|row| Ok((row.try_get::<_, Abstraction>(0)?, row.get::<_, Energy>(1))))
+            .collect()
+    }
+}
+
+/// blocking bridge onto the async client, so Postgres can stand in
+/// wherever a Store is expected. this parks a runtime worker for the
+/// duration of the query, so it needs the multi-threaded runtime.
+impl Store for API {
+    fn abstraction(&self, iso: &Isomorphism) -> Option<Abstraction> {
+        let query = self.encode(iso.0);
+        tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(query)).ok()
+    }
+    fn abstractions(&self, street: Street) -> Vec<Abstraction> {
+        let query = self.abstractions_on(street);
+        tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(query))
+            .expect("query street abstractions")
     }
 }
 
 impl From<Client> for API {
     fn from(client: Client) -> Self {
-        Self(Arc::new(client))
+        Self(
+            Arc::new(client),
+            Arc::new(Cache::default()),
+            ClusterConfig::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::config::Host;
+
+    #[test]
+    fn config_reads_variables() {
+        let vars = BTreeMap::from([("PGHOST", "db.example.com"), ("PGPORT", "6543")]);
+        let config = API::config_from(|key| vars.get(key).map(|v| v.to_string()));
+        assert!(config.get_hosts() == [Host::Tcp("db.example.com".to_string())]);
+        assert!(config.get_ports() == [6543]);
+        assert!(config.get_dbname() == Some("robopoker"));
+    }
+
+    #[test]
+    fn mismatched_streets() {
+        let flop = Abstraction::from((Street::Flop, 0));
+        let turn = Abstraction::from((Street::Turn, 0));
+        assert!(API::matching(flop.street(), flop.street()).is_ok());
+        assert!(matches!(
+            API::matching(flop.street(), turn.street()),
+            Err(ApiError::StreetMismatch {
+                left: Street::Flop,
+                right: Street::Turn
+            })
+        ));
     }
 }
@@ -0,0 +1,139 @@
+use super::lru::Lru;
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use crate::clustering::metric::Metric;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// in-memory learned Metric per Street, shared across API requests.
+///
+/// metrics are expensive to pull and only change when the database
+/// is re-trained. if an interval is configured, callers are asked to
+/// check the database version at most once per interval, and every
+/// cached street is dropped when that version moves. with no interval
+/// we never check, and cached metrics live as long as the server.
+///
+/// encoder lookups can optionally be kept in a bounded LRU keyed by
+/// canonical isomorphism. abstractions never change within a trained
+/// version, so these are only dropped alongside the metrics.
+pub struct Cache {
+    interval: Option<Duration>,
+    state: Mutex<State>,
+    encodings: Option<Lru<i64, Abstraction>>,
+}
+
+struct State {
+    version: Option<i64>,
+    checked: Instant,
+    metrics: HashMap<Street, Arc<Metric>>,
+}
+
+impl Cache {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            state: Mutex::new(State {
+                version: None,
+                checked: Instant::now(),
+                metrics: HashMap::new(),
+            }),
+            encodings: None,
+        }
+    }
+    /// opt in to caching up to capacity encoder lookups
+    pub fn with_encodings(mut self, capacity: usize) -> Self {
+        self.encodings = Some(Lru::new(capacity));
+        self
+    }
+    pub fn encoding(&self, iso: i64) -> Option<Abstraction> {
+        self.encodings.as_ref().and_then(|lru| lru.get(&iso))
+    }
+    pub fn put_encoding(&self, iso: i64, abs: Abstraction) {
+        if let Some(lru) = self.encodings.as_ref() {
+            lru.put(iso, abs);
+        }
+    }
+    pub fn get(&self, street: Street) -> Option<Arc<Metric>> {
+        self.state.lock().unwrap().metrics.get(&street).cloned()
+    }
+    pub fn put(&self, street: Street, metric: Arc<Metric>) {
+        self.state.lock().unwrap().metrics.insert(street, metric);
+    }
+    /// whether enough time has passed that the version should be checked.
+    /// always true before the first check so that we learn a baseline.
+    pub fn due(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        match self.interval {
+            None => false,
+            Some(_) if state.version.is_none() => true,
+            Some(interval) => state.checked.elapsed() >= interval,
+        }
+    }
+    /// record the latest database version, invalidating
+    /// every cached street if it differs from the last one seen.
+    pub fn observe(&self, version: i64) {
+        let mut state = self.state.lock().unwrap();
+        if state.version.is_some_and(|v| v != version) {
+            log::info!("database version changed, dropping cached metrics");
+            state.metrics.clear();
+            if let Some(lru) = self.encodings.as_ref() {
+                lru.clear();
+            }
+        }
+        state.version = Some(version);
+        state.checked = Instant::now();
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new(crate::ANALYSIS_VERSION_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidates_on_version_change() {
+        let cache = Cache::new(Some(Duration::ZERO));
+        cache.observe(1);
+        cache.put(Street::Flop, Arc::new(Metric::default()));
+        cache.observe(1);
+        assert!(cache.get(Street::Flop).is_some());
+        cache.observe(2);
+        assert!(cache.get(Street::Flop).is_none());
+    }
+
+    #[test]
+    fn encodings_opt_in() {
+        let ref abs = Abstraction::from(0.5);
+        let plain = Cache::new(None);
+        plain.put_encoding(7, *abs);
+        assert!(plain.encoding(7).is_none());
+        let cached = Cache::new(Some(Duration::ZERO)).with_encodings(16);
+        cached.observe(1);
+        cached.put_encoding(7, *abs);
+        assert!(cached.encoding(7) == Some(*abs));
+        cached.observe(2);
+        assert!(cached.encoding(7).is_none());
+    }
+
+    #[test]
+    fn never_due_without_interval() {
+        let cache = Cache::new(None);
+        assert!(!cache.due());
+        assert!(Cache::new(Some(Duration::from_secs(3600))).due());
+    }
+
+    #[test]
+    fn version_zero_is_a_baseline() {
+        let cache = Cache::new(Some(Duration::from_secs(3600)));
+        cache.observe(0);
+        assert!(!cache.due());
+    }
+}
@@ -12,17 +12,7 @@ pub struct CLI(API);
 impl CLI {
     pub async fn new() -> Self {
         log::info!("connecting to db (CLI)");
-        let (client, connection) = tokio_postgres::Config::default()
-            .port(5432)
-            .host("localhost")
-            .user("postgres")
-            .dbname("robopoker")
-            .password("postgrespassword")
-            .connect(tokio_postgres::NoTls)
-            .await
-            .expect("db connection");
-        tokio::spawn(connection);
-        Self(API::from(client))
+        Self(API::connect(API::config()).await)
     }
 
     pub async fn run(&self) -> () {
@@ -183,6 +173,47 @@ impl CLI {
                 }
                 Err("invalid histogram target".into())
             }
+
+            Query::Analyze { target, json } => {
+                if let Ok(obs) = Observation::try_from(target.as_str()) {
+                    let analysis = self.analyze(obs).await;
+                    let analysis = match json {
+                        true => analysis.to_string(),
+                        false => format!("{:#}", analysis),
+                    };
+                    return Ok(println!("{}", analysis));
+                }
+                Err("invalid analysis target".into())
+            }
         }
     }
+
+    /// run every lookup concurrently. failed lookups
+    /// become nulls rather than aborting the whole dump.
+    async fn analyze(&self, obs: Observation) -> serde_json::Value {
+        let (abstraction, equity, population, centrality, histogram, nearby) = tokio::join!(
+            self.0.encode(obs),
+            self.0.obs_equity(obs),
+            self.0.obs_population(obs),
+            self.0.obs_centrality(obs),
+            self.0.obs_histogram(obs),
+            self.0.obs_nearby(obs),
+        );
+        serde_json::json!({
+            "observation": obs.to_string(),
+            "abstraction": abstraction.ok().map(|abs| abs.to_string()),
+            "equity": equity.ok(),
+            "population": population.ok(),
+            "centrality": centrality.ok(),
+            "histogram": histogram.ok().map(|h| h
+                .distribution()
+                .into_iter()
+                .map(|(abs, p)| serde_json::json!({ "abstraction": abs.to_string(), "density": p }))
+                .collect::<Vec<_>>()),
+            "nearby": nearby.ok().map(|n| n
+                .into_iter()
+                .map(|(abs, d)| serde_json::json!({ "abstraction": abs.to_string(), "distance": d }))
+                .collect::<Vec<_>>()),
+        })
+    }
 }
@@ -0,0 +1,44 @@
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use tokio_postgres::Error as E;
+
+/// failure to answer an API query. only Db is a genuine
+/// database failure; the rest mean the query itself can't
+/// be answered, so callers can report them to the user
+/// rather than treating them like a broken connection.
+#[derive(Debug)]
+pub enum ApiError {
+    Db(E),
+    /// the query compares or connects streets that don't fit together
+    StreetMismatch {
+        left: Street,
+        right: Street,
+    },
+    /// a sparse metric or a partial build leaves some pairs
+    /// without a row in the metric table
+    MissingDistance(Abstraction, Abstraction),
+    /// nothing stored for this query, e.g. preflop has no metric
+    NotFound,
+    /// something stored for this query, but it doesn't decode
+    Corrupt(String),
+}
+
+impl From<E> for ApiError {
+    fn from(e: E) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "{}", e),
+            Self::StreetMismatch { left, right } => write!(f, "street mismatch {} {}", left, right),
+            Self::MissingDistance(a, b) => write!(f, "distance unavailable {} {}", a, b),
+            Self::NotFound => write!(f, "not found"),
+            Self::Corrupt(what) => write!(f, "corrupt {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
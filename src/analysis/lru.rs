@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// bounded least-recently-used map, safe to share across tasks.
+///
+/// every access stamps its key with a fresh tick, and the key
+/// with the oldest tick is evicted once capacity is exceeded.
+pub struct Lru<K, V> {
+    capacity: usize,
+    state: Mutex<State<K, V>>,
+}
+
+struct State<K, V> {
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+    recency: BTreeMap<u64, K>,
+}
+
+impl<K, V> Lru<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "lru capacity must be positive");
+        Self {
+            capacity,
+            state: Mutex::new(State {
+                tick: 0,
+                entries: HashMap::with_capacity(capacity),
+                recency: BTreeMap::new(),
+            }),
+        }
+    }
+    pub fn get(&self, key: &K) -> Option<V> {
+        let ref mut state = *self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        let (value, stamp) = state.entries.get_mut(key)?;
+        let value = value.clone();
+        let prior = std::mem::replace(stamp, tick);
+        state.recency.remove(&prior);
+        state.recency.insert(tick, key.clone());
+        Some(value)
+    }
+    pub fn put(&self, key: K, value: V) {
+        let ref mut state = *self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some((_, prior)) = state.entries.insert(key.clone(), (value, tick)) {
+            state.recency.remove(&prior);
+        }
+        state.recency.insert(tick, key);
+        if state.entries.len() > self.capacity {
+            let (_, oldest) = state.recency.pop_first().expect("non empty");
+            state.entries.remove(&oldest);
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().entries.is_empty()
+    }
+    pub fn clear(&self) {
+        let ref mut state = *self.state.lock().unwrap();
+        state.entries.clear();
+        state.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recent() {
+        let lru = Lru::new(2);
+        lru.put(1, 'a');
+        lru.put(2, 'b');
+        assert!(lru.get(&1) == Some('a'));
+        lru.put(3, 'c');
+        assert!(lru.get(&2) == None);
+        assert!(lru.get(&1) == Some('a'));
+        assert!(lru.get(&3) == Some('c'));
+        assert!(lru.len() == 2);
+    }
+
+    #[test]
+    fn empties_on_clear() {
+        let lru = Lru::new(2);
+        assert!(lru.is_empty());
+        lru.put(1, 'a');
+        assert!(!lru.is_empty());
+        lru.clear();
+        assert!(lru.is_empty());
+        assert!(lru.get(&1) == None);
+    }
+}
@@ -1,4 +1,9 @@
 pub mod api;
+pub mod cache;
 pub mod cli;
+pub mod error;
+pub mod lru;
 pub mod query;
+pub mod report;
+pub mod summary;
 pub mod upload;
@@ -76,4 +76,15 @@ pub enum Query {
         #[arg(required = true)]
         target: String,
     },
+
+    #[command(
+        about = "Dump the full analysis of any given observation",
+        alias = "all"
+    )]
+    Analyze {
+        #[arg(required = true)]
+        target: String,
+        #[arg(long, help = "emit a single JSON object")]
+        json: bool,
+    },
 }
@@ -0,0 +1,92 @@
+use crate::cards::street::Street;
+use crate::Probability;
+
+/// health of the trained abstraction on one street
+#[derive(Debug, Clone)]
+pub struct StreetReport {
+    pub street: Street,
+    pub abstractions: usize,
+    pub isomorphisms: usize,
+    pub bucket_mean: f32,
+    pub bucket_variance: f32,
+    pub centrality_mean: Probability,
+}
+
+impl From<(Street, Vec<usize>, Probability)> for StreetReport {
+    fn from((street, buckets, centrality_mean): (Street, Vec<usize>, Probability)) -> Self {
+        let abstractions = buckets.len();
+        let isomorphisms = buckets.iter().sum::<usize>();
+        let n = abstractions.max(1) as f32;
+        let bucket_mean = isomorphisms as f32 / n;
+        let bucket_variance = buckets
+            .iter()
+            .map(|&size| size as f32 - bucket_mean)
+            .map(|delta| delta * delta)
+            .sum::<f32>()
+            / n;
+        Self {
+            street,
+            abstractions,
+            isomorphisms,
+            bucket_mean,
+            bucket_variance,
+            centrality_mean,
+        }
+    }
+}
+
+impl From<&StreetReport> for serde_json::Value {
+    fn from(report: &StreetReport) -> Self {
+        serde_json::json!({
+            "street": report.street.to_string(),
+            "abstractions": report.abstractions,
+            "isomorphisms": report.isomorphisms,
+            "bucket_mean": report.bucket_mean,
+            "bucket_variance": report.bucket_variance,
+            "centrality_mean": report.centrality_mean,
+        })
+    }
+}
+
+/// one StreetReport per street, the first sanity check after training
+#[derive(Debug, Clone)]
+pub struct ModelReport(pub Vec<StreetReport>);
+
+impl From<&ModelReport> for serde_json::Value {
+    fn from(report: &ModelReport) -> Self {
+        serde_json::Value::Array(report.0.iter().map(serde_json::Value::from).collect())
+    }
+}
+
+impl std::fmt::Display for ModelReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for r in self.0.iter() {
+            writeln!(
+                f,
+                "{:<8} {:>6} abs {:>12} iso  bucket {:>12.1} ± {:<12.1} centrality {:.4}",
+                r.street,
+                r.abstractions,
+                r.isomorphisms,
+                r.bucket_mean,
+                r.bucket_variance.sqrt(),
+                r.centrality_mean,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_moments() {
+        let report = StreetReport::from((Street::Flop, vec![1, 3, 5], 0.5));
+        assert!(report.abstractions == 3);
+        assert!(report.isomorphisms == 9);
+        assert!(report.bucket_mean == 3.);
+        assert!((report.bucket_variance - 8. / 3.).abs() < 1e-6);
+        assert!(serde_json::Value::from(&report)["isomorphisms"] == 9);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cards::observation::Observation;
+use crate::clustering::abstraction::Abstraction;
+use crate::Chips;
+use crate::Energy;
+use crate::Probability;
+
+/// everything the "analyze this spot" panel shows about one
+/// hero observation facing a bet: where it lands in the
+/// abstraction, how it fares, what price it's getting, and
+/// which abstractions and observations look like it.
+#[derive(Debug, Clone)]
+pub struct SpotSummary {
+    pub observation: Observation,
+    pub abstraction: Abstraction,
+    pub equity: Probability,
+    pub pot_odds: Probability,
+    pub nearby: Vec<(Abstraction, Energy)>,
+    pub similar: Vec<Observation>,
+}
+
+impl SpotSummary {
+    /// equity needed to call profitably: the call over the pot
+    /// after calling, where pot already includes the bet we face.
+    /// the call is capped by our stack and any uncalled excess is
+    /// left out, matching Game::continue_threshold.
+    pub fn pot_odds(pot: Chips, to_call: Chips, stack: Chips) -> Probability {
+        let call = to_call.min(stack);
+        match call {
+            0 => 0.,
+            _ => call as Probability / (pot - to_call + call + call) as Probability,
+        }
+    }
+    /// calling is +EV on raw equity alone
+    pub fn is_profitable_call(&self) -> bool {
+        self.equity >= self.pot_odds
+    }
+}
+
+impl From<&SpotSummary> for serde_json::Value {
+    fn from(summary: &SpotSummary) -> Self {
+        serde_json::json!({
+            "observation": summary.observation.to_string(),
+            "abstraction": summary.abstraction.to_string(),
+            "equity": summary.equity,
+            "pot_odds": summary.pot_odds,
+            "nearby": summary
+                .nearby
+                .iter()
+                .map(|(abs, distance)| serde_json::json!({
+                    "abstraction": abs.to_string(),
+                    "distance": distance,
+                }))
+                .collect::<Vec<serde_json::Value>>(),
+            "similar": summary
+                .similar
+                .iter()
+                .map(|obs| obs.to_string())
+                .collect::<Vec<String>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::game::Game;
+
+    #[test]
+    fn pot_odds_matches_game() {
+        let game = Game::root();
+        let pot = game.pot();
+        let to_call = game.to_call();
+        let stack = game.to_shove();
+        assert!(SpotSummary::pot_odds(pot, to_call, stack) == game.continue_threshold());
+        assert!(SpotSummary::pot_odds(10, 0, 100) == 0.);
+        assert!(SpotSummary::pot_odds(10, 5, 100) == 1. / 3.);
+        assert!(SpotSummary::pot_odds(10, 5, 2) == 2. / 9.);
+    }
+}
@@ -1,3 +1,4 @@
+use super::api::API;
 use std::sync::Arc;
 use tokio_postgres::Client;
 use tokio_postgres::Error as E;
@@ -7,12 +8,7 @@ pub struct Upload(Arc<Client>);
 impl Upload {
     pub async fn new() -> Self {
         log::info!("connecting to db (Upload)");
-        let (client, connection) = tokio_postgres::Config::default()
-            .port(5432)
-            .host("localhost")
-            .user("postgres")
-            .dbname("robopoker")
-            .password("postgrespassword")
+        let (client, connection) = API::config()
             .connect(tokio_postgres::NoTls)
             .await
             .expect("db connection");
@@ -36,7 +32,9 @@ impl Upload {
             db.copy_blueprint().await?;
             db.copy_abstraction().await?;
             db.copy_transitions().await?;
+            db.copy_histograms().await?;
             db.copy_streets().await?;
+            db.copy_version().await?;
             Ok(())
         }
     }
@@ -95,6 +93,13 @@ impl Upload {
                 next       BIGINT,
                 dx         REAL
             );
+            CREATE TABLE IF NOT EXISTS histogram   (
+                abs        BIGINT,
+                blob       BYTEA
+            );
+            CREATE TABLE IF NOT EXISTS version     (
+                version    BIGINT
+            );
             CREATE TABLE IF NOT EXISTS blueprint   (
                 edge       BIGINT,
                 past       BIGINT,
@@ -119,6 +124,8 @@ impl Upload {
             TRUNCATE TABLE transitions;
             TRUNCATE TABLE street;
             TRUNCATE TABLE blueprint;
+            TRUNCATE TABLE version;
+            TRUNCATE TABLE histogram;
         "#,
             )
             .await?)
@@ -135,6 +142,7 @@ impl Upload {
             ALTER TABLE transitions  SET UNLOGGED;
             ALTER TABLE street       SET UNLOGGED;
             ALTER TABLE blueprint    SET UNLOGGED;
+            ALTER TABLE histogram    SET UNLOGGED;
         "#,
             )
             .await?)
@@ -219,6 +227,18 @@ impl Upload {
             .await?)
     }
 
+    async fn copy_version(&self) -> Result<(), E> {
+        Ok(self
+            .0
+            .batch_execute(
+                r#"
+            INSERT INTO version (version) VALUES
+                (EXTRACT(EPOCH FROM NOW())::BIGINT);
+        "#,
+            )
+            .await?)
+    }
+
     async fn copy_transitions(&self) -> Result<(), E> {
         let path = self.path();
         Ok(self.0.batch_execute(format!(r#"
@@ -232,6 +252,24 @@ impl Upload {
         "#, path, path, path, path).as_str()).await?)
     }
 
+    /// pack each abstraction's transitions into one blob of
+    /// big-endian (next: i64, dx: f32) records, so that
+    /// API::abs_histogram reads a single row instead of aggregating.
+    async fn copy_histograms(&self) -> Result<(), E> {
+        Ok(self
+            .0
+            .batch_execute(
+                r#"
+            INSERT INTO histogram (abs, blob)
+                SELECT prev, string_agg(int8send(next) || float4send(dx), ''::bytea ORDER BY next)
+                FROM transitions
+                GROUP BY prev;
+            CREATE INDEX IF NOT EXISTS idx_histogram_abs ON histogram (abs);
+        "#,
+            )
+            .await?)
+    }
+
     async fn copy_blueprint(&self) -> Result<(), E> {
         let path = self.path();
         Ok(self.0.batch_execute(format!(r#"
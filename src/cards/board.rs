@@ -1,6 +1,9 @@
 use super::card::Card;
 use super::hand::Hand;
+use super::hands::HandIterator;
+use super::hole::Hole;
 use super::street::Street;
+use super::strength::Strength;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Board(Hand);
@@ -28,6 +31,16 @@ impl Board {
             _ => panic!("Invalid board size"),
         }
     }
+    /// the best hand any player could hold on this board,
+    /// along with the hole cards that make it. ties go to
+    /// whichever hole is enumerated first.
+    pub fn nuts(&self) -> (Strength, Hole) {
+        assert!(self.street() != Street::Pref);
+        HandIterator::from((2, self.0))
+            .map(|hole| (Strength::from(Hand::add(self.0, hole)), Hole::from(hole)))
+            .reduce(|best, next| if next.0 > best.0 { next } else { best })
+            .expect("cards remain in deck")
+    }
 }
 
 /// Board isomorphism
@@ -59,3 +72,40 @@ impl std::fmt::Display for Board {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nuts(board: &str) -> (Strength, Hole) {
+        Board::from(Hand::try_from(board).unwrap()).nuts()
+    }
+    fn made(board: &str, hole: &str) -> Strength {
+        let board = Hand::try_from(board).unwrap();
+        let hole = Hand::try_from(hole).unwrap();
+        Strength::from(Hand::add(board, hole))
+    }
+
+    #[test]
+    fn nuts_straight_flush() {
+        let board = "9h Th Jh 2c 3d";
+        let (strength, hole) = nuts(board);
+        assert!(hole == Hole::try_from("Kh Qh").unwrap());
+        assert!(strength == made(board, "Kh Qh"));
+    }
+
+    #[test]
+    fn nuts_quads() {
+        let board = "As Ad 7c";
+        let (strength, hole) = nuts(board);
+        assert!(hole == Hole::try_from("Ac Ah").unwrap());
+        assert!(strength == made(board, "Ac Ah"));
+    }
+
+    #[test]
+    fn nuts_excludes_board() {
+        let board = Hand::try_from("Kd 8s 4c 2h").unwrap();
+        let (_, hole) = Board::from(board).nuts();
+        assert!(u64::from(Hand::from(hole)) & u64::from(board) == 0);
+    }
+}
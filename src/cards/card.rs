@@ -84,11 +84,18 @@ impl TryFrom<&str> for Card {
                 let suit = Suit::try_from(&s.trim()[1..2])?;
                 Ok(Card::from((rank, suit)))
             }
-            _ => Err("2 characters".into()),
+            _ => Err(format!("card needs 2 characters: {}", s)),
         }
     }
 }
 
+impl std::str::FromStr for Card {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +120,14 @@ mod tests {
         let card = Deck::new().draw();
         assert!(card == Card::from(u32::from(card)));
     }
+
+    #[test]
+    fn bijective_str() {
+        let card = Deck::new().draw();
+        assert!(card == card.to_string().parse().unwrap());
+        assert!("Ts".parse::<Card>().unwrap() == Card::from((Rank::Ten, Suit::S)));
+        assert!("T".parse::<Card>().is_err());
+        assert!("Tx".parse::<Card>().is_err());
+        assert!("1s".parse::<Card>().is_err());
+    }
 }
@@ -0,0 +1,103 @@
+use super::ranking::Ranking;
+
+/// A poker hand's made-hand category.
+///
+/// This drops every rank from Ranking, so two hands compare
+/// equal whenever they make the same kind of hand. Useful as
+/// a coarse key for bucketing boards and hands.
+#[cfg(feature = "shortdeck")]
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOAK,
+    Straight,
+    FullHouse,
+    Flush,
+    FourOAK,
+    StraightFlush,
+}
+#[cfg(not(feature = "shortdeck"))]
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOAK,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOAK,
+    StraightFlush,
+}
+
+impl From<Ranking> for HandCategory {
+    fn from(ranking: Ranking) -> Self {
+        match ranking {
+            Ranking::HighCard(_) => HandCategory::HighCard,
+            Ranking::OnePair(_) => HandCategory::OnePair,
+            Ranking::TwoPair(_, _) => HandCategory::TwoPair,
+            Ranking::ThreeOAK(_) => HandCategory::ThreeOAK,
+            Ranking::Straight(_) => HandCategory::Straight,
+            Ranking::Flush(_) => HandCategory::Flush,
+            Ranking::FullHouse(_, _) => HandCategory::FullHouse,
+            Ranking::FourOAK(_) => HandCategory::FourOAK,
+            Ranking::StraightFlush(_) => HandCategory::StraightFlush,
+            Ranking::MAX => unreachable!(),
+        }
+    }
+}
+
+impl std::fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HandCategory::HighCard => write!(f, "HighCard"),
+            HandCategory::OnePair => write!(f, "OnePair"),
+            HandCategory::TwoPair => write!(f, "TwoPair"),
+            HandCategory::ThreeOAK => write!(f, "ThreeOfAKind"),
+            HandCategory::Straight => write!(f, "Straight"),
+            HandCategory::Flush => write!(f, "Flush"),
+            HandCategory::FullHouse => write!(f, "FullHouse"),
+            HandCategory::FourOAK => write!(f, "FourOfAKind"),
+            HandCategory::StraightFlush => write!(f, "StraightFlush"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::hand::Hand;
+    use crate::cards::strength::Strength;
+
+    fn category(hand: &str) -> HandCategory {
+        Strength::from(Hand::try_from(hand).unwrap()).category()
+    }
+
+    #[test]
+    fn ignores_ranks() {
+        assert!(category("As Ah Kd Qc Js") == category("2s 2h 7d 8c 9s"));
+        assert!(category("As Ah Kd Kc Js") == category("3s 3h 2d 2c 9s"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn hand_ranking_order() {
+        assert!(category("As Kh 9d 7c 4s") < category("2s 2h 7d 8c 9s"));
+        assert!(category("2s 2h 7d 7c 9s") < category("2s 2h 2d 8c 9s"));
+        assert!(category("2s 2h 2d 8c 9s") < category("5s 6h 7d 8c 9s"));
+        assert!(category("5s 6h 7d 8c 9s") < category("2s 4s 7s 8s 9s"));
+        assert!(category("2s 4s 7s 8s 9s") < category("9s 9h 9d 2c 2s"));
+        assert!(category("9s 9h 9d 2c 2s") < category("2s 2h 2d 2c 9s"));
+        assert!(category("2s 2h 2d 2c 9s") < category("5s 6s 7s 8s 9s"));
+    }
+
+    #[test]
+    fn compares_by_category_only() {
+        let lo = Strength::from(Hand::try_from("2s 2h 7d 8c 9s").unwrap());
+        let hi = Strength::from(Hand::try_from("As Ah Kd Qc Js").unwrap());
+        assert!(lo < hi);
+        assert!(lo.cmp_category(&hi) == std::cmp::Ordering::Equal);
+    }
+}
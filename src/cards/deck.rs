@@ -11,32 +11,56 @@ impl Deck {
     pub fn new() -> Self {
         Self(Hand::from(Hand::mask()))
     }
+    /// a full deck with these cards already dealt, e.g. the
+    /// hole cards we want to run a board out against
+    pub fn except(dead: Hand) -> Self {
+        Self(Hand::from(!u64::from(dead) & Hand::mask()))
+    }
+    /// every card dealt so far, or excluded up front
+    pub fn dead(&self) -> Hand {
+        self.0.complement()
+    }
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
 
     /// remove a random card from the deck.
     /// different from Hand::draw() since that removes
     /// highest card deterministically
     pub fn draw(&mut self) -> Card {
-        let ref mut rng = rand::thread_rng();
+        self.draw_with(&mut crate::rng())
+    }
+    /// draw(), but from a caller's rng, so a seeded
+    /// SmallRng deals the same cards every time
+    pub fn draw_with(&mut self, rng: &mut impl Rng) -> Card {
         let n = self.0.size();
-        let i = rng.gen_range(0..n as u8);
-        let mut ones = 0u8;
+        assert!(n > 0, "draw from an empty deck");
+        let i = rng.gen_range(0..n);
         let mut deck = u64::from(self.0);
-        let mut card = u64::from(self.0).trailing_zeros() as u8;
-        while ones < i {
-            card = deck.trailing_zeros() as u8;
+        for _ in 0..i {
             deck = deck & (deck - 1);
-            ones = ones + 1;
         }
-        let card = Card::from(card);
+        let card = Card::from(deck.trailing_zeros() as u8);
         self.0.remove(card);
         card
     }
+    /// deal n cards, none of which are dead
+    pub fn deal_with(&mut self, n: usize, rng: &mut impl Rng) -> Hand {
+        (0..n)
+            .map(|_| self.draw_with(rng))
+            .fold(Hand::empty(), |h, c| Hand::add(h, Hand::from(c)))
+    }
+    /// every remaining card in a uniformly random order.
+    /// the deck itself is untouched, since dealing from it
+    /// is already a uniform draw without replacement.
+    pub fn shuffle(&self, rng: &mut impl Rng) -> Vec<Card> {
+        let mut deck = *self;
+        (0..deck.size()).map(|_| deck.draw_with(rng)).collect()
+    }
 
     /// only needed for Flop, but the creation of a Hand is well-generalized
     pub fn deal(&mut self, street: Street) -> Hand {
-        (0..street.n_revealed())
-            .map(|_| self.draw())
-            .fold(Hand::empty(), |h, c| Hand::add(h, Hand::from(c)))
+        self.deal_with(street.n_revealed(), &mut crate::rng())
     }
 
     /// remove two cards from the deck
@@ -58,3 +82,52 @@ impl From<Hand> for Deck {
         Self(hand)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let deck = Deck::new();
+        let cards = deck.shuffle(rng);
+        let hand = cards
+            .iter()
+            .copied()
+            .map(Hand::from)
+            .fold(Hand::empty(), Hand::add);
+        assert!(cards.len() == Hand::mask().count_ones() as usize);
+        assert!(hand == Hand::from(deck));
+        assert!(cards != deck.shuffle(rng));
+        assert!(cards == deck.shuffle(&mut SmallRng::seed_from_u64(0)));
+    }
+
+    #[test]
+    fn deal_never_repeats() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let hole = Hand::try_from("As Kd").unwrap();
+        let mut deck = Deck::except(hole);
+        let mut dealt = hole;
+        while deck.size() >= 5 {
+            let board = deck.deal_with(5, rng);
+            assert!(board.size() == 5);
+            dealt = Hand::add(dealt, board);
+            assert!(deck.dead() == dealt);
+        }
+    }
+
+    #[test]
+    fn draws_every_card() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let deck = Deck::from(Hand::try_from("As Kd Qh").unwrap());
+        let mut seen = Hand::empty();
+        for _ in 0..64 {
+            let mut copy = deck;
+            seen = Hand::from(u64::from(seen) | u64::from(copy.draw_with(rng)));
+        }
+        assert!(seen == Hand::from(deck));
+    }
+}
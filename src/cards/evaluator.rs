@@ -4,29 +4,34 @@ use super::rank::Rank;
 use super::ranking::Ranking;
 use super::suit::Suit;
 
-#[cfg(not(feature = "shortdeck"))]
 const WHEEL: u16 = 0b_1000000001111;
-#[cfg(not(feature = "shortdeck"))]
 const LOWEST_STRAIGHT_RANK: Rank = Rank::Five;
 
-#[cfg(feature = "shortdeck")]
-const LOWEST_STRAIGHT_RANK: Rank = Rank::Nine;
-#[cfg(feature = "shortdeck")]
-const WHEEL: u16 = 0b_1000011110000;
+const SHORTDECK_WHEEL: u16 = 0b_1000011110000;
+const SHORTDECK_LOWEST_STRAIGHT_RANK: Rank = Rank::Nine;
+
+/// one bit at the bottom of each of the thirteen rank nibbles
+const NIBBLES: u64 = 0x0001111111111111;
 
 /// A lazy evaluator for a hand's strength.
 ///
 /// Using a compact representation of the Hand, we search for
 /// the highest Value hand using bitwise operations. I should
 /// benchmark this and compare to a massive HashMap<Hand, Value> lookup implementation.
-pub struct Evaluator(Hand);
+///
+/// the flag plays the ace low under short deck rules, A6789,
+/// which is what the shortdeck feature builds by default.
+pub struct Evaluator(Hand, bool);
 impl From<Hand> for Evaluator {
     fn from(h: Hand) -> Self {
-        Self(h)
+        Self(h, cfg!(feature = "shortdeck"))
     }
 }
 
 impl Evaluator {
+    /// the best Ranking these cards make. fewer than five cards
+    /// can still pair, trip, or quad up, but never straighten or
+    /// flush. an empty Hand makes the weakest possible HighCard.
     pub fn find_ranking(&self) -> Ranking {
         None.or_else(|| self.find_flush())
             .or_else(|| self.find_4_oak())
@@ -36,8 +41,11 @@ impl Evaluator {
             .or_else(|| self.find_2_oak_2_oak())
             .or_else(|| self.find_2_oak())
             .or_else(|| self.find_1_oak())
-            .expect("at least one card in Hand")
+            .unwrap_or(Ranking::HighCard(Rank::default()))
     }
+    /// the highest ranks outside the made hand, up to as many as
+    /// the Ranking allows. short Hands fill fewer kickers, and a
+    /// missing kicker loses to any present one.
     pub fn find_kickers(&self, value: Ranking) -> Kickers {
         let n = match value {
             Ranking::FourOAK(_) | Ranking::TwoPair(_, _) => 1,
@@ -54,7 +62,7 @@ impl Evaluator {
             | Ranking::FourOAK(hi) => u16::from(hi),
             _ => unreachable!(),
         };
-        let mut bits = u16::from(self.0) & mask;
+        let mut bits = u16::from(self.0) & !mask;
         while bits.count_ones() > n {
             bits &= !(1 << bits.trailing_zeros());
         }
@@ -84,7 +92,9 @@ impl Evaluator {
     }
     fn find_3_oak_2_oak(&self) -> Option<Ranking> {
         self.find_rank_of_n_oak(3).and_then(|trips| {
-            self.find_rank_of_n_oak_under(2, Some(trips))
+            let rest = u64::from(self.0) & !(0xF << (4 * u8::from(trips)));
+            Self(Hand::from(rest), self.1)
+                .find_rank_of_n_oak(2)
                 .map(|pairs| Ranking::FullHouse(trips, pairs))
         })
     }
@@ -103,8 +113,15 @@ impl Evaluator {
         })
     }
 
+    /// the evaluator short deck rules use, whatever the build
+    pub fn shortdeck(hand: Hand) -> Self {
+        Self(hand, true)
+    }
     fn find_rank_of_straight(&self, hand: Hand) -> Option<Rank> {
-        let wheel = WHEEL;
+        let (wheel, lowest) = match self.1 {
+            true => (SHORTDECK_WHEEL, SHORTDECK_LOWEST_STRAIGHT_RANK),
+            false => (WHEEL, LOWEST_STRAIGHT_RANK),
+        };
         let ranks = u16::from(hand);
         let mut bits = ranks;
         bits &= bits << 1;
@@ -114,7 +131,7 @@ impl Evaluator {
         if bits > 0 {
             Some(Rank::from(bits))
         } else if wheel == (wheel & ranks) {
-            Some(LOWEST_STRAIGHT_RANK)
+            Some(lowest)
         } else {
             None
         }
@@ -124,28 +141,67 @@ impl Evaluator {
         self.find_rank_of_straight(hand)
     }
     fn find_suit_of_flush(&self) -> Option<Suit> {
-        Suit::all()
-            .map(|s| u64::from(s))
-            .map(|u| u64::from(self.0) & u)
-            .map(|n| n.count_ones() as u8)
+        self.suit_counts()
             .iter()
             .position(|&n| n >= 5)
             .map(|i| Suit::from(i as u8))
     }
     fn find_rank_of_n_oak_under(&self, oak: usize, rank: Option<Rank>) -> Option<Rank> {
+        assert!(oak > 0 && oak <= 4);
         let rank = rank.map(|c| u8::from(c)).unwrap_or(13) as u64;
         let mask = (1u64 << (4 * rank)) - 1;
-        let hand = u64::from(self.0) & mask;
-        let mut mask = 0xF << (4 * (rank)) >> 4;
-        while mask > 0 {
-            if oak <= (hand & mask).count_ones() as usize {
-                let rank = mask.trailing_zeros() / 4;
-                let rank = Rank::from(rank as u8);
-                return Some(rank);
-            }
-            mask >>= 4;
+        let counts = self.rank_counts() & mask;
+        // counts are at most 4, so adding 8 - oak to every nibble
+        // sets its high bit exactly when count >= oak, no carries
+        let bias = (8 - oak as u64) * NIBBLES;
+        let oaks = (counts + bias) & (NIBBLES << 3) & mask;
+        match oaks {
+            0 => None,
+            _ => Some(Rank::from(((63 - oaks.leading_zeros()) / 4) as u8)),
         }
-        None
+    }
+
+    /// how many cards of each Rank, one count per nibble in
+    /// the same layout as Hand. the simd feature counts all
+    /// thirteen nibbles at once within a single register.
+    fn rank_counts(&self) -> u64 {
+        #[cfg(feature = "simd")]
+        return Self::rank_counts_swar(u64::from(self.0));
+        #[cfg(not(feature = "simd"))]
+        return Self::rank_counts_scalar(u64::from(self.0));
+    }
+    /// how many cards of each Suit, indexed by Suit as u8
+    fn suit_counts(&self) -> [u8; 4] {
+        #[cfg(feature = "simd")]
+        return Self::suit_counts_swar(u64::from(self.0));
+        #[cfg(not(feature = "simd"))]
+        return Self::suit_counts_scalar(u64::from(self.0));
+    }
+    #[cfg(any(test, not(feature = "simd")))]
+    fn rank_counts_scalar(hand: u64) -> u64 {
+        (0..13)
+            .map(|r| 4 * r)
+            .map(|shift| (((hand >> shift) & 0xF).count_ones() as u64) << shift)
+            .fold(0, |counts, count| counts | count)
+    }
+    #[cfg(any(test, feature = "simd"))]
+    fn rank_counts_swar(hand: u64) -> u64 {
+        let pairs = hand - ((hand >> 1) & 0x5555555555555555);
+        (pairs & 0x3333333333333333) + ((pairs >> 2) & 0x3333333333333333)
+    }
+    #[cfg(any(test, not(feature = "simd")))]
+    fn suit_counts_scalar(hand: u64) -> [u8; 4] {
+        Suit::all()
+            .map(|s| u64::from(s))
+            .map(|u| hand & u)
+            .map(|n| n.count_ones() as u8)
+    }
+    #[cfg(any(test, feature = "simd"))]
+    fn suit_counts_swar(hand: u64) -> [u8; 4] {
+        // isolate one suit's bit in each nibble, then multiply to
+        // accumulate all thirteen nibbles into the top one
+        let clubs = u64::from(Suit::C);
+        [0, 1, 2, 3].map(|s| (((hand >> s) & clubs).wrapping_mul(clubs) >> 48) as u8 & 0xF)
     }
     fn find_rank_of_n_oak(&self, n: usize) -> Option<Rank> {
         self.find_rank_of_n_oak_under(n, None)
@@ -156,6 +212,96 @@ impl Evaluator {
 mod tests {
     use super::*;
     use crate::cards::hand::Hand;
+    use crate::cards::strength::Strength;
+    use crate::Arbitrary;
+
+    #[test]
+    fn swar_counts_match_scalar() {
+        for _ in 0..4096 {
+            let hand = u64::from(Hand::random());
+            assert!(Evaluator::rank_counts_swar(hand) == Evaluator::rank_counts_scalar(hand));
+            assert!(Evaluator::suit_counts_swar(hand) == Evaluator::suit_counts_scalar(hand));
+        }
+    }
+
+    #[test]
+    fn empty_hand() {
+        let e = Evaluator::from(Hand::empty());
+        let ranking = e.find_ranking();
+        assert!(ranking == Ranking::HighCard(Rank::Two));
+        assert!(e.find_kickers(ranking) == Kickers::default());
+    }
+
+    #[test]
+    fn single_card() {
+        let e = Evaluator::from(Hand::try_from("Kd").unwrap());
+        let ranking = e.find_ranking();
+        assert!(ranking == Ranking::HighCard(Rank::King));
+        assert!(e.find_kickers(ranking) == Kickers::default());
+    }
+
+    #[test]
+    fn pair_without_kickers() {
+        let e = Evaluator::from(Hand::try_from("As Ah").unwrap());
+        let ranking = e.find_ranking();
+        assert!(ranking == Ranking::OnePair(Rank::Ace));
+        assert!(e.find_kickers(ranking) == Kickers::default());
+    }
+
+    #[test]
+    fn short_hands_fill_fewer_kickers() {
+        let e = Evaluator::from(Hand::try_from("As Ah Kd 2c").unwrap());
+        let ranking = e.find_ranking();
+        assert!(ranking == Ranking::OnePair(Rank::Ace));
+        assert!(
+            e.find_kickers(ranking) == Kickers::from(u16::from(Rank::King) | u16::from(Rank::Two))
+        );
+        let short = Strength::from(Hand::try_from("As Ah Kd").unwrap());
+        let full = Strength::from(Hand::try_from("As Ah Kd 2c").unwrap());
+        assert!(short < full);
+    }
+
+    #[test]
+    fn kickers_break_ties() {
+        let king = Hand::try_from("As Ah Kd Tc 8s 7h 6d").unwrap();
+        let queen = Hand::try_from("As Ah Qd Tc 8s 7h 6d").unwrap();
+        let kickers = |hand: Hand| {
+            let e = Evaluator::from(hand);
+            e.find_kickers(e.find_ranking())
+        };
+        assert!(kickers(king) > kickers(queen));
+        assert!(Strength::from(king) > Strength::from(queen));
+    }
+
+    #[test]
+    fn kickers_exclude_made_ranks() {
+        let e = Evaluator::from(Hand::try_from("As Ah Kd Kc Qs Qh Jd").unwrap());
+        let ranking = e.find_ranking();
+        assert!(ranking == Ranking::TwoPair(Rank::Ace, Rank::King));
+        assert!(e.find_kickers(ranking) == Kickers::from(u16::from(Rank::Queen)));
+    }
+
+    #[test]
+    fn n_oak_counts_match_scan() {
+        // one rank nibble at a time, from below the cap downward
+        let scan = |hand: u64, oak: usize, under: Option<Rank>| {
+            let top = under.map(u8::from).unwrap_or(13);
+            (0..top)
+                .rev()
+                .find(|r| oak <= ((hand >> (4 * r)) & 0xF).count_ones() as usize)
+                .map(Rank::from)
+        };
+        for _ in 0..1024 {
+            let hand = Hand::random();
+            let e = Evaluator::from(hand);
+            for oak in 1..=4 {
+                for under in std::iter::once(None).chain((0..13u8).map(Rank::from).map(Some)) {
+                    let expected = scan(u64::from(hand), oak, under);
+                    assert!(e.find_rank_of_n_oak_under(oak, under) == expected);
+                }
+            }
+        }
+    }
 
     #[test]
     fn high_card() {
@@ -256,6 +402,33 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "shortdeck")]
+    fn shortdeck_old_wheel_is_not_straight() {
+        assert!(
+            Evaluator::from(Hand::try_from("As 2h 3d 4c 5s").unwrap()).find_ranking()
+                == Ranking::HighCard(Rank::Ace)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "shortdeck")]
+    fn shortdeck_flush_over_full_house() {
+        let flush = Evaluator::from(Hand::try_from("6s 8s Ts Qs As").unwrap()).find_ranking();
+        let house = Evaluator::from(Hand::try_from("As Ah Ad Kc Ks").unwrap()).find_ranking();
+        assert!(flush == Ranking::Flush(Rank::Ace));
+        assert!(house == Ranking::FullHouse(Rank::Ace, Rank::King));
+        assert!(flush > house);
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn full_house_over_flush_ranking() {
+        let flush = Evaluator::from(Hand::try_from("2s 8s Ts Qs As").unwrap()).find_ranking();
+        let house = Evaluator::from(Hand::try_from("2s 2h 2d 3c 3s").unwrap()).find_ranking();
+        assert!(house > flush);
+    }
+
     #[test]
     #[cfg(feature = "shortdeck")]
     fn wheel_straight_flush() {
@@ -328,4 +501,64 @@ mod tests {
                 == Ranking::FullHouse(Rank::Ace, Rank::King)
         );
     }
+
+    /// hand-curated reference cases, one per line as
+    /// `seven cards, category, primary rank(s)`, with expected
+    /// values worked out by hand from standard hold'em rules.
+    /// every category appears, plus the usual traps: wheels,
+    /// steel wheels, three pairs, two trips, and a full house
+    /// whose pair outranks its trips. to extend, append a line.
+    #[cfg(not(feature = "shortdeck"))]
+    const GOLDEN: &str = r#"
+        As Kh Qd Jc 9s 7h 2d, HighCard,      A
+        7s 5h 4d 3c 2s 9h Jd, HighCard,      J
+        As Ah Kd Qc 9s 7h 2d, OnePair,       A
+        2s 2h Kd Qc 9s 7h 4d, OnePair,       2
+        As Ah Kd Kc 9s 7h 2d, TwoPair,       A K
+        As Ah Kd Kc Qs Qh 2d, TwoPair,       A K
+        3s 3h 2d 2c As 7h 9d, TwoPair,       3 2
+        7s 7h 7d Ac Ks 2h 4d, ThreeOAK,      7
+        Ts Jh Qd Kc As 2h 3d, Straight,      A
+        9s Th Jd Qc Ks Ah 2d, Straight,      A
+        As 2h 3d 4c 5s 9h Kd, Straight,      5
+        6s 2h 3d 4c 5s As Kd, Straight,      6
+        As Ks Qs Js 9s 2h 3d, Flush,         A
+        2h 4h 7h 9h Jh Ah Kd, Flush,         A
+        As Ah Ad Kc Ks 2h 3d, FullHouse,     A K
+        As Ah Ad Kc Ks Kh 2d, FullHouse,     A K
+        2s 2h 2d 9c 9s Ah Kd, FullHouse,     2 9
+        7s 7h 7d 9c 9s 3h 3d, FullHouse,     7 9
+        As Ah Ad Ac Ks 2h 3d, FourOAK,       A
+        2s 2h 2d 2c Ks Kh Kd, FourOAK,       2
+        Ts Js Qs Ks As 2h 3d, StraightFlush, A
+        9h Th Jh Qh Kh Ah 2d, StraightFlush, A
+        As 2s 3s 4s 5s Kh Qd, StraightFlush, 5
+    "#;
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn golden() {
+        for line in GOLDEN.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let fields = line.split(',').map(|f| f.trim()).collect::<Vec<&str>>();
+            let hand = Hand::try_from(fields[0]).unwrap();
+            let ranks = fields[2]
+                .split_whitespace()
+                .map(|r| Rank::try_from(r).unwrap())
+                .collect::<Vec<Rank>>();
+            let expected = match fields[1] {
+                "HighCard" => Ranking::HighCard(ranks[0]),
+                "OnePair" => Ranking::OnePair(ranks[0]),
+                "TwoPair" => Ranking::TwoPair(ranks[0], ranks[1]),
+                "ThreeOAK" => Ranking::ThreeOAK(ranks[0]),
+                "Straight" => Ranking::Straight(ranks[0]),
+                "Flush" => Ranking::Flush(ranks[0]),
+                "FullHouse" => Ranking::FullHouse(ranks[0], ranks[1]),
+                "FourOAK" => Ranking::FourOAK(ranks[0]),
+                "StraightFlush" => Ranking::StraightFlush(ranks[0]),
+                other => panic!("unknown category {}", other),
+            };
+            let ranking = Evaluator::from(hand).find_ranking();
+            assert!(ranking == expected, "{}: {} != {}", line, ranking, expected);
+        }
+    }
 }
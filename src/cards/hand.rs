@@ -150,24 +150,28 @@ impl From<Card> for Hand {
 }
 
 /// str isomorphism
-/// this follows from Vec<Card> isomorphism
+/// this follows from Vec<Card> isomorphism, except that a
+/// Hand is a set, so naming the same Card twice is an error
 impl TryFrom<&str> for Hand {
     type Error = String;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        Ok(s.split_whitespace()
-            .map(|token| {
+        s.split_whitespace()
+            .flat_map(|token| {
                 token
                     .chars()
                     .collect::<Vec<_>>()
                     .chunks(2)
                     .map(|chunk| chunk.iter().collect::<String>())
-                    .map(|owned| Card::try_from(owned.as_str()))
-                    .collect::<Result<Vec<Card>, _>>()
+                    .collect::<Vec<_>>()
+            })
+            .map(|owned| Card::try_from(owned.as_str()))
+            .try_fold(Hand::empty(), |hand, card| {
+                let card = card?;
+                match u64::from(hand) & u64::from(card) {
+                    0 => Ok(Hand::add(hand, Hand::from(card))),
+                    _ => Err(format!("duplicate card: {}", card)),
+                }
             })
-            .flatten()
-            .flatten()
-            .collect::<Vec<Card>>()
-            .into())
     }
 }
 
@@ -182,7 +186,7 @@ impl std::fmt::Display for Hand {
 
 impl Arbitrary for Hand {
     fn random() -> Self {
-        let ref mut rng = rand::thread_rng();
+        let ref mut rng = crate::rng();
         let cards = rand::Rng::gen::<u64>(rng);
         let cards = cards & Self::mask();
         Self(cards)
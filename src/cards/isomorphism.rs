@@ -1,5 +1,7 @@
+use super::isomorphisms::IsomorphismIterator;
 use super::observation::Observation;
 use super::permutation::Permutation;
+use super::street::Street;
 use crate::Arbitrary;
 
 /// because of the equivalence of Suit,
@@ -38,6 +40,12 @@ impl From<Isomorphism> for Observation {
     }
 }
 
+impl From<Isomorphism> for i64 {
+    fn from(isomorphism: Isomorphism) -> Self {
+        i64::from(isomorphism.0)
+    }
+}
+
 impl From<i64> for Isomorphism {
     fn from(i: i64) -> Self {
         Self(Observation::from(i))
@@ -51,6 +59,44 @@ impl Arbitrary for Isomorphism {
 }
 
 impl Isomorphism {
+    /// the database key of an Observation's canonical form. every
+    /// lookup keyed by observation goes through here, so nobody
+    /// keys on a raw Observation by mistake.
+    pub fn key(observation: Observation) -> i64 {
+        i64::from(Self::from(observation))
+    }
+    /// uniformly random canonical isomorphism on a street, with
+    /// every class equally likely. canonicalizing a random deal
+    /// instead would favor classes with more suit permutations.
+    /// preflop and flop index straight into a table enumerated
+    /// once per process; past the flop that table is too large
+    /// to hold, so later streets fall back to rejecting deals
+    /// that aren't already canonical.
+    pub fn random_canonical(street: Street) -> Self {
+        use rand::Rng;
+        match street {
+            Street::Pref | Street::Flop => {
+                let table = Self::table(street);
+                table[crate::rng().gen_range(0..table.len())]
+            }
+            Street::Turn | Street::Rive => std::iter::repeat_with(|| Observation::from(street))
+                .find(|o| Self::is_canonical(o))
+                .map(Self)
+                .expect("infinite sampler"),
+        }
+    }
+    /// every canonical isomorphism on a small street, built lazily
+    fn table(street: Street) -> &'static [Self] {
+        use std::sync::OnceLock;
+        static PREF: OnceLock<Vec<Isomorphism>> = OnceLock::new();
+        static FLOP: OnceLock<Vec<Isomorphism>> = OnceLock::new();
+        let cell = match street {
+            Street::Pref => &PREF,
+            Street::Flop => &FLOP,
+            _ => unreachable!("no isomorphism table past the flop"),
+        };
+        cell.get_or_init(|| IsomorphismIterator::from(street).collect())
+    }
     pub fn is_canonical(observation: &Observation) -> bool {
         Permutation::from(observation) == Permutation::identity()
     }
@@ -62,12 +108,51 @@ impl std::fmt::Display for Isomorphism {
     }
 }
 
+/// parse an Observation and canonicalize it, so any
+/// suit relabeling of the same cards gives the same key
+impl std::str::FromStr for Isomorphism {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Observation::try_from(s).map(Self::from)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cards::hand::Hand;
     use crate::cards::permutation::Permutation;
-    use crate::cards::street::Street;
+
+    #[test]
+    fn random_canonical_uniform() {
+        let street = Street::Pref;
+        let n = street.n_isomorphisms();
+        let samples = 200 * n;
+        let mut counts = std::collections::BTreeMap::new();
+        for _ in 0..samples {
+            let iso = Isomorphism::random_canonical(street);
+            assert!(Isomorphism::is_canonical(&iso.0));
+            *counts.entry(iso).or_insert(0usize) += 1;
+        }
+        assert!(counts.len() == n);
+        assert!(counts.keys().copied().eq(IsomorphismIterator::from(street)));
+        let expected = samples as f32 / n as f32;
+        let chi2 = counts
+            .values()
+            .map(|&c| (c as f32 - expected).powi(2) / expected)
+            .sum::<f32>();
+        assert!(chi2 < 2. * n as f32); // df = 168, mean 168, sd ~18
+    }
+
+    #[test]
+    fn bijective_str() {
+        let isomorphism = Isomorphism::from(Observation::from(Street::Turn));
+        assert!(isomorphism == format!("{}", isomorphism).parse().unwrap());
+        let hearts = "AhKh~9h7cTd".parse::<Isomorphism>().unwrap();
+        let spades = "AsKs~9s7dTc".parse::<Isomorphism>().unwrap();
+        assert!(hearts == spades);
+        assert!("AhKh~9h7c".parse::<Isomorphism>().is_err());
+    }
 
     #[test]
     fn false_positives() {
@@ -0,0 +1,197 @@
+use super::hand::Hand;
+use super::strength::Strength;
+use super::suit::Suit;
+use std::sync::OnceLock;
+
+/// most cards a table entry can describe. larger Hands
+/// fall back to the lazy Evaluator.
+const MAX_CARDS: usize = 7;
+
+/// A table-driven evaluator for a hand's strength.
+///
+/// With at most seven cards, five or more of one Suit rule out
+/// quads and full houses, so a flush is decided by that Suit's
+/// thirteen rank bits alone. Otherwise Strength depends only on
+/// how many cards of each Rank we hold, and that multiset has a
+/// dense index in the combinatorial number system. So we keep
+/// one table per case and evaluate with a handful of indexes.
+///
+/// Memory: 2^13 flush entries plus C(20, 7) = 77,520 multiset
+/// entries, about 0.5MB of Strengths in total. Building it runs
+/// the lazy Evaluator once per entry, which takes a fraction of
+/// a second. Nothing is allocated until ::new() or ::shared()
+/// is called, so callers who only use Evaluator pay nothing.
+pub struct LookupEvaluator {
+    flushes: Vec<Option<Strength>>,
+    multisets: Vec<Option<Strength>>,
+    steps: [[[usize; 5]; MAX_CARDS + 1]; 13],
+    offsets: [usize; MAX_CARDS + 1],
+}
+
+impl LookupEvaluator {
+    /// build both tables. prefer ::shared() unless you
+    /// need an instance you can drop.
+    pub fn new() -> Self {
+        let steps = std::array::from_fn(|rank| {
+            std::array::from_fn(|i| {
+                std::array::from_fn(|n| (0..n).map(|j| Self::choose(rank + i + j, i + j + 1)).sum())
+            })
+        });
+        let offsets = std::array::from_fn(|k| (0..k).map(|j| Self::choose(12 + j, j)).sum());
+        let mut this = Self {
+            flushes: vec![None; 1 << 13],
+            multisets: vec![None; Self::choose(13 + MAX_CARDS, MAX_CARDS)],
+            steps,
+            offsets,
+        };
+        for ranks in (0..1u64 << 13).filter(|r| r.count_ones() >= 5) {
+            let suited = (0..13)
+                .filter(|r| ranks & (1 << r) != 0)
+                .map(|r| 1u64 << (4 * r))
+                .fold(0, |hand, card| hand | card);
+            this.flushes[ranks as usize] = Self::lazy(suited);
+        }
+        for counts in Self::multisets() {
+            let index = this.index(counts);
+            this.multisets[index] = Self::lazy(Self::spread(counts));
+        }
+        this
+    }
+    /// the process-wide table, built on first use
+    pub fn shared() -> &'static Self {
+        static TABLE: OnceLock<LookupEvaluator> = OnceLock::new();
+        TABLE.get_or_init(Self::new)
+    }
+    /// identical to Strength::from(hand)
+    pub fn strength(&self, hand: Hand) -> Strength {
+        let n = hand.size();
+        if n == 0 || n > MAX_CARDS {
+            return Strength::from(hand);
+        }
+        let bits = u64::from(hand);
+        let clubs = u64::from(Suit::C);
+        let entry = match (0..4)
+            .map(|s| (bits >> s) & clubs)
+            .find(|suited| suited.count_ones() >= 5)
+        {
+            Some(suited) => self.flushes[Self::ranks(suited)],
+            None => self.multisets[self.index(Self::counts(bits))],
+        };
+        entry.expect("every hand of at most seven cards is tabled")
+    }
+
+    /// Strength of the Hand, without the tables. None if some
+    /// of these cards aren't in the deck, as under shortdeck.
+    fn lazy(bits: u64) -> Option<Strength> {
+        let hand = Hand::from(bits);
+        match hand.size() == bits.count_ones() as usize {
+            true => Some(Strength::from(hand)),
+            false => None,
+        }
+    }
+    /// collapse one Suit's bits (shifted down to the clubs
+    /// position) into a 13 bit rank mask
+    fn ranks(mut suited: u64) -> usize {
+        let mut mask = 0;
+        while suited > 0 {
+            mask |= 1 << (suited.trailing_zeros() / 4);
+            suited &= suited - 1;
+        }
+        mask
+    }
+    /// how many cards of each Rank, one count per nibble
+    fn counts(bits: u64) -> u64 {
+        let pairs = bits - ((bits >> 1) & 0x5555555555555555);
+        (pairs & 0x3333333333333333) + ((pairs >> 2) & 0x3333333333333333)
+    }
+    /// a Hand with these rank counts and no flush. cards are
+    /// dealt suits round robin, so a Rank never repeats a Suit
+    /// and no Suit gets more than two of seven cards.
+    fn spread(counts: u64) -> u64 {
+        let mut hand = 0u64;
+        let mut suit = 0u64;
+        for rank in 0..13 {
+            for _ in 0..(counts >> (4 * rank)) & 0xF {
+                hand |= 1 << (4 * rank + suit);
+                suit = (suit + 1) % 4;
+            }
+        }
+        hand
+    }
+    /// every rank multiset of one to seven cards
+    /// with at most four of any Rank
+    fn multisets() -> Vec<u64> {
+        let sizes = (0..13).fold(vec![0u64], |partial, rank| {
+            partial
+                .into_iter()
+                .flat_map(|counts| {
+                    let held = (0..13).map(|r| (counts >> (4 * r)) & 0xF).sum::<u64>();
+                    (0..=4)
+                        .take_while(move |c| held + c <= MAX_CARDS as u64)
+                        .map(move |c| counts | c << (4 * rank))
+                })
+                .collect()
+        });
+        sizes.into_iter().filter(|&counts| counts != 0).collect()
+    }
+    /// colex rank of the multiset among those of the same size,
+    /// offset past every smaller size. the ith smallest card of
+    /// rank r becomes the element r + i of a strict subset, and
+    /// steps[r][i][n] sums n such terms for a run of n cards.
+    fn index(&self, mut counts: u64) -> usize {
+        let mut i = 0;
+        let mut index = 0;
+        while counts > 0 {
+            let rank = counts.trailing_zeros() as usize / 4;
+            let n = (counts >> (4 * rank)) as usize & 0xF;
+            index += self.steps[rank][i][n];
+            i += n;
+            counts &= !(0xF << (4 * rank));
+        }
+        self.offsets[i] + index
+    }
+    fn choose(n: usize, k: usize) -> usize {
+        match k > n {
+            true => 0,
+            false => (0..k).fold(1, |c, i| c * (n - i) / (i + 1)),
+        }
+    }
+}
+
+impl Default for LookupEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::Deck;
+
+    #[test]
+    fn indices_are_distinct() {
+        let table = LookupEvaluator::shared();
+        let mut seen = LookupEvaluator::multisets()
+            .into_iter()
+            .map(|counts| table.index(counts))
+            .collect::<Vec<usize>>();
+        let n = seen.len();
+        seen.sort_unstable();
+        seen.dedup();
+        assert!(seen.len() == n);
+        assert!(seen.iter().all(|&i| i < table.multisets.len()));
+    }
+
+    #[test]
+    fn agrees_with_lazy_evaluator() {
+        let table = LookupEvaluator::shared();
+        for i in 0..250_000 {
+            let mut deck = Deck::new();
+            let hand = (0..5 + i % 3)
+                .map(|_| Hand::from(deck.draw()))
+                .fold(Hand::empty(), Hand::add);
+            assert!(table.strength(hand) == Strength::from(hand), "{}", hand);
+        }
+    }
+}
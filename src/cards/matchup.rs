@@ -0,0 +1,187 @@
+use super::card::Card;
+use super::deck::Deck;
+use super::hand::Hand;
+use super::strength::Strength;
+use crate::Probability;
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// how often hero wins, ties and loses one specific
+/// matchup, e.g. AhKh vs QsQd on a given flop.
+///
+/// Observation::equity is exact but plays hero against every
+/// opponent holding on a complete board. here both hands are
+/// fixed and we roll out random runouts instead, so the answer
+/// is approximate, cheap, and works on any street.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matchup {
+    pub win: Probability,
+    pub tie: Probability,
+    pub loss: Probability,
+}
+
+impl Matchup {
+    /// deal the rest of the board trials times from a Deck
+    /// without the known cards, and count who wins each
+    /// showdown. pass a seeded rng to reproduce a run.
+    pub fn equity_vs(
+        hero: &[Card],
+        villain: &[Card],
+        board: &[Card],
+        trials: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let hero = Hand::from(hero.to_vec());
+        let villain = Hand::from(villain.to_vec());
+        let board = Hand::from(board.to_vec());
+        assert!(hero.size() == 2, "hero needs two cards: {}", hero);
+        assert!(villain.size() == 2, "villain needs two cards: {}", villain);
+        assert!(board.size() <= 5, "too many board cards: {}", board);
+        assert!(u64::from(hero) & u64::from(villain) == 0);
+        assert!(u64::from(Hand::add(hero, villain)) & u64::from(board) == 0);
+        assert!(trials > 0);
+        let deck = Deck::except(Hand::add(Hand::add(hero, villain), board));
+        let (win, tie, loss) = (0..trials)
+            .map(|_| {
+                let mut deck = deck;
+                Hand::add(board, deck.deal_with(5 - board.size(), rng))
+            })
+            .map(|runout| {
+                let h = Strength::from(Hand::add(hero, runout));
+                let v = Strength::from(Hand::add(villain, runout));
+                h.cmp(&v)
+            })
+            .fold((0usize, 0usize, 0usize), |(w, t, l), ord| match ord {
+                Ordering::Greater => (w + 1, t, l),
+                Ordering::Equal => (w, t + 1, l),
+                Ordering::Less => (w, t, l + 1),
+            });
+        let n = trials as Probability;
+        Self {
+            win: win as Probability / n,
+            tie: tie as Probability / n,
+            loss: loss as Probability / n,
+        }
+    }
+    /// hero's share of the pot, with ties split
+    pub fn equity(&self) -> Probability {
+        self.win + self.tie / 2.
+    }
+    /// each hand's share of the pot when all of them see the
+    /// same runouts. every trial splits one pot among the best
+    /// hands, so the shares sum to one.
+    pub fn equity_multiway(
+        hands: &[Vec<Card>],
+        board: &[Card],
+        trials: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<Probability> {
+        let hands = hands
+            .iter()
+            .map(|hand| Hand::from(hand.clone()))
+            .collect::<Vec<Hand>>();
+        let board = Hand::from(board.to_vec());
+        assert!(hands.len() >= 2, "equity needs an opponent");
+        assert!(hands.iter().all(|h| h.size() == 2), "two cards per hand");
+        assert!(board.size() <= 5, "too many board cards: {}", board);
+        assert!(trials > 0);
+        let dead = hands.iter().copied().fold(board, |dead, hand| {
+            assert!(u64::from(dead) & u64::from(hand) == 0);
+            Hand::add(dead, hand)
+        });
+        let deck = Deck::except(dead);
+        let mut shares = vec![0.; hands.len()];
+        for _ in 0..trials {
+            let mut deck = deck;
+            let runout = Hand::add(board, deck.deal_with(5 - board.size(), rng));
+            let strengths = hands
+                .iter()
+                .map(|hand| Strength::from(Hand::add(*hand, runout)))
+                .collect::<Vec<Strength>>();
+            let best = strengths.iter().max().expect("at least two hands");
+            let winners = strengths.iter().filter(|s| *s == best).count();
+            for (share, strength) in shares.iter_mut().zip(strengths.iter()) {
+                if strength == best {
+                    *share += 1. / winners as Probability;
+                }
+            }
+        }
+        shares.iter().map(|s| s / trials as Probability).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    fn cards(s: &str) -> Vec<Card> {
+        Vec::<Card>::from(Hand::try_from(s).unwrap())
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn aces_dominate_kings() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let m = Matchup::equity_vs(&cards("Ah Ad"), &cards("Ks Kc"), &[], 20_000, rng);
+        assert!((m.win + m.tie + m.loss - 1.).abs() < 1e-5);
+        assert!((m.equity() - 0.82).abs() < 0.015, "{:?}", m);
+    }
+
+    #[test]
+    fn complete_board_is_exact() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let board = cards("Qs Qd 7c 8h 9d");
+        let m = Matchup::equity_vs(&cards("Ah Kh"), &cards("Ts Js"), &board, 16, rng);
+        assert!(m.loss == 1.);
+        let m = Matchup::equity_vs(&cards("Ah Kh"), &cards("Ac Kc"), &board, 16, rng);
+        assert!(m.tie == 1.);
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn multiway_shares_are_ordered() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let hands = [cards("Ah Ad"), cards("Kh Kd"), cards("Qh Qd")];
+        let shares = Matchup::equity_multiway(&hands, &[], 10_000, rng);
+        assert!((shares.iter().sum::<Probability>() - 1.).abs() < 1e-4);
+        assert!(shares[0] > shares[1], "{:?}", shares);
+        assert!(shares[1] > shares[2], "{:?}", shares);
+    }
+
+    #[test]
+    fn multiway_splits_ties() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let board = cards("As Ks Qs Js Ts");
+        let hands = [cards("2h 3h"), cards("4d 5d"), cards("6c 7c")];
+        let shares = Matchup::equity_multiway(&hands, &board, 16, rng);
+        assert!(shares.iter().all(|s| (s - 1. / 3.).abs() < 1e-5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiway_hands_are_disjoint() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let hands = [cards("Ah Kh"), cards("Ah Qd")];
+        Matchup::equity_multiway(&hands, &[], 16, rng);
+    }
+
+    #[test]
+    #[should_panic]
+    fn multiway_hands_miss_the_board() {
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let hands = [cards("Ah Kh"), cards("Qs Qd")];
+        Matchup::equity_multiway(&hands, &cards("Kh Jc Tc"), 16, rng);
+    }
+
+    #[test]
+    fn seeded_runs_agree() {
+        let run = |seed| {
+            let ref mut rng = SmallRng::seed_from_u64(seed);
+            let board = cards("9h 7h Jc");
+            Matchup::equity_vs(&cards("Ah Kh"), &cards("Qs Qd"), &board, 256, rng)
+        };
+        assert!(run(1) == run(1));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod board;
 pub mod card;
+pub mod category;
 pub mod deck;
 pub mod evaluator;
 pub mod hand;
@@ -8,11 +9,17 @@ pub mod hole;
 pub mod isomorphism;
 pub mod isomorphisms;
 pub mod kicks;
+pub mod lookup;
+pub mod matchup;
 pub mod observation;
 pub mod observations;
+pub mod omaha;
 pub mod permutation;
 pub mod rank;
 pub mod ranking;
+pub mod runouts;
+pub mod shortdeck;
 pub mod street;
 pub mod strength;
 pub mod suit;
+pub mod wildcard;
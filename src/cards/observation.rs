@@ -2,8 +2,13 @@ use super::card::Card;
 use super::deck::Deck;
 use super::hand::Hand;
 use super::hands::HandIterator;
+use super::permutation::Permutation;
+use super::runouts::Runouts;
+use super::runouts::Table;
 use super::street::Street;
 use super::strength::Strength;
+use crate::clustering::abstraction::Abstraction;
+use crate::clustering::histogram::Histogram;
 use crate::Arbitrary;
 use crate::Probability;
 use std::cmp::Ordering;
@@ -22,6 +27,8 @@ pub struct Observation {
 }
 
 impl Observation {
+    /// lazily deal every possible next street. nothing is collected,
+    /// so callers can project huge streets in bounded memory.
     pub fn children<'a>(&'a self) -> impl Iterator<Item = Self> + 'a {
         let n = self.street().n_revealed();
         let removed = Hand::from(*self);
@@ -29,6 +36,13 @@ impl Observation {
             .map(|reveal| Hand::add(self.public, reveal))
             .map(|public| Self::from((self.pocket, public)))
     }
+    /// number of children, without dealing any of them.
+    /// n cards chosen from whatever's left of the deck in use
+    pub fn children_count(&self) -> usize {
+        let n = self.street().n_revealed();
+        let left = Hand::mask().count_ones() as usize - Hand::from(*self).size();
+        (0..n).fold(1, |x, i| x * (left - i) / (i + 1))
+    }
     pub fn equity(&self) -> Probability {
         assert!(self.street() == Street::Rive);
         let hand = Hand::from(*self);
@@ -48,6 +62,54 @@ impl Observation {
             _ => won as Probability / sum as Probability,
         }
     }
+    /// distribution of equity across every possible opponent hand,
+    /// rather than its mean. each opponent hand contributes its
+    /// win rate over all runouts of the board, bucketed into the
+    /// same equity Abstractions used for river clustering, so we
+    /// can see how often we are way ahead versus way behind.
+    /// this enumerates runouts per opponent, so keep it off the
+    /// preflop hot path.
+    pub fn equity_distribution(&self) -> Histogram {
+        self.equity_distribution_with(Runouts::shared())
+    }
+    /// equity_distribution, reading holding strengths off each
+    /// runout's board Table so other hands on this board reuse them
+    pub fn equity_distribution_with(&self, runouts: &Runouts) -> Histogram {
+        let tables = self
+            .runouts()
+            .map(|board| (u64::from(board), runouts.table(board)))
+            .collect::<Vec<(u64, Table)>>();
+        HandIterator::from((2, Hand::from(*self)))
+            .map(|villain| {
+                let (won, sum) = tables
+                    .iter()
+                    .filter(|(board, _)| board & u64::from(villain) == 0)
+                    .map(|(_, table)| table[&self.pocket].cmp(&table[&villain]))
+                    .filter(|&ord| ord != Ordering::Equal)
+                    .fold((0u32, 0u32), |(wins, total), ord| match ord {
+                        Ordering::Greater => (wins + 1, total + 1),
+                        Ordering::Less => (wins, total + 1),
+                        Ordering::Equal => unreachable!(),
+                    });
+                match sum {
+                    0 => 0.5, // all draw edge case
+                    _ => won as Probability / sum as Probability,
+                }
+            })
+            .map(Abstraction::from)
+            .fold(Histogram::default(), |hist, abs| hist.increment(abs))
+    }
+    /// every complete board our public cards can run out to
+    fn runouts(&self) -> impl Iterator<Item = Hand> + '_ {
+        let dead = Hand::from(*self);
+        let boards = match 5 - self.public.size() {
+            0 => vec![Hand::empty()],
+            n => HandIterator::from((n, dead)).collect::<Vec<Hand>>(),
+        };
+        boards
+            .into_iter()
+            .map(move |runout| Hand::add(self.public, runout))
+    }
     pub fn street(&self) -> Street {
         Street::from(self.public.size())
     }
@@ -57,6 +119,17 @@ impl Observation {
     pub fn public(&self) -> &Hand {
         &self.public
     }
+    /// lexicographically smallest Observation among all
+    /// suit permutations of this one. unlike Isomorphism,
+    /// which picks whatever Permutation canonicalizes, this
+    /// is stable under display and easy to explain to a user.
+    pub fn canonical_representative(&self) -> Self {
+        Permutation::exhaust()
+            .iter()
+            .map(|p| p.permute(self))
+            .min_by_key(|o| o.to_string())
+            .expect("non-empty permutation group")
+    }
 
     const SEPARATOR: &'static str = "~";
 }
@@ -98,6 +171,51 @@ impl From<i64> for Observation {
     }
 }
 
+/// validated i64 conversion
+///
+/// From<i64> trusts its input, which is fine for values we packed ourselves.
+/// values read back from the DB get checked here instead: every packed byte
+/// must be a distinct card in the deck, with no gaps, and the card counts
+/// must land on a real Street.
+impl Observation {
+    pub fn try_from_i64(bits: i64) -> Result<Self, String> {
+        let bytes = (0u64..8u64)
+            .map(|i| (bits as u64 >> (i * 8)) as u8)
+            .collect::<Vec<u8>>();
+        let n = bytes.iter().take_while(|&&b| b > 0).count();
+        if bytes.iter().skip(n).any(|&b| b > 0) {
+            return Err(format!("gap in packed observation {:#018x}", bits));
+        }
+        if !matches!(n, 2 | 5 | 6 | 7) {
+            return Err(format!("invalid card count {} in observation {:#018x}", n, bits));
+        }
+        let mut cards = Hand::empty();
+        for byte in bytes.into_iter().take(n) {
+            let card = byte - 1;
+            if card as u32 >= u64::BITS || Hand::mask() & (1u64 << card) == 0 {
+                return Err(format!("invalid card byte {} in observation {:#018x}", byte, bits));
+            }
+            let hand = Hand::from(Card::from(card));
+            if u64::from(cards) & u64::from(hand) > 0 {
+                return Err(format!("duplicate card in observation {:#018x}", bits));
+            }
+            cards = Hand::add(cards, hand);
+        }
+        Ok(Self::from(bits))
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for Observation {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self::try_from_i64(i64::from_sql(ty, raw)?)?)
+    }
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        <i64 as tokio_postgres::types::FromSql>::accepts(ty)
+    }
+}
+
 /// assemble Observation from private + public Hands
 impl From<(Hand, Hand)> for Observation {
     fn from((pocket, public): (Hand, Hand)) -> Self {
@@ -133,6 +251,8 @@ impl From<Observation> for Hand {
     }
 }
 
+/// parse hole~board notation, e.g. Kd8s~6dJsAc. whitespace
+/// between cards is optional, so Display round trips.
 impl TryFrom<&str> for Observation {
     type Error = String;
     fn try_from(s: &str) -> Result<Self, Self::Error> {
@@ -143,9 +263,24 @@ impl TryFrom<&str> for Observation {
         let pocket = Hand::try_from(pocket)?;
         let public = Hand::try_from(public)?;
         match (pocket.size(), public.size()) {
-            (2, 0) | (2, 3) | (2, 4) | (2, 5) => Ok(Self::from((pocket, public))),
-            _ => Err(format!("invalid card counts: {} {}", pocket, public)),
+            (2, 0 | 3 | 4 | 5) => {}
+            (2, n) => return Err(format!("{} board cards fit no street: {}", n, public)),
+            (n, _) => return Err(format!("{} hole cards, need 2: {}", n, pocket)),
         }
+        if u64::from(pocket) & u64::from(public) > 0 {
+            return Err(format!("duplicate card in hole and board: {}", s.trim()));
+        }
+        if (u64::from(pocket) | u64::from(public)) & !Hand::mask() > 0 {
+            return Err(format!("card not in the deck: {}", s.trim()));
+        }
+        Ok(Self::from((pocket, public)))
+    }
+}
+
+impl std::str::FromStr for Observation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
     }
 }
 
@@ -171,4 +306,127 @@ mod tests {
         let random = Observation::random();
         assert!(random == Observation::from(i64::from(random)));
     }
+
+    #[test]
+    fn validated_i64() {
+        let random = Observation::random();
+        let packed = i64::from(random);
+        assert!(random == Observation::try_from_i64(packed).unwrap());
+        assert!(Observation::try_from_i64(0).is_err());
+        assert!(Observation::try_from_i64(packed & !0xFF).is_err());
+        assert!(Observation::try_from_i64(packed | 0xFF).is_err());
+        assert!(Observation::try_from_i64(0x0101).is_err());
+    }
+
+    #[test]
+    fn bijective_str() {
+        for street in Street::all() {
+            let observation = Observation::from(*street);
+            assert!(observation == format!("{}", observation).parse().unwrap());
+        }
+        let parsed = "Kd8s~6dJsAc".parse::<Observation>().unwrap();
+        assert!(parsed.pocket() == &Hand::try_from("Kd 8s").unwrap());
+        assert!(parsed.public() == &Hand::try_from("6d Js Ac").unwrap());
+        assert!(parsed.street() == Street::Flop);
+        assert!("AsKs".parse::<Observation>().unwrap().street() == Street::Pref);
+    }
+
+    #[test]
+    fn malformed_str() {
+        assert!("".parse::<Observation>().is_err());
+        assert!("Kd~6dJsAc".parse::<Observation>().is_err());
+        assert!("Kd8sQh~6dJsAc".parse::<Observation>().is_err());
+        assert!("Kd8s~6dJs".parse::<Observation>().is_err());
+        assert!("Kd8s~6dJsAc9c2h3h".parse::<Observation>().is_err());
+        assert!("KdKd~6dJsAc".parse::<Observation>().is_err());
+        assert!("Kd8s~6dJsKd".parse::<Observation>().is_err());
+        assert!("Kd8s~6dJsA".parse::<Observation>().is_err());
+        assert!("Kd8s~6dJsAx".parse::<Observation>().is_err());
+    }
+
+    #[test]
+    fn children_count() {
+        for street in Street::all().iter().filter(|s| **s != Street::Rive) {
+            let observation = Observation::from(*street);
+            assert!(observation.children().count() == observation.children_count());
+        }
+    }
+
+    #[test]
+    fn equity_distribution_sums_to_one() {
+        for street in [Street::Turn, Street::Rive] {
+            let observation = Observation::from(street);
+            let histogram = observation.equity_distribution();
+            let total = histogram
+                .support()
+                .map(|abs| histogram.density(abs))
+                .sum::<Probability>();
+            assert!((total - 1.).abs() < 1e-4);
+            assert!(street != Street::Rive || histogram.n() <= 3); // win, tie, or lose
+        }
+    }
+
+    /// the distribution evaluated runout by runout, per opponent
+    fn uncached(observation: &Observation) -> Histogram {
+        let hand = Hand::from(*observation);
+        HandIterator::from((2, hand))
+            .map(|villain| {
+                let dead = Hand::add(hand, villain);
+                let (won, sum) = match 5 - observation.public().size() {
+                    0 => vec![Hand::empty()],
+                    n => HandIterator::from((n, dead)).collect::<Vec<Hand>>(),
+                }
+                .into_iter()
+                .map(|runout| Hand::add(*observation.public(), runout))
+                .map(|board| {
+                    let hero = Strength::from(Hand::add(*observation.pocket(), board));
+                    hero.cmp(&Strength::from(Hand::add(villain, board)))
+                })
+                .filter(|&ord| ord != Ordering::Equal)
+                .fold((0u32, 0u32), |(w, n), ord| match ord {
+                    Ordering::Greater => (w + 1, n + 1),
+                    _ => (w, n + 1),
+                });
+                match sum {
+                    0 => 0.5,
+                    _ => won as Probability / sum as Probability,
+                }
+            })
+            .map(Abstraction::from)
+            .fold(Histogram::default(), |hist, abs| hist.increment(abs))
+    }
+
+    #[test]
+    fn cached_distribution_agrees_with_uncached() {
+        let ref runouts = Runouts::new(64);
+        let agree = |o: &Observation| {
+            o.equity_distribution_with(runouts).distribution() == uncached(o).distribution()
+        };
+        for street in [Street::Turn, Street::Rive] {
+            let observation = Observation::from(street);
+            let public = *observation.public();
+            let neighbor = loop {
+                let pocket = *Observation::from(street).pocket();
+                if u64::from(pocket) & u64::from(public) == 0 {
+                    break Observation::from((pocket, public));
+                }
+            };
+            assert!(agree(&observation));
+            assert!(agree(&observation));
+            assert!(agree(&neighbor));
+        }
+    }
+
+    #[test]
+    fn canonical_representative() {
+        for street in Street::all() {
+            let observation = Observation::from(*street);
+            let representative = observation.canonical_representative();
+            assert!(Permutation::exhaust()
+                .iter()
+                .map(|p| p.permute(&observation))
+                .map(|o| o.canonical_representative())
+                .all(|o| o == representative));
+        }
+    }
 }
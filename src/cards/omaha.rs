@@ -0,0 +1,94 @@
+use super::hand::Hand;
+use super::hands::HandIterator;
+use super::strength::Strength;
+
+/// An evaluator for Omaha hands.
+///
+/// A showdown hand must use exactly two of the four hole cards
+/// and exactly three of the board cards. So unlike hold'em, a
+/// board flush or straight only plays if the hole cards can
+/// complete it. We score all 6 x C(board, 3) candidate five card
+/// hands with the base Evaluator and keep the best.
+pub struct OmahaEvaluator {
+    hole: Hand,
+    board: Hand,
+}
+
+impl From<(Hand, Hand)> for OmahaEvaluator {
+    fn from((hole, board): (Hand, Hand)) -> Self {
+        assert!(hole.size() == 4, "omaha needs four hole cards: {}", hole);
+        assert!(
+            board.size() >= 3,
+            "omaha needs three board cards: {}",
+            board
+        );
+        assert!(u64::from(hole) & u64::from(board) == 0);
+        Self { hole, board }
+    }
+}
+
+impl OmahaEvaluator {
+    pub fn strength(&self) -> Strength {
+        HandIterator::from((2, self.hole.complement()))
+            .flat_map(|hole| {
+                HandIterator::from((3, self.board.complement()))
+                    .map(move |board| Hand::add(hole, board))
+            })
+            .map(Strength::from)
+            .max()
+            .expect("at least one candidate hand")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn omaha(hole: &str, board: &str) -> Strength {
+        let hole = Hand::try_from(hole).unwrap();
+        let board = Hand::try_from(board).unwrap();
+        OmahaEvaluator::from((hole, board)).strength()
+    }
+    fn holdem(hole: &str, board: &str) -> Strength {
+        let hole = Hand::try_from(hole).unwrap();
+        let board = Hand::try_from(board).unwrap();
+        Strength::from(Hand::add(hole, board))
+    }
+    fn five(cards: &str) -> Strength {
+        Strength::from(Hand::try_from(cards).unwrap())
+    }
+
+    #[test]
+    fn board_flush_does_not_play() {
+        let (hole, board) = ("Ah Qc Qd 7s", "2h 5h 8h Jh Kh");
+        assert!(omaha(hole, board) == five("Qc Qd Kh Jh 8h"));
+        assert!(holdem(hole, board) == five("Ah Kh Jh 8h 5h"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn four_flush_in_hand_does_not_play() {
+        let (hole, board) = ("Ah Kh Qh Jh", "2h 5h 8c 9d 3s");
+        assert!(omaha(hole, board) == five("Ah Kh 9d 8c 5h"));
+        assert!(holdem(hole, board) == five("Ah Kh Qh Jh 5h"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn two_hole_hearts_complete_board_flush() {
+        let (hole, board) = ("Ah 2h Qc Qd", "5h 8h Jh 9c 3s");
+        assert!(omaha(hole, board) == five("Ah 2h 5h 8h Jh"));
+    }
+
+    #[test]
+    fn board_quads_do_not_play() {
+        let (hole, board) = ("2c 3d 7h 8s", "As Ah Ad Ac Kc");
+        assert!(omaha(hole, board) == five("As Ah Ad 8s 7h"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn three_hole_cards() {
+        omaha("Ah Kh Qh", "2h 5h 8c 9d 3s");
+    }
+}
@@ -4,6 +4,9 @@ use super::rank::Rank;
 ///
 /// This is a simplified version of the hand's value, and does not include the hand's kicker cards.
 /// The value is ordered by the hand's Strength, and the kicker cards are used to break ties.
+///
+/// Short deck strips the deuces through fives, so with only nine
+/// cards per suit a flush is rarer than a full house and beats it.
 #[cfg(feature = "shortdeck")]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub enum Ranking {
@@ -12,8 +15,8 @@ pub enum Ranking {
     TwoPair(Rank, Rank),   // 1 kickers
     ThreeOAK(Rank),        // 2 kickers
     Straight(Rank),        // 0 kickers
-    Flush(Rank),           // 0 kickers
     FullHouse(Rank, Rank), // 0 kickers
+    Flush(Rank),           // 0 kickers
     FourOAK(Rank),         // 1 kickers
     StraightFlush(Rank),   // 0 kickers
     MAX,                   // useful for showdown implementation
@@ -26,13 +29,38 @@ pub enum Ranking {
     TwoPair(Rank, Rank),   // 1 kickers
     ThreeOAK(Rank),        // 2 kickers
     Straight(Rank),        // 0 kickers
-    FullHouse(Rank, Rank), // 0 kickers
     Flush(Rank),           // 0 kickers
+    FullHouse(Rank, Rank), // 0 kickers
     FourOAK(Rank),         // 1 kickers
     StraightFlush(Rank),   // 0 kickers
     MAX,                   // useful for showdown implementation
 }
 
+impl Ranking {
+    /// position of this variant in the derived Ord, which
+    /// differs between deck variants
+    pub fn ordinal(&self) -> u32 {
+        match self {
+            Ranking::HighCard(_) => 0,
+            Ranking::OnePair(_) => 1,
+            Ranking::TwoPair(_, _) => 2,
+            Ranking::ThreeOAK(_) => 3,
+            Ranking::Straight(_) => 4,
+            #[cfg(feature = "shortdeck")]
+            Ranking::FullHouse(_, _) => 5,
+            #[cfg(feature = "shortdeck")]
+            Ranking::Flush(_) => 6,
+            #[cfg(not(feature = "shortdeck"))]
+            Ranking::Flush(_) => 5,
+            #[cfg(not(feature = "shortdeck"))]
+            Ranking::FullHouse(_, _) => 6,
+            Ranking::FourOAK(_) => 7,
+            Ranking::StraightFlush(_) => 8,
+            Ranking::MAX => 9,
+        }
+    }
+}
+
 impl std::fmt::Display for Ranking {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
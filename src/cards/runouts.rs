@@ -0,0 +1,46 @@
+use super::hand::Hand;
+use super::hands::HandIterator;
+use super::strength::Strength;
+use crate::analysis::lru::Lru;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+/// strength of every two card holding on a complete board
+pub type Table = Arc<HashMap<Hand, Strength>>;
+
+/// board-keyed cache of holding strength tables, for range
+/// tooling that evaluates many hands on the same board.
+///
+/// every hero hand, and every runout a hero hand passes through,
+/// would otherwise re-evaluate each opponent holding. tables are
+/// keyed by the complete five card board, so any hand whose
+/// runouts pass through that board shares them. bounded by an
+/// LRU and safe to share across threads.
+pub struct Runouts(Lru<Hand, Table>);
+
+impl Runouts {
+    pub fn new(capacity: usize) -> Self {
+        Self(Lru::new(capacity))
+    }
+    /// the process-wide cache, built on first use
+    pub fn shared() -> &'static Self {
+        static CACHE: OnceLock<Runouts> = OnceLock::new();
+        CACHE.get_or_init(|| Self::new(crate::RUNOUT_CACHE_CAPACITY))
+    }
+    /// every holding on this board, built once and shared
+    pub fn table(&self, board: Hand) -> Table {
+        assert!(board.size() == 5, "tables are built on complete boards");
+        match self.0.get(&board) {
+            Some(table) => table,
+            None => {
+                let table = HandIterator::from((2, board))
+                    .map(|holding| (holding, Strength::from(Hand::add(board, holding))))
+                    .collect::<HashMap<Hand, Strength>>();
+                let table = Arc::new(table);
+                self.0.put(board, table.clone());
+                table
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::evaluator::Evaluator;
+use super::hand::Hand;
+use super::kicks::Kickers;
+use super::ranking::Ranking;
+use std::cmp::Ordering;
+
+/// An evaluator for short deck hands, usable in any build.
+///
+/// Short deck strips the deuces through fives, so the ace plays
+/// low only in A6789, and a flush is rarer than a full house and
+/// beats it. Ranking derives the standard order unless the crate
+/// is built with the shortdeck feature, so rather than compare
+/// Strengths we order hands here under short deck rules.
+pub struct ShortDeckEvaluator(Evaluator);
+
+impl From<Hand> for ShortDeckEvaluator {
+    fn from(hand: Hand) -> Self {
+        Self(Evaluator::shortdeck(hand))
+    }
+}
+
+impl ShortDeckEvaluator {
+    pub fn find_ranking(&self) -> Ranking {
+        self.0.find_ranking()
+    }
+    pub fn find_kickers(&self, value: Ranking) -> Kickers {
+        self.0.find_kickers(value)
+    }
+    /// showdown order under short deck rules: by category, then
+    /// by the ranks that make the hand, then by kickers
+    pub fn cmp(&self, other: &Self) -> Ordering {
+        let (x, y) = (self.find_ranking(), other.find_ranking());
+        Self::order(&x)
+            .cmp(&Self::order(&y))
+            .then(x.cmp(&y))
+            .then(self.find_kickers(x).cmp(&other.find_kickers(y)))
+    }
+    fn order(ranking: &Ranking) -> u8 {
+        match ranking {
+            Ranking::HighCard(_) => 0,
+            Ranking::OnePair(_) => 1,
+            Ranking::TwoPair(_, _) => 2,
+            Ranking::ThreeOAK(_) => 3,
+            Ranking::Straight(_) => 4,
+            Ranking::FullHouse(_, _) => 5,
+            Ranking::Flush(_) => 6,
+            Ranking::FourOAK(_) => 7,
+            Ranking::StraightFlush(_) => 8,
+            Ranking::MAX => 9,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::rank::Rank;
+
+    fn shortdeck(hand: &str) -> ShortDeckEvaluator {
+        ShortDeckEvaluator::from(Hand::try_from(hand).unwrap())
+    }
+
+    #[test]
+    fn flush_beats_full_house() {
+        let flush = shortdeck("6s 8s Ts Qs As");
+        let house = shortdeck("As Ah Ad Kc Ks");
+        assert!(flush.find_ranking() == Ranking::Flush(Rank::Ace));
+        assert!(house.find_ranking() == Ranking::FullHouse(Rank::Ace, Rank::King));
+        assert!(flush.cmp(&house) == Ordering::Greater);
+        assert!(house.cmp(&flush) == Ordering::Less);
+    }
+
+    #[test]
+    fn ace_plays_low_above_the_fives() {
+        assert!(shortdeck("As 6h 7d 8c 9s").find_ranking() == Ranking::Straight(Rank::Nine));
+        assert!(shortdeck("As 2h 3d 4c 5s").find_ranking() == Ranking::HighCard(Rank::Ace));
+        assert!(shortdeck("As 6s 7s 8s 9s").find_ranking() == Ranking::StraightFlush(Rank::Nine));
+    }
+
+    #[test]
+    fn agrees_with_standard_below_the_flush() {
+        let trips = shortdeck("7s 7h 7d Ac Ks");
+        let straight = shortdeck("6s 7h 8d 9c Ts");
+        assert!(straight.cmp(&trips) == Ordering::Greater);
+        assert!(trips.cmp(&shortdeck("7s 7h 7d Ac Qs")) == Ordering::Greater);
+    }
+}
@@ -182,7 +182,7 @@ impl TryFrom<&str> for Street {
 impl crate::Arbitrary for Street {
     fn random() -> Self {
         use rand::Rng;
-        match rand::thread_rng().gen_range(0..4) {
+        match crate::rng().gen_range(0..4) {
             0 => Self::Pref,
             1 => Self::Flop,
             2 => Self::Turn,
@@ -1,6 +1,8 @@
+use super::category::HandCategory;
 use super::evaluator::Evaluator;
 use super::hand::Hand;
 use super::kicks::Kickers;
+use super::rank::Rank;
 use super::ranking::Ranking;
 
 /// A hand's strength.
@@ -8,12 +10,59 @@ use super::ranking::Ranking;
 /// This will always be constructed from a Hand, which is an unordered
 /// set of Cards. The strength is determined by the Hand's value, and the
 /// kicker cards are used to break ties.
+///
+/// Ord compares the Ranking first, then the kickers from highest
+/// to lowest. Kickers are a bitmask of distinct Ranks, so comparing
+/// them as integers is exactly that lexicographic comparison.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub struct Strength {
     value: Ranking,
     kicks: Kickers,
 }
 
+impl Strength {
+    /// made-hand category, ignoring ranks and kickers
+    pub fn category(&self) -> HandCategory {
+        HandCategory::from(self.value)
+    }
+    /// showdown between two hands. None means they split
+    /// the pot, otherwise which of self and other wins.
+    pub fn beats(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match self.cmp(other) {
+            std::cmp::Ordering::Equal => None,
+            ordering => Some(ordering),
+        }
+    }
+    /// coarser comparison that only looks at category
+    pub fn cmp_category(&self, other: &Self) -> std::cmp::Ordering {
+        self.category().cmp(&other.category())
+    }
+    /// stable integer encoding for storing outside this crate.
+    /// higher is stronger, and comparing scores agrees with Ord.
+    ///
+    /// bits 24..28  Ranking variant, see Ranking::ordinal
+    /// bits 20..24  primary rank (pair, trips, high card, ...)
+    /// bits 16..20  secondary rank (two pair, full house), else 0
+    /// bits  0..16  kicker rank mask
+    pub fn score(&self) -> u32 {
+        let (hi, lo) = match self.value {
+            Ranking::TwoPair(hi, lo) | Ranking::FullHouse(hi, lo) => (hi, lo),
+            Ranking::HighCard(hi)
+            | Ranking::OnePair(hi)
+            | Ranking::ThreeOAK(hi)
+            | Ranking::Straight(hi)
+            | Ranking::Flush(hi)
+            | Ranking::FourOAK(hi)
+            | Ranking::StraightFlush(hi) => (hi, Rank::Two),
+            Ranking::MAX => unreachable!(),
+        };
+        self.value.ordinal() << 24
+            | (u8::from(hi) as u32) << 20
+            | (u8::from(lo) as u32) << 16
+            | u16::from(self.kicks) as u32
+    }
+}
+
 impl From<Hand> for Strength {
     fn from(hand: Hand) -> Self {
         Self::from(Evaluator::from(hand))
@@ -39,3 +88,54 @@ impl std::fmt::Display for Strength {
         write!(f, "{:<18}", self.value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::Deck;
+
+    #[test]
+    #[cfg(not(feature = "shortdeck"))]
+    fn score_separates_categories() {
+        let hands = [
+            "As Kh 9d 7c 4s",
+            "2s 2h 7d 8c 9s",
+            "2s 2h 7d 7c 9s",
+            "2s 2h 2d 8c 9s",
+            "5s 6h 7d 8c 9s",
+            "2s 4s 7s 8s 9s",
+            "9s 9h 9d 2c 2s",
+            "2s 2h 2d 2c 9s",
+            "5s 6s 7s 8s 9s",
+        ]
+        .map(|hand| Strength::from(Hand::try_from(hand).unwrap()));
+        assert!(hands.windows(2).all(|w| w[0] < w[1]));
+        assert!(hands.windows(2).all(|w| w[0].score() < w[1].score()));
+    }
+
+    #[test]
+    fn beats_breaks_ties_on_kickers() {
+        use std::cmp::Ordering;
+        let strength = |hand: &str| Strength::from(Hand::try_from(hand).unwrap());
+        let kq = strength("As Ah Kd Qc 9s");
+        let kj = strength("Ac Ad Kh Jc Ts");
+        let kq_suited = strength("Ac Ad Kh Qh 9h");
+        assert!(kq.beats(&kj) == Some(Ordering::Greater));
+        assert!(kj.beats(&kq) == Some(Ordering::Less));
+        assert!(kq.beats(&kq_suited) == None);
+    }
+
+    #[test]
+    fn score_agrees_with_ord() {
+        for _ in 0..1024 {
+            let mut deck = Deck::new();
+            let a = Strength::from(
+                (0..7).fold(Hand::empty(), |h, _| Hand::add(h, Hand::from(deck.draw()))),
+            );
+            let b = Strength::from(
+                (0..7).fold(Hand::empty(), |h, _| Hand::add(h, Hand::from(deck.draw()))),
+            );
+            assert!(a.cmp(&b) == a.score().cmp(&b.score()));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::hand::Hand;
+use super::hands::HandIterator;
+use super::strength::Strength;
+
+/// the substitution search is combinatorial in the number of
+/// wild cards, so we refuse to evaluate more than a handful.
+const MAX_WILDS: usize = 3;
+
+/// An evaluator for hands containing wild cards.
+///
+/// Any card of the Hand that is also in the designated wild set
+/// stands in for whichever card maximizes Strength. We try every
+/// assignment of wilds to cards not already held and keep the best,
+/// delegating each candidate to the base Evaluator.
+pub struct WildCardEvaluator {
+    natural: Hand,
+    n: usize,
+}
+
+impl From<(Hand, Hand)> for WildCardEvaluator {
+    fn from((hand, wilds): (Hand, Hand)) -> Self {
+        let wild = Hand::from(u64::from(hand) & u64::from(wilds));
+        let natural = Hand::from(u64::from(hand) & !u64::from(wilds));
+        let n = wild.size();
+        assert!(n <= MAX_WILDS, "too many wild cards: {}", n);
+        Self { natural, n }
+    }
+}
+
+impl WildCardEvaluator {
+    pub fn strength(&self) -> Strength {
+        match self.n {
+            0 => Strength::from(self.natural),
+            n => HandIterator::from((n, self.natural))
+                .map(|substitute| Hand::add(self.natural, substitute))
+                .map(Strength::from)
+                .max()
+                .expect("at least one substitution"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::evaluator::Evaluator;
+    use crate::Arbitrary;
+
+    #[test]
+    fn no_wilds() {
+        let hand = Hand::random();
+        let wild = WildCardEvaluator::from((hand, Hand::empty()));
+        assert!(wild.strength() == Strength::from(Evaluator::from(hand)));
+    }
+
+    #[test]
+    fn wild_completes_quads() {
+        let hand = Hand::try_from("As Ah Ad Kc 7c").unwrap();
+        let wilds = Hand::try_from("7c").unwrap();
+        let quads = Hand::try_from("As Ah Ad Ac Kc").unwrap();
+        assert!(WildCardEvaluator::from((hand, wilds)).strength() == Strength::from(quads));
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_wilds() {
+        let hand = Hand::try_from("6c 7c 8c 9c").unwrap();
+        let _ = WildCardEvaluator::from((hand, hand));
+    }
+}
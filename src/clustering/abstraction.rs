@@ -1,4 +1,5 @@
 use crate::cards::street::Street;
+use crate::clustering::config::ClusterConfig;
 use crate::transport::support::Support;
 use crate::Arbitrary;
 use crate::Probability;
@@ -34,6 +35,26 @@ impl Abstraction {
     pub fn range() -> impl Iterator<Item = Self> {
         (0..=Self::N).map(|i| Self::from((Street::Rive, i as usize)))
     }
+    /// every Abstraction learned on a street under the
+    /// default cluster counts, see ClusterConfig::basis
+    pub fn basis(street: Street) -> Vec<Self> {
+        ClusterConfig::default().basis(street)
+    }
+    /// how many indices the street's abstraction space can hold.
+    /// learned streets are bounded by the index bits rather than
+    /// the default cluster count, so any ClusterConfig fits.
+    pub fn bound(street: Street) -> usize {
+        match street {
+            Street::Rive => Self::size(),
+            Street::Pref => street.k(),
+            _ => L as usize + 1,
+        }
+    }
+    /// whether we lie in the street's abstraction space, by
+    /// street tag and index range rather than by searching it
+    pub fn within(&self, street: Street) -> bool {
+        self.street() == street && self.index() < Self::bound(street)
+    }
     pub fn street(&self) -> Street {
         match self {
             Abstraction::Percent(n) | Abstraction::Learned(n) | Abstraction::Preflop(n) => {
@@ -62,6 +83,12 @@ impl Abstraction {
         let bits = M & bits;
         bits as usize
     }
+    /// equity at the center of a river bucket. buckets round to
+    /// the nearest of N + 1 evenly spaced equities, so the center
+    /// is that equity itself.
+    pub fn equity_of(&self) -> Probability {
+        Probability::from(*self)
+    }
     fn quantize(p: Probability) -> usize {
         (p * Self::N as Probability).round() as usize
     }
@@ -142,6 +169,51 @@ impl From<i64> for Abstraction {
         Self::from(n as u64)
     }
 }
+
+/// validated i64 conversion
+///
+/// the blanket TryFrom<i64> is already claimed by the infallible
+/// From<i64> above, which we keep for trusted internal paths.
+/// at the DB boundary we want to reject corrupt rows instead, so we
+/// check that street bits, index range, and hash signature all agree
+/// with what From<(Street, usize)> would have produced. learned
+/// indices are only bounded by what the index bits hold, so rows
+/// clustered with more than the default counts still load.
+impl Abstraction {
+    pub fn try_from_i64(n: i64) -> Result<Self, String> {
+        let bits = n as u64;
+        let street = match (H & bits) >> H.count_zeros() {
+            0 => Street::Pref,
+            1 => Street::Flop,
+            2 => Street::Turn,
+            3 => Street::Rive,
+            _ => return Err(format!("invalid street bits in abstraction {:#018x}", bits)),
+        };
+        let index = (L & bits) as usize;
+        if index >= Self::bound(street) {
+            return Err(format!(
+                "abstraction index {} out of range for {}",
+                index, street
+            ));
+        }
+        let abstraction = Self::from((street, index));
+        if u64::from(abstraction) != bits {
+            return Err(format!("abstraction signature mismatch {:#018x}", bits));
+        }
+        Ok(abstraction)
+    }
+}
+impl<'a> tokio_postgres::types::FromSql<'a> for Abstraction {
+    fn from_sql(
+        ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Self::try_from_i64(i64::from_sql(ty, raw)?)?)
+    }
+    fn accepts(ty: &tokio_postgres::types::Type) -> bool {
+        <i64 as tokio_postgres::types::FromSql>::accepts(ty)
+    }
+}
 /// string isomorophism
 impl TryFrom<&str> for Abstraction {
     type Error = Box<dyn std::error::Error>;
@@ -176,7 +248,7 @@ impl Arbitrary for Abstraction {
         use rand::Rng;
         let street = Street::Flop;
         let k = street.k();
-        let i = rand::thread_rng().gen_range(0..k);
+        let i = crate::rng().gen_range(0..k);
         Abstraction::from((street, i))
     }
 }
@@ -190,6 +262,12 @@ mod tests {
     use crate::cards::street::Street;
     use crate::Arbitrary;
 
+    #[test]
+    fn equity_of_bucket_center() {
+        assert!(Abstraction::from(0.5).equity_of() == 0.5);
+        assert!(Abstraction::range().all(|a| Abstraction::from(a.equity_of()) == a));
+    }
+
     #[test]
     fn is_quantize_inverse_floatize() {
         for p in (0..=100).map(|x| x as Probability / 100.) {
@@ -217,6 +295,39 @@ mod tests {
         assert_eq!(equity, Abstraction::from(u64::from(equity)));
     }
     #[test]
+    fn within_street() {
+        let random = Abstraction::random();
+        let beyond = Abstraction::from((Street::Rive, Abstraction::bound(Street::Rive)));
+        assert!(random.within(Street::Flop));
+        assert!(!random.within(Street::Turn));
+        assert!(!beyond.within(Street::Rive));
+    }
+    #[test]
+    fn more_clusters_than_default() {
+        let learned = Abstraction::from((Street::Turn, Street::Turn.k() + 1));
+        assert!(learned.within(Street::Turn));
+        assert_eq!(
+            learned,
+            Abstraction::try_from_i64(i64::from(learned)).unwrap()
+        );
+    }
+    #[test]
+    fn validated_i64() {
+        let random = Abstraction::random();
+        let equity = Abstraction::from(Observation::from(Street::Rive).equity());
+        assert_eq!(
+            random,
+            Abstraction::try_from_i64(i64::from(random)).unwrap()
+        );
+        assert_eq!(
+            equity,
+            Abstraction::try_from_i64(i64::from(equity)).unwrap()
+        );
+        assert!(Abstraction::try_from_i64(i64::from(random) ^ 0x1000).is_err());
+        assert!(Abstraction::try_from_i64(i64::from(random) | 0xFFF).is_err());
+        assert!(Abstraction::try_from_i64(-1).is_err());
+    }
+    #[test]
     fn bijective_str() {
         let abs = Abstraction::random();
         let str = format!("{}", abs);
@@ -0,0 +1,201 @@
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use crate::clustering::config::ClusterConfig;
+use crate::clustering::histogram::Histogram;
+use crate::Save;
+
+/// kmeans centroids as of some completed iteration, so an
+/// interrupted Layer can pick up where it left off instead of
+/// starting over from kmeans++.
+///
+/// unlike Decomp, which stores normalized transition weights,
+/// we keep raw counts. centroid masses grow with every point
+/// absorbed, and resuming has to see exactly the same Histograms
+/// for the remaining iterations to match an uninterrupted run.
+/// that also takes the same seed and ClusterConfig, so we store
+/// both and refuse to resume a run that was set up differently.
+pub struct Checkpoint {
+    street: Street,
+    iteration: usize,
+    seed: u64,
+    config: ClusterConfig,
+    kmeans: Vec<Histogram>,
+}
+
+impl Checkpoint {
+    /// iterations completed when these centroids were saved
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+    /// whether a run with this seed and config can pick up here.
+    /// we only store and compare what shapes the centroids,
+    /// see ClusterConfig::resumes.
+    pub fn resumable(&self, seed: u64, config: ClusterConfig) -> Result<(), String> {
+        if self.seed != seed {
+            return Err(format!("checkpoint seed {} is not {}", self.seed, seed));
+        }
+        if !self.config.resumes(&config) {
+            return Err(format!(
+                "checkpoint config {:?} is not {:?}",
+                self.config, config
+            ));
+        }
+        Ok(())
+    }
+    /// centroids by kmeans index. a centroid that no point was
+    /// assigned to has no rows, so it comes back empty, and we
+    /// pad up to k for the same reason.
+    pub fn kmeans(self, k: usize) -> Vec<Histogram> {
+        assert!(
+            self.kmeans.len() <= k,
+            "checkpoint has more than {} centroids",
+            k
+        );
+        let mut kmeans = self.kmeans;
+        kmeans.resize(k, Histogram::default());
+        kmeans
+    }
+    /// drop the checkpoint once the finished Layer is saved
+    pub fn clear(street: Street) {
+        let ref path = Self::path(street);
+        if Self::done(street) {
+            std::fs::remove_file(path).expect(&format!("remove {}", path));
+        }
+    }
+    /// load() from somewhere other than the default path
+    pub fn load_from(street: Street, path: &str) -> Self {
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        use std::fs::File;
+        use std::io::BufReader;
+        use std::io::Read;
+        use std::io::Seek;
+        use std::io::SeekFrom;
+        let ref file = File::open(path).expect(&format!("open {}", path));
+        let mut reader = BufReader::new(file);
+        let mut buffer = [0u8; 2];
+        reader.seek(SeekFrom::Start(19)).expect("seek past header");
+        reader.read_exact(&mut buffer).expect("read run");
+        assert!(
+            u16::from_be_bytes(buffer) == 7,
+            "checkpoint starts with its run"
+        );
+        let mut run = [0i64; 7];
+        for field in run.iter_mut() {
+            reader.read_u32::<BE>().expect("run length");
+            *field = reader.read_i64::<BE>().expect("read run");
+        }
+        let seed = run[0] as u64;
+        let [iteration, flop_k, flop_t, turn_k, turn_t, batch] =
+            [run[1], run[2], run[3], run[4], run[5], run[6]].map(|x| x as usize);
+        let config = ClusterConfig::default()
+            .with_flop(flop_k, flop_t)
+            .with_turn(turn_k, turn_t)
+            .with_batch(batch);
+        let mut kmeans = Vec::<Histogram>::new();
+        while reader.read_exact(&mut buffer).is_ok() {
+            if u16::from_be_bytes(buffer) == 3 {
+                reader.read_u32::<BE>().expect("centroid");
+                let k = reader.read_i64::<BE>().expect("read centroid") as usize;
+                reader.read_u32::<BE>().expect("abstraction");
+                let abs = reader.read_i64::<BE>().expect("read abstraction");
+                reader.read_u32::<BE>().expect("count");
+                let count = reader.read_i64::<BE>().expect("read count") as usize;
+                if kmeans.len() <= k {
+                    kmeans.resize(k + 1, Histogram::default());
+                }
+                kmeans[k].set(Abstraction::from(abs), count);
+                continue;
+            } else {
+                break;
+            }
+        }
+        Self {
+            street,
+            iteration,
+            seed,
+            config,
+            kmeans,
+        }
+    }
+    /// save() somewhere other than the default path. we write
+    /// next to it and rename into place, so that a run killed
+    /// mid-write leaves the previous checkpoint intact.
+    pub fn save_to(&self, path: &str) {
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::fs::File;
+        use std::io::Write;
+        let ref temp = format!("{}.tmp", path);
+        let ref mut file = File::create(temp).expect(&format!("touch {}", temp));
+        file.write_all(b"PGCOPY\n\xFF\r\n\0").expect("header");
+        file.write_u32::<BE>(0).expect("flags");
+        file.write_u32::<BE>(0).expect("extension");
+        let run = [
+            self.seed as i64,
+            self.iteration as i64,
+            self.config.k(Street::Flop) as i64,
+            self.config.t(Street::Flop) as i64,
+            self.config.k(Street::Turn) as i64,
+            self.config.t(Street::Turn) as i64,
+            self.config.batch() as i64,
+        ];
+        file.write_u16::<BE>(run.len() as u16).unwrap();
+        for field in run {
+            file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+            file.write_i64::<BE>(field).unwrap();
+        }
+        for (k, histogram) in self.kmeans.iter().enumerate() {
+            for abs in histogram.support() {
+                const N_FIELDS: u16 = 3;
+                file.write_u16::<BE>(N_FIELDS).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(k as i64).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(i64::from(*abs)).unwrap();
+                file.write_u32::<BE>(size_of::<i64>() as u32).unwrap();
+                file.write_i64::<BE>(histogram.count(abs) as i64).unwrap();
+            }
+        }
+        file.write_u16::<BE>(0xFFFF).expect("trailer");
+        file.sync_all().expect(&format!("sync {}", temp));
+        std::fs::rename(temp, path).expect(&format!("rename {}", temp));
+    }
+}
+
+impl From<(Street, usize, u64, ClusterConfig, &[Histogram])> for Checkpoint {
+    fn from(
+        (street, iteration, seed, config, kmeans): (
+            Street,
+            usize,
+            u64,
+            ClusterConfig,
+            &[Histogram],
+        ),
+    ) -> Self {
+        Self {
+            street,
+            iteration,
+            seed,
+            config,
+            kmeans: kmeans.to_vec(),
+        }
+    }
+}
+
+impl Save for Checkpoint {
+    fn name() -> &'static str {
+        "pgcopy.checkpoint."
+    }
+    fn make(street: Street) -> Self {
+        unreachable!("checkpoints come from a running Layer, not from scratch {street}")
+    }
+    fn load(street: Street) -> Self {
+        log::info!("{:<32}{:<32}", "loading     checkpoint", street);
+        Self::load_from(street, &Self::path(street))
+    }
+    fn save(&self) {
+        log::info!("{:<32}{:<32}", "saving      checkpoint", self.street);
+        self.save_to(&Self::path(self.street))
+    }
+}
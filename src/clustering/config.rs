@@ -0,0 +1,154 @@
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use crate::Energy;
+
+/// how many clusters kmeans learns on each street, for how many
+/// iterations at most, and how many points each iteration samples.
+/// also how the metric between the learned clusters is stored.
+/// defaults to the KMEANS_* crate consts, so sweeping
+/// granularity from a script doesn't need a rebuild.
+///
+/// Street::k() is only the default. validation and the database
+/// loaders bound indices by what an Abstraction can hold, so any
+/// count up to that round trips through everything downstream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClusterConfig {
+    flop: (usize, usize),
+    turn: (usize, usize),
+    tolerance: Energy,
+    batch: usize,
+    neighbors: usize,
+    far: Energy,
+    power: Energy,
+    repair: bool,
+}
+
+impl ClusterConfig {
+    pub fn with_flop(mut self, k: usize, t: usize) -> Self {
+        assert!(k > 0, "at least one flop cluster");
+        assert!(
+            k <= Abstraction::bound(Street::Flop),
+            "too many flop clusters"
+        );
+        self.flop = (k, t);
+        self
+    }
+    pub fn with_turn(mut self, k: usize, t: usize) -> Self {
+        assert!(k > 0, "at least one turn cluster");
+        assert!(
+            k <= Abstraction::bound(Street::Turn),
+            "too many turn clusters"
+        );
+        self.turn = (k, t);
+        self
+    }
+    /// stop short of the iteration count once RMS loss changes by
+    /// at most this fraction between iterations. 0 stops only on
+    /// an exactly repeated loss, after which nothing would change.
+    pub fn with_tolerance(mut self, tolerance: Energy) -> Self {
+        assert!(tolerance >= 0., "nonnegative loss tolerance");
+        self.tolerance = tolerance;
+        self
+    }
+    /// mini-batch kmeans over this many sampled points per
+    /// iteration. 0, or anything at least the number of points,
+    /// assigns every point every iteration as usual.
+    pub fn with_batch(mut self, batch: usize) -> Self {
+        self.batch = batch;
+        self
+    }
+    /// store only the k nearest distances from each cluster, and
+    /// read every dropped pair as far apart. 0 stores them all.
+    pub fn with_neighbors(mut self, k: usize, far: Energy) -> Self {
+        self.neighbors = k;
+        self.far = far;
+        self
+    }
+    /// store distances raised to this power. above 1 sharpens
+    /// the separation of far clusters, 1 keeps the plain emd.
+    pub fn with_power(mut self, power: Energy) -> Self {
+        assert!(power > 0., "metric power must be positive");
+        self.power = power;
+        self
+    }
+    /// close stored distances under shortest paths, so that
+    /// they obey the triangle inequality
+    pub fn with_repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+    /// cluster count for this street. streets we don't
+    /// learn keep whatever Street::k() says.
+    pub fn k(&self, street: Street) -> usize {
+        match street {
+            Street::Flop => self.flop.0,
+            Street::Turn => self.turn.0,
+            _ => street.k(),
+        }
+    }
+    /// every Abstraction learned on this street
+    pub fn basis(&self, street: Street) -> Vec<Abstraction> {
+        match street {
+            Street::Rive => Abstraction::range().collect(),
+            _ => (0..self.k(street))
+                .map(|i| Abstraction::from((street, i)))
+                .collect(),
+        }
+    }
+    /// kmeans iterations for this street
+    pub fn t(&self, street: Street) -> usize {
+        match street {
+            Street::Flop => self.flop.1,
+            Street::Turn => self.turn.1,
+            _ => street.t(),
+        }
+    }
+    /// relative change in loss that counts as converged
+    pub fn tolerance(&self) -> Energy {
+        self.tolerance
+    }
+    /// points sampled per iteration, 0 for full batch
+    pub fn batch(&self) -> usize {
+        self.batch
+    }
+    /// nearest distances kept per cluster, 0 for a dense metric
+    pub fn neighbors(&self) -> usize {
+        self.neighbors
+    }
+    /// distance of the pairs a sparse metric dropped, None when
+    /// the metric is dense and drops nothing
+    pub fn far(&self) -> Option<Energy> {
+        match self.neighbors {
+            0 => None,
+            _ => Some(self.far),
+        }
+    }
+    pub fn power(&self) -> Energy {
+        self.power
+    }
+    pub fn repair(&self) -> bool {
+        self.repair
+    }
+    /// whether a run under the other config learns the same
+    /// centroids iteration for iteration. the tolerance only
+    /// decides when a run stops, and the metric options only
+    /// what it stores once it has, so neither counts here.
+    pub fn resumes(&self, other: &Self) -> bool {
+        self.flop == other.flop && self.turn == other.turn && self.batch == other.batch
+    }
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            flop: (Street::Flop.k(), Street::Flop.t()),
+            turn: (Street::Turn.k(), Street::Turn.t()),
+            tolerance: 0.,
+            batch: crate::KMEANS_BATCH_SIZE,
+            neighbors: crate::KMEANS_METRIC_NEIGHBORS,
+            far: crate::KMEANS_METRIC_FAR,
+            power: crate::KMEANS_METRIC_POWER,
+            repair: crate::KMEANS_METRIC_REPAIR,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::exact::Exact;
 use super::heuristic::Heuristic;
 use super::histogram::Histogram;
 use super::metric::Metric;
@@ -15,12 +16,15 @@ impl EMD {
     pub fn metric(&self) -> &Metric {
         &self.0
     }
-    pub fn sinkhorn(&self) -> Sinkhorn {
+    pub fn sinkhorn(&self) -> Sinkhorn<'_> {
         Sinkhorn::from((&self.1, &self.2, &self.0)).minimize()
     }
-    pub fn heuristic(&self) -> Heuristic {
+    pub fn heuristic(&self) -> Heuristic<'_> {
         Heuristic::from((&self.1, &self.2, &self.0)).minimize()
     }
+    pub fn exact(&self) -> Exact<'_> {
+        Exact::from((&self.1, &self.2, &self.0)).minimize()
+    }
     pub fn inner(self) -> (Metric, Histogram, Histogram, Histogram) {
         (self.0, self.1, self.2, self.3)
     }
@@ -30,7 +34,7 @@ impl Arbitrary for EMD {
     fn random() -> Self {
         // construct random metric satisfying symmetric semipositivity
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng();
         let p = Histogram::random();
         let q = Histogram::random();
         let r = Histogram::random();
@@ -60,6 +64,7 @@ mod tests {
     use super::*;
     use crate::cards::observation::Observation;
     use crate::cards::street::Street;
+    use crate::clustering::equity::Equity;
     use crate::clustering::histogram::Histogram;
 
     /// equity implementation should be
@@ -119,6 +124,17 @@ mod tests {
         assert!(d21 > 0., "{}", d21);
     }
     #[test]
+    fn is_sinkhorn_emd_sparse() {
+        let EMD(metric, h1, h2, h3) = EMD::random();
+        let mut padded = h1.clone();
+        for x in h3.support().filter(|x| h1.density(x) == 0.) {
+            padded.set(*x, 0);
+        }
+        let sparse = Sinkhorn::from((&h1, &h2, &metric)).minimize().cost();
+        let padded = Sinkhorn::from((&padded, &h2, &metric)).minimize().cost();
+        assert!(sparse == padded, "{} != {}", sparse, padded);
+    }
+    #[test]
     fn is_sinkhorn_emd_zero() {
         const TOLERANCE: f32 = 0.01;
         let EMD(metric, h1, h2, _) = EMD::random();
@@ -161,4 +177,35 @@ mod tests {
         assert!(d11 == 0.);
         assert!(d22 == 0.);
     }
+
+    /// exact implementation should be
+    /// 1. exactly self-annihilating
+    /// 2. a lower bound on the heuristic
+    /// 3. the same as Equity's closed form on the line
+
+    #[test]
+    fn is_exact_emd_zero() {
+        let EMD(metric, h1, _, _) = EMD::random();
+        let d11 = Exact::from((&h1, &h1, &metric)).minimize().cost();
+        assert!(d11 == 0.);
+    }
+    #[test]
+    fn is_exact_emd_below_heuristic() {
+        const TOLERANCE: f32 = 1e-4;
+        for _ in 0..16 {
+            let emd = EMD::random();
+            let exact = emd.exact().cost();
+            let heuristic = emd.heuristic().cost();
+            assert!(heuristic >= exact - TOLERANCE, "{} < {}", heuristic, exact);
+        }
+    }
+    #[test]
+    fn is_exact_emd_equity_variation() {
+        let metric = Metric::default();
+        let ref h1 = Histogram::from(Observation::from(Street::Turn));
+        let ref h2 = Histogram::from(Observation::from(Street::Turn));
+        let exact = Exact::from((h1, h2, &metric)).minimize().cost();
+        let variation = 2. * Equity::variation(h1, h2);
+        assert!((exact - variation).abs() < 1e-4, "{} {}", exact, variation);
+    }
 }
@@ -1,7 +1,9 @@
 use super::abstraction::Abstraction;
 use super::histogram::Histogram;
+use crate::cards::street::Street;
 use crate::transport::measure::Measure;
 use crate::Energy;
+use crate::Probability;
 
 /// useful struct for grouping methods that help in calculating
 /// optimal transport between two Equity Histograms.
@@ -12,14 +14,17 @@ use crate::Energy;
 /// we can think of the constraint as being probabilistic unitarity.
 /// equivalently, we constrain the coupling to be doubly stochastic
 /// over the product of support spaces, i.e. [0, 1] x [0, 1].
+/// Monotone stores that coupling when you need flow(x, y).
 pub struct Equity;
 
 impl Measure for Equity {
     type X = Abstraction; //::Equity(i8) variant
     type Y = Abstraction; //::Equity(i8) variant
+    /// the gap between bucket indices, normalized by the number of
+    /// buckets. Metric::with_centers spaces them by equity instead.
     fn distance(&self, x: &Self::X, y: &Self::Y) -> f32 {
         match (x, y) {
-            (Self::X::Percent(x), Self::Y::Percent(y)) => (*x as f32 - *y as f32).abs() / Abstraction::size() as f32,
+            (Self::X::Percent(_), Self::Y::Percent(_)) => (x.index() as f32 - y.index() as f32).abs() / Abstraction::size() as f32,
             _ => unreachable!("should make Abstraction::distance a thing. perhaps Self::X should be f32 to avoid this pattern match"),
         }
     }
@@ -29,7 +34,82 @@ impl Measure for Equity {
 /// conveniently have properties of distributions over the [0, 1] interval.
 #[allow(dead_code)]
 impl Equity {
+    /// mean equity of the members of each river bucket, by bucket
+    /// index. equities round to the nearest bucket, so members sit
+    /// off center wherever hands bunch up. buckets nobody lands in
+    /// keep their nominal center.
+    pub fn centers(equities: impl IntoIterator<Item = Probability>) -> Vec<Probability> {
+        let mut sums = vec![(0., 0usize); Abstraction::size()];
+        for equity in equities {
+            let (sum, n) = &mut sums[Abstraction::from(equity).index()];
+            *sum += equity;
+            *n += 1;
+        }
+        sums.into_iter()
+            .enumerate()
+            .map(|(i, (sum, n))| match n {
+                0 => Abstraction::from((Street::Rive, i)).equity_of(),
+                n => sum / n as Probability,
+            })
+            .collect()
+    }
+    /// total variation between CDFs, visiting only buckets where
+    /// either Histogram has mass. both CDFs are flat across a run of
+    /// empty buckets, so each run contributes its constant gap times
+    /// its length. agrees with variation_dense up to float rounding.
     pub fn variation(x: &Histogram, y: &Histogram) -> Energy {
+        Self::runs(x, y)
+            .scan((0., 0.), |cdf, (abstraction, run)| {
+                cdf.0 += x.density(&abstraction);
+                cdf.1 += y.density(&abstraction);
+                Some((cdf.0 - cdf.1).abs() * run as Energy)
+            })
+            .sum::<Energy>()
+            / Abstraction::size() as Energy
+            / 2.
+    }
+    /// variation() accumulated in double precision. running CDFs
+    /// near 1/2 can't resolve a mass of 1e-7 in f32, so nearly
+    /// identical Histograms come out spuriously far apart.
+    /// too slow for the clustering loop, fine for one-off queries.
+    pub fn variation_f64(x: &Histogram, y: &Histogram) -> Energy {
+        (Self::runs(x, y)
+            .scan((0f64, 0f64), |cdf, (abstraction, run)| {
+                cdf.0 += x.density_f64(&abstraction);
+                cdf.1 += y.density_f64(&abstraction);
+                Some((cdf.0 - cdf.1).abs() * run as f64)
+            })
+            .sum::<f64>()
+            / Abstraction::size() as f64
+            / 2.) as Energy
+    }
+    /// buckets where either Histogram has mass, paired with
+    /// the length of the flat run of buckets they begin
+    fn runs<'a>(
+        x: &'a Histogram,
+        y: &'a Histogram,
+    ) -> impl Iterator<Item = (Abstraction, usize)> + 'a {
+        let mut indices = x
+            .support()
+            .chain(y.support())
+            .filter(|a| x.density(a) > 0. || y.density(a) > 0.)
+            .map(|a| a.index())
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        indices.dedup();
+        let ends = indices
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(std::iter::once(Abstraction::size()))
+            .collect::<Vec<usize>>();
+        indices
+            .into_iter()
+            .zip(ends)
+            .map(|(i, j)| (Abstraction::from((Street::Rive, i)), j - i))
+    }
+    /// total variation between CDFs over every bucket in the domain
+    pub fn variation_dense(x: &Histogram, y: &Histogram) -> Energy {
         Abstraction::range()
             .map(|abstraction| (x.density(&abstraction), y.density(&abstraction)))
             .scan((0., 0.), |cdf, (px, py)| {
@@ -44,6 +124,15 @@ impl Equity {
             / Abstraction::size() as Energy
             / 2.
     }
+    /// distance between (mean, standard deviation) summaries.
+    /// made hands and draws with the same mean equity differ
+    /// in spread, so this keeps them apart with two numbers
+    /// per Histogram instead of the whole distribution.
+    pub fn potential(x: &Histogram, y: &Histogram) -> Energy {
+        let mean = x.equity() - y.equity();
+        let spread = x.potential() - y.potential();
+        (mean * mean + spread * spread).sqrt()
+    }
     pub fn euclidean(x: &Histogram, y: &Histogram) -> Energy {
         Abstraction::range()
             .map(|abstraction| x.density(&abstraction) - y.density(&abstraction))
@@ -64,3 +153,93 @@ impl Equity {
             .sum::<Energy>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_variation_matches_dense() {
+        for _ in 0..64 {
+            let x = Histogram::from(
+                (0..8)
+                    .map(|_| Abstraction::from(rand::Rng::gen::<f32>(&mut crate::rng())))
+                    .collect::<Vec<Abstraction>>(),
+            );
+            let y = Histogram::from(
+                (0..3)
+                    .map(|_| Abstraction::from(rand::Rng::gen::<f32>(&mut crate::rng())))
+                    .collect::<Vec<Abstraction>>(),
+            );
+            let sparse = Equity::variation(&x, &y);
+            let dense = Equity::variation_dense(&x, &y);
+            assert!((sparse - dense).abs() < 1e-5, "{} != {}", sparse, dense);
+        }
+    }
+
+    #[test]
+    fn variation_f64_resolves_tiny_masses() {
+        // shift one unit of mass by one bucket out of n. the running
+        // CDFs sit near 1/2 where f32 can't represent 1/n exactly,
+        // and the rounding error is smeared across the long run
+        // that follows.
+        let n = 10_000_000usize;
+        let a = Abstraction::from((Street::Rive, 10));
+        let b = Abstraction::from((Street::Rive, 11));
+        let c = Abstraction::from((Street::Rive, Abstraction::size() - 1));
+        let mut x = Histogram::default();
+        x.set(a, n / 2);
+        x.set(c, n / 2);
+        let mut y = Histogram::default();
+        y.set(a, n / 2 - 1);
+        y.set(b, 1);
+        y.set(c, n / 2);
+        let exact = 1. / n as f64 / Abstraction::size() as f64 / 2.;
+        let err32 = (Equity::variation(&x, &y) as f64 - exact).abs();
+        let err64 = (Equity::variation_f64(&x, &y) as f64 - exact).abs();
+        assert!(err64 < err32, "{} !< {}", err64, err32);
+        assert!(err64 / exact < 1e-6);
+        assert!(Equity::variation_f64(&x, &x) == 0.);
+    }
+
+    #[test]
+    fn potential_separates_draws() {
+        let equities = |xs: &[f32]| {
+            Histogram::from(
+                xs.iter()
+                    .copied()
+                    .map(Abstraction::from)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let made = equities(&[0.5, 0.5, 0.5, 0.5]);
+        let draw = equities(&[0.0, 0.0, 1.0, 1.0]);
+        let centroids = [
+            equities(&[0.45, 0.45, 0.45, 0.45]),
+            equities(&[0.05, 0.05, 0.95, 0.95]),
+        ];
+        let nearest = |h: &Histogram| {
+            (0..2)
+                .min_by(|&i, &j| {
+                    let di = Equity::potential(h, &centroids[i]);
+                    let dj = Equity::potential(h, &centroids[j]);
+                    di.partial_cmp(&dj).unwrap()
+                })
+                .unwrap()
+        };
+        assert!((made.equity() - draw.equity()).abs() < 0.02);
+        assert!(Equity::potential(&made, &draw) > 0.4);
+        assert!(nearest(&made) == 0);
+        assert!(nearest(&draw) == 1);
+    }
+
+    #[test]
+    fn distance_between_extremes() {
+        let lo = Abstraction::from(0.);
+        let hi = Abstraction::from(1.);
+        let d = Equity.distance(&lo, &hi);
+        assert!(Equity.distance(&lo, &lo) == 0.);
+        assert!(d > 0.99 * (Abstraction::size() - 1) as f32 / Abstraction::size() as f32);
+        assert!(d <= 1.);
+    }
+}
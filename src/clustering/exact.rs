@@ -0,0 +1,166 @@
+use super::abstraction::Abstraction;
+use super::histogram::Histogram;
+use super::metric::Metric;
+use crate::transport::coupling::Coupling;
+use crate::transport::measure::Measure;
+use crate::Energy;
+use crate::Probability;
+use std::collections::BTreeMap;
+
+/// the exact optimal transport plan, as a baseline for
+/// Sinkhorn and Heuristic. far too slow for clustering, but
+/// the supports we test on have a few dozen Abstractions.
+///
+/// Histograms are counts, so scaling each source count by the
+/// target's total and vice versa makes every supply and demand
+/// an integer with the same sum. the transportation problem is
+/// then a min cost flow, which we solve by successive shortest
+/// paths: repeatedly push as much mass as fits along the
+/// cheapest route from a source with supply left to a target
+/// with demand left, where routes may undo earlier flow at
+/// negative cost. integer capacities mean this stops, and
+/// cheapest-first means the final plan is optimal.
+pub struct Exact<'a> {
+    plan: BTreeMap<(Abstraction, Abstraction), Probability>,
+    metric: &'a Metric,
+    source: &'a Histogram,
+    target: &'a Histogram,
+}
+
+impl Exact<'_> {
+    /// probability mass moved from x to y
+    pub fn mass(&self, x: &Abstraction, y: &Abstraction) -> Probability {
+        self.plan.get(&(*x, *y)).copied().unwrap_or(0.)
+    }
+    /// every support point with its count scaled by the
+    /// other side's total, so both sides sum to the same
+    fn scaled(h: &Histogram, n: usize) -> Vec<(Abstraction, u64)> {
+        h.support()
+            .map(|x| (*x, (h.count(x) * n) as u64))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+    /// cheapest residual path from any source with supply left,
+    /// by Bellman-Ford over sources 0..n then targets n..n + m.
+    /// forward arcs cost the distance and never fill up, while
+    /// backward arcs exist only where flow can be undone.
+    fn shortest(
+        cost: &[Vec<f64>],
+        flow: &[Vec<u64>],
+        supply: &[u64],
+    ) -> (Vec<f64>, Vec<Option<usize>>) {
+        let (n, m) = (cost.len(), cost[0].len());
+        let mut dist = vec![f64::INFINITY; n + m];
+        let mut prev = vec![None; n + m];
+        for i in (0..n).filter(|&i| supply[i] > 0) {
+            dist[i] = 0.;
+        }
+        for _ in 0..n + m {
+            let mut relaxed = false;
+            for i in 0..n {
+                for j in 0..m {
+                    if dist[i] + cost[i][j] < dist[n + j] - 1e-12 {
+                        dist[n + j] = dist[i] + cost[i][j];
+                        prev[n + j] = Some(i);
+                        relaxed = true;
+                    }
+                    if flow[i][j] > 0 && dist[n + j] - cost[i][j] < dist[i] - 1e-12 {
+                        dist[i] = dist[n + j] - cost[i][j];
+                        prev[i] = Some(n + j);
+                        relaxed = true;
+                    }
+                }
+            }
+            if !relaxed {
+                break;
+            }
+        }
+        (dist, prev)
+    }
+}
+
+impl Coupling for Exact<'_> {
+    type X = Abstraction;
+    type Y = Abstraction;
+    type P = Histogram;
+    type Q = Histogram;
+    type M = Metric;
+
+    fn minimize(mut self) -> Self {
+        self.plan.clear();
+        let xs = Self::scaled(self.source, self.target.mass());
+        let ys = Self::scaled(self.target, self.source.mass());
+        let (n, m) = (xs.len(), ys.len());
+        let cost = xs
+            .iter()
+            .map(|(x, _)| {
+                ys.iter()
+                    .map(|(y, _)| self.metric.distance(x, y) as f64)
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let mut supply = xs.iter().map(|(_, c)| *c).collect::<Vec<u64>>();
+        let mut demand = ys.iter().map(|(_, c)| *c).collect::<Vec<u64>>();
+        let mut flow = vec![vec![0u64; m]; n];
+        while supply.iter().any(|&s| s > 0) {
+            let (dist, prev) = Self::shortest(&cost, &flow, &supply);
+            let j = (0..m)
+                .filter(|&j| demand[j] > 0)
+                .min_by(|&a, &b| dist[n + a].partial_cmp(&dist[n + b]).unwrap())
+                .expect("supply and demand sum to the same total");
+            // walk back to the source this path starts from,
+            // collecting the arcs and how much each can carry
+            let mut arcs = Vec::new();
+            let mut node = n + j;
+            let mut mass = demand[j];
+            while let Some(back) = prev[node] {
+                match node >= n {
+                    true => arcs.push((back, node - n, true)),
+                    false => {
+                        mass = mass.min(flow[node][back - n]);
+                        arcs.push((node, back - n, false));
+                    }
+                }
+                node = back;
+            }
+            mass = mass.min(supply[node]);
+            for (i, j, forward) in arcs {
+                match forward {
+                    true => flow[i][j] += mass,
+                    false => flow[i][j] -= mass,
+                }
+            }
+            supply[node] -= mass;
+            demand[j] -= mass;
+        }
+        let total = (self.source.mass() * self.target.mass()) as f64;
+        for (i, (x, _)) in xs.iter().enumerate() {
+            for (j, (y, _)) in ys.iter().enumerate() {
+                if flow[i][j] > 0 {
+                    let mass = flow[i][j] as f64 / total;
+                    self.plan.insert((*x, *y), mass as Probability);
+                }
+            }
+        }
+        self
+    }
+    fn flow(&self, x: &Self::X, y: &Self::Y) -> Energy {
+        self.mass(x, y) * self.metric.distance(x, y)
+    }
+    fn cost(&self) -> Energy {
+        self.plan.keys().map(|(x, y)| self.flow(x, y)).sum()
+    }
+}
+
+impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for Exact<'a> {
+    fn from((source, target, metric): (&'a Histogram, &'a Histogram, &'a Metric)) -> Self {
+        #[cfg(debug_assertions)]
+        Metric::validate(source, target).expect("valid transport inputs");
+        Self {
+            plan: BTreeMap::default(),
+            metric,
+            source,
+            target,
+        }
+    }
+}
@@ -2,9 +2,12 @@ use super::abstraction::Abstraction;
 use super::histogram::Histogram;
 use super::metric::Metric;
 use super::pair::Pair;
+use super::parallel::*;
 use super::potential::Potential;
 use crate::transport::coupling::Coupling;
+use crate::transport::density::Density;
 use crate::transport::measure::Measure;
+use crate::Energy;
 use crate::Probability;
 use std::collections::BTreeMap;
 
@@ -26,11 +29,41 @@ use std::collections::BTreeMap;
 ///
 /// also, it turns out this algorithm sucks in worst case. like it's just not at all
 /// a reasonable heuristic, even in pathological 1D trivial cases.
+///
+/// the search for each source's nearest target is independent of
+/// the other targets, so for large enough targets it runs in
+/// parallel, see KMEANS_HEURISTIC_PARALLEL. ties go to the smaller
+/// Abstraction either way, so both searches build the same plan.
 pub struct Heuristic<'a> {
     plan: BTreeMap<Pair, Probability>,
     metric: &'a Metric,
     source: &'a Histogram,
     target: &'a Histogram,
+    parallel: bool,
+}
+
+impl Heuristic<'_> {
+    /// override whether the nearest target search runs in parallel
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+    /// nearest target that still has mass to absorb
+    fn nearest(&self, x: &Abstraction, sink: &Potential) -> Option<(Abstraction, Energy)> {
+        let distance = |y: &Abstraction| (*y, self.metric.distance(x, y));
+        let closer = |a: &(Abstraction, Energy), b: &(Abstraction, Energy)| {
+            a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0))
+        };
+        let targets = sink.iter().filter(|(_, dy)| **dy > 0.).map(|(y, _)| y);
+        match self.parallel {
+            false => targets.map(distance).min_by(closer),
+            true => targets
+                .collect::<Vec<&Abstraction>>()
+                .into_par_iter()
+                .map(distance)
+                .min_by(closer),
+        }
+    }
 }
 
 impl Coupling for Heuristic<'_> {
@@ -61,18 +94,13 @@ impl Coupling for Heuristic<'_> {
                 .map(|(&x, dx)| (x, dx))
                 .collect::<Vec<_>>()
             {
-                match sink
-                    .iter_mut()
-                    .filter(|(_, dy)| **dy > 0.)
-                    .map(|(&y, dy)| ((y, dy), self.metric.distance(&x, &y)))
-                    .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
-                {
+                match self.nearest(&x, sink) {
                     None => break 'cost,
-                    Some(((y, dy), distance)) => {
-                        let mass = Probability::min(*dx, *dy);
+                    Some((y, distance)) => {
+                        let mass = Probability::min(*dx, sink.density(&y));
                         let pair = Pair::from((&x, &y));
                         *dx -= mass;
-                        *dy -= mass;
+                        sink.increment(&y, -mass);
                         *self.plan.entry(pair).or_default() += mass * distance;
                         continue 'pile;
                     }
@@ -85,11 +113,50 @@ impl Coupling for Heuristic<'_> {
 
 impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for Heuristic<'a> {
     fn from((source, target, metric): (&'a Histogram, &'a Histogram, &'a Metric)) -> Self {
+        #[cfg(debug_assertions)]
+        Metric::validate(source, target).expect("valid transport inputs");
         Self {
             plan: BTreeMap::default(),
             metric,
             source,
             target,
+            parallel: target.n() >= crate::KMEANS_HEURISTIC_PARALLEL,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::street::Street;
+    use rand::Rng;
+
+    #[test]
+    fn parallel_matches_serial() {
+        let ref mut rng = crate::rng();
+        let basis = Abstraction::basis(Street::Turn);
+        let metric = Metric::from(
+            basis
+                .iter()
+                .flat_map(|x| basis.iter().map(move |y| (x, y)))
+                .filter(|(x, y)| x > y)
+                .map(|(x, y)| (Pair::from((x, y)), rng.gen::<Energy>()))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        let histogram = |rng: &mut crate::Random| {
+            (0..1024)
+                .map(|_| basis[rng.gen_range(0..basis.len())])
+                .fold(Histogram::default(), |h, a| h.increment(a))
+        };
+        let ref h1 = histogram(rng);
+        let ref h2 = histogram(rng);
+        let serial = Heuristic::from((h1, h2, &metric))
+            .with_parallel(false)
+            .minimize();
+        let parallel = Heuristic::from((h1, h2, &metric))
+            .with_parallel(true)
+            .minimize();
+        assert!(serial.plan == parallel.plan);
+        assert!(serial.cost() == parallel.cost());
+    }
+}
@@ -1,10 +1,13 @@
 use crate::cards::observation::Observation;
+use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::transport::density::Density;
 use crate::Arbitrary;
+use crate::Entropy;
 use crate::Equity;
 use crate::Probability;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::ops::AddAssign;
 
 /// A distribution over arbitrary Abstractions.
@@ -22,18 +25,54 @@ impl Histogram {
         self.counts.insert(abs, count);
         self.mass += count;
     }
+    /// raw number of times this Abstraction was witnessed
+    pub fn count(&self, x: &Abstraction) -> usize {
+        self.counts.get(x).copied().unwrap_or(0usize)
+    }
+    /// total count across every Abstraction
+    pub fn mass(&self) -> usize {
+        self.mass
+    }
     /// the weight of a given Abstraction. returns 0 if the Abstraction was never witnessed.
     pub fn density(&self, x: &Abstraction) -> Probability {
         self.counts.get(x).copied().unwrap_or(0usize) as f32 / self.mass as f32
     }
+    /// density() divided in double precision, for accumulating
+    /// many small masses without losing the tail.
+    pub fn density_f64(&self, x: &Abstraction) -> f64 {
+        self.counts.get(x).copied().unwrap_or(0usize) as f64 / self.mass as f64
+    }
     /// all witnessed Abstractions in the support
     pub fn support(&self) -> impl Iterator<Item = &Abstraction> {
         self.counts.keys()
     }
+    /// every Abstraction in the support must lie in the street's
+    /// abstraction space, otherwise transport against a metric
+    /// over that space is meaningless
+    pub fn validate_against(&self, street: Street) -> Result<(), String> {
+        match self.support().find(|x| !x.within(street)) {
+            None => Ok(()),
+            Some(x) => Err(format!(
+                "histogram abstraction {} on {} is outside the {} basis",
+                x,
+                x.street(),
+                street,
+            )),
+        }
+    }
     /// size of the support
     pub fn n(&self) -> usize {
         self.counts.len()
     }
+    /// number of Abstractions carrying nonzero weight.
+    /// differs from n() when set() stored a zero count.
+    pub fn support_len(&self) -> usize {
+        self.counts.values().filter(|&&c| c > 0).count()
+    }
+    /// no mass witnessed, so density is undefined
+    pub fn is_empty(&self) -> bool {
+        self.mass == 0
+    }
 
     /// insert the Abstraction into our support,
     /// incrementing its local weight,
@@ -47,12 +86,31 @@ impl Histogram {
         self
     }
     /// absorb the other histogram into this one.
+    ///
+    /// counts are integers, so summing many histograms into a
+    /// centroid never goes non-finite, but it could wrap. if the
+    /// combined mass would overflow, both sides are halved until
+    /// it fits, which keeps densities approximately intact.
     pub fn absorb(&mut self, other: &Self) {
+        if self.mass.checked_add(other.mass).is_none() {
+            log::warn!("histogram mass overflow, renormalizing centroid");
+            let mut other = other.clone();
+            while self.mass.checked_add(other.mass).is_none() {
+                self.halve();
+                other.halve();
+            }
+            return self.absorb(&other);
+        }
         self.mass += other.mass;
         for (key, count) in other.counts.iter() {
             self.counts.entry(*key).or_insert(0usize).add_assign(*count);
         }
     }
+    /// halve every count, rounding up so no support is lost
+    fn halve(&mut self) {
+        self.counts.values_mut().for_each(|c| *c = c.div_ceil(2));
+        self.mass = self.counts.values().sum();
+    }
 
     /// it is useful in EMD calculation
     /// to know if we're dealing with ::Equity or ::Random
@@ -68,6 +126,16 @@ impl Histogram {
         assert!(matches!(self.peek(), Abstraction::Percent(_)));
         self.pdf().iter().map(|(x, y)| x * y).sum()
     }
+    /// standard deviation of showdown equity. high for draws
+    /// that either get there or don't, low for made hands.
+    pub fn potential(&self) -> Equity {
+        let mean = self.equity();
+        self.pdf()
+            .iter()
+            .map(|(x, p)| p * (x - mean) * (x - mean))
+            .sum::<Probability>()
+            .sqrt()
+    }
     /// this yields the posterior equity distribution
     /// at Street::Turn.
     /// this is the only street we explicitly can calculate
@@ -83,6 +151,33 @@ impl Histogram {
             .map(|(k, v)| (Equity::from(k), Probability::from(v)))
             .collect()
     }
+    /// fraction of outcomes at or below this equity, over the
+    /// river equity buckets. equity is clamped into [0, 1], and
+    /// an empty histogram has no mass below anything.
+    pub fn cdf_at(&self, equity: Equity) -> Probability {
+        let equity = equity.clamp(0., 1.);
+        match self.is_empty() {
+            true => 0.,
+            false => Abstraction::range()
+                .filter(|abs| abs.equity_of() <= equity)
+                .map(|abs| self.density(&abs))
+                .sum(),
+        }
+    }
+    /// smallest equity bucket whose cumulative mass reaches p,
+    /// so quantile(0.5) is the median bucket. p is clamped into
+    /// [0, 1], and an empty histogram has no quantiles.
+    pub fn quantile(&self, p: Probability) -> Option<Abstraction> {
+        let p = p.clamp(0., 1.);
+        let target = ((p * self.mass as Probability).ceil() as usize).max(1);
+        Abstraction::range()
+            .scan(0usize, |cumulative, abs| {
+                *cumulative += self.counts.get(&abs).copied().unwrap_or(0);
+                Some((abs, *cumulative))
+            })
+            .find(|(_, cumulative)| *cumulative >= target)
+            .map(|(abs, _)| abs)
+    }
 
     /// owned vector of Abstractions and their densities
     /// sorted by density in descending order (most likely first)
@@ -95,6 +190,43 @@ impl Histogram {
         distribution.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
         distribution
     }
+
+    /// Kullback-Leibler divergence KL(self || other), in nats,
+    /// over the union of both supports. KL is infinite wherever
+    /// other has no mass but self does, so we apply additive
+    /// smoothing: every Abstraction in the shared domain gets an
+    /// extra SMOOTHING pseudo-probability before renormalizing.
+    pub fn kl_divergence(&self, other: &Self) -> Entropy {
+        let ref domain = self.domain(other);
+        let n = domain.len() as Probability;
+        let smooth = |h: &Self, x: &Abstraction| {
+            (h.density(x) + Self::SMOOTHING) / (1. + Self::SMOOTHING * n)
+        };
+        domain
+            .iter()
+            .map(|x| (smooth(self, x), smooth(other, x)))
+            .map(|(p, q)| p * (p / q).ln())
+            .sum::<Entropy>()
+    }
+    /// Jensen-Shannon divergence, in nats. symmetric and bounded by ln 2.
+    /// the mixture has mass wherever either side does, so no smoothing
+    /// is needed and we take 0 ln 0 = 0.
+    pub fn js_divergence(&self, other: &Self) -> Entropy {
+        let half = |p: Probability, m: Probability| match p {
+            0. => 0.,
+            p => p * (p / m).ln(),
+        };
+        self.domain(other)
+            .iter()
+            .map(|x| (self.density(x), other.density(x)))
+            .map(|(p, q)| (p, q, (p + q) / 2.))
+            .map(|(p, q, m)| (half(p, m) + half(q, m)) / 2.)
+            .sum::<Entropy>()
+    }
+    fn domain(&self, other: &Self) -> BTreeSet<Abstraction> {
+        self.support().chain(other.support()).copied().collect()
+    }
+    const SMOOTHING: Probability = 1e-6;
 }
 
 impl From<Observation> for Histogram {
@@ -125,6 +257,7 @@ impl Density for Histogram {
 
 impl Arbitrary for Histogram {
     fn random() -> Self {
+        use rand::Rng;
         const S: usize = 16;
         const N: usize = 64;
         (0..)
@@ -134,7 +267,7 @@ impl Arbitrary for Histogram {
             .collect::<Vec<_>>()
             .into_iter()
             .cycle()
-            .filter(|_| rand::random::<bool>())
+            .filter(|_| crate::rng().gen::<bool>())
             .take(N)
             .fold(Self::default(), |h, a| h.increment(a))
     }
@@ -182,3 +315,96 @@ impl std::fmt::Display for Histogram {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absorb_stays_finite() {
+        let ref a = Abstraction::from(0.25);
+        let ref b = Abstraction::from(0.75);
+        let mut member = Histogram::default();
+        member.set(*a, usize::MAX / 8);
+        member.set(*b, usize::MAX / 8);
+        let mut centroid = Histogram::default();
+        for _ in 0..64 {
+            centroid.absorb(&member);
+        }
+        assert!(centroid.density(a).is_finite());
+        assert!((centroid.density(a) - 0.5).abs() < 1e-3);
+        assert!((centroid.density(a) + centroid.density(b) - 1.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn cdf_and_quantile() {
+        let histogram = Histogram::from(vec![
+            Abstraction::from(0.1),
+            Abstraction::from(0.2),
+            Abstraction::from(0.2),
+            Abstraction::from(0.9),
+        ]);
+        assert!(histogram.cdf_at(-1.) == 0.);
+        assert!(histogram.cdf_at(0.2) == 0.75);
+        assert!(histogram.cdf_at(2.) == 1.);
+        assert!(histogram.quantile(0.) == Some(Abstraction::from(0.1)));
+        assert!(histogram.quantile(0.5) == Some(Abstraction::from(0.2)));
+        assert!(histogram.quantile(1.5) == Some(Abstraction::from(0.9)));
+        assert!(Histogram::default().cdf_at(0.5) == 0.);
+        assert!(Histogram::default().quantile(0.5) == None);
+    }
+
+    #[test]
+    fn support_len_skips_zero_weight() {
+        let mut h = Histogram::default();
+        assert!(h.is_empty());
+        assert_eq!(h.support_len(), 0);
+        h.set(Abstraction::from(0.25), 0);
+        assert!(h.is_empty());
+        assert_eq!(h.n(), 1);
+        assert_eq!(h.support_len(), 0);
+        h.set(Abstraction::from(0.75), 3);
+        assert!(!h.is_empty());
+        assert_eq!(h.support_len(), 1);
+    }
+
+    #[test]
+    fn validate_against() {
+        let h = Histogram::random();
+        assert!(h.validate_against(Street::Flop).is_ok());
+        assert!(h.validate_against(Street::Rive).is_err());
+    }
+
+    #[test]
+    fn kl_identical() {
+        let h = Histogram::random();
+        assert!(h.kl_divergence(&h).abs() < 1e-4);
+    }
+
+    #[test]
+    fn kl_disjoint_finite() {
+        let p = Histogram::from(vec![Abstraction::from(0.25)]);
+        let q = Histogram::from(vec![Abstraction::from(0.75)]);
+        let kl = p.kl_divergence(&q);
+        assert!(kl.is_finite());
+        assert!(kl > 0.);
+    }
+
+    #[test]
+    fn js_symmetric_bounded() {
+        let p = Histogram::random();
+        let q = Histogram::random();
+        let pq = p.js_divergence(&q);
+        let qp = q.js_divergence(&p);
+        assert!((pq - qp).abs() < 1e-5);
+        assert!(pq >= 0.);
+        assert!(pq <= 2f32.ln() + 1e-5);
+    }
+
+    #[test]
+    fn js_disjoint() {
+        let p = Histogram::from(vec![Abstraction::from(0.25)]);
+        let q = Histogram::from(vec![Abstraction::from(0.75)]);
+        assert!((p.js_divergence(&q) - 2f32.ln()).abs() < 1e-5);
+    }
+}
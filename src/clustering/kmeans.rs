@@ -1,8 +1,11 @@
 use super::abstraction::Abstraction;
+use super::checkpoint::Checkpoint;
+use super::config::ClusterConfig;
 use super::histogram::Histogram;
 use super::lookup::Lookup;
 use super::metric::Metric;
 use super::pair::Pair;
+use super::stats::IterationStats;
 use super::transitions::Decomp;
 use crate::cards::isomorphism::Isomorphism;
 use crate::cards::isomorphisms::IsomorphismIterator;
@@ -11,12 +14,17 @@ use crate::Energy;
 use crate::Save;
 use rand::distributions::Distribution;
 use rand::distributions::WeightedIndex;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
 use std::collections::BTreeMap;
+use std::hash::Hash;
 
 type Neighbor = (usize, f32);
 
 pub struct Layer {
     street: Street,
+    seed: u64,
+    config: ClusterConfig,
     metric: Metric,
     points: Vec<Histogram>, // positioned by Isomorphism
     kmeans: Vec<Histogram>, // positioned by K-means abstraction
@@ -26,31 +34,133 @@ impl Layer {
     /// all-in-one entry point for learning the kmeans abstraction and
     /// writing to disk in pgcopy
     pub fn learn() {
+        Self::learn_with(ClusterConfig::default(), |_| ())
+    }
+    /// learn() with other cluster counts and iterations. each street
+    /// clusters over the one below it, which this same config shaped.
+    /// streets already on disk are kept, so clear them between runs
+    /// of a sweep. observe hears about every kmeans iteration that
+    /// runs, so nothing more once a street's loss has converged.
+    pub fn learn_with(config: ClusterConfig, mut observe: impl FnMut(IterationStats)) {
         Street::all()
             .iter()
             .rev()
             .filter(|s| !Self::done(**s))
-            .map(|s| Self::make(*s).save())
+            .map(|s| {
+                Self::load(*s)
+                    .with_config(config)
+                    .cluster(&mut observe)
+                    .save()
+            })
             .count();
     }
+    /// the metric we cluster under was stored by the street learned
+    /// just before ours, under this same config, so it is as sparse
+    /// as the config says
+    fn with_config(mut self, config: ClusterConfig) -> Self {
+        self.metric = std::mem::take(&mut self.metric).with_far(config.far());
+        self.config = config;
+        self
+    }
 
-    /// primary clustering algorithm loop
-    fn cluster(mut self) -> Self {
-        log::info!("{:<32}{:<32}", "initialize  kmeans", self.street());
-        let ref mut init = self.init();
-        let ref mut last = self.kmeans;
-        std::mem::swap(init, last);
+    /// primary clustering algorithm loop. resumes from the
+    /// last Checkpoint if an earlier run was interrupted.
+    fn cluster(mut self, observe: &mut dyn FnMut(IterationStats)) -> Self {
+        let resume = if Checkpoint::done(self.street()) {
+            let checkpoint = Checkpoint::load(self.street());
+            match checkpoint.resumable(self.seed, self.config) {
+                Ok(()) => Some(checkpoint),
+                Err(e) => {
+                    log::warn!("{:<32}{:<32}", "discarding  checkpoint", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let from = if let Some(checkpoint) = resume {
+            let from = checkpoint.iteration();
+            log::info!(
+                "{:<32}{:<32}",
+                "resuming    kmeans",
+                format!("{} at iteration {}", self.street(), from)
+            );
+            self.kmeans = checkpoint.kmeans(self.k());
+            from
+        } else {
+            log::info!("{:<32}{:<32}", "initialize  kmeans", self.street());
+            let ref mut init = self.init();
+            let ref mut last = self.kmeans;
+            std::mem::swap(init, last);
+            0
+        };
         log::info!("{:<32}{:<32}", "clustering  kmeans", self.street());
-        let t = self.street().t();
+        let t = self.t();
+        let used = self.iterate(from, true, observe);
+        log::info!(
+            "{:<32}{:<32}",
+            "converged   kmeans",
+            format!("{} of {} iterations", used, t)
+        );
+        self.approximation();
+        self
+    }
+    /// centroids as of this many completed iterations
+    fn checkpoint(&self, iteration: usize) -> Checkpoint {
+        let ref kmeans = self.kmeans;
+        let street = self.street();
+        Checkpoint::from((street, iteration, self.seed, self.config, kmeans.as_slice()))
+    }
+    /// run kmeans iterations from the given one up to t, stopping
+    /// early once the loss has converged, and checkpointing the
+    /// centroids after each if asked. returns the iteration reached.
+    /// observe hears about each iteration that ran, including the
+    /// one that converged, and about none of the skipped ones.
+    fn iterate(
+        &mut self,
+        from: usize,
+        checkpoint: bool,
+        observe: &mut dyn FnMut(IterationStats),
+    ) -> usize {
+        let t = self.t();
         let progress = crate::progress(t);
-        for _ in 0..t {
-            let ref mut next = self.next();
+        progress.inc(from as u64);
+        let mut prev = None;
+        for i in from..t {
+            let (ref mut next, loss) = self.next(i);
             let ref mut last = self.kmeans;
             std::mem::swap(next, last);
+            if checkpoint {
+                self.checkpoint(i + 1).save();
+            }
+            if i % crate::KMEANS_LOSS_INTERVAL == 0 {
+                log::debug!("{:<32}{:<32}", "abstraction cluster RMS error", loss);
+            }
+            observe(IterationStats {
+                street: self.street(),
+                iteration: i,
+                loss,
+                empty: self.kmeans().iter().filter(|h| h.is_empty()).count(),
+            });
             progress.inc(1);
+            if self.converged(prev, loss) {
+                progress.finish();
+                return i + 1;
+            }
+            prev = Some(loss);
         }
         progress.finish();
-        self
+        t
+    }
+    /// relative change in loss between consecutive iterations is
+    /// within the configured tolerance. once an assignment repeats, the
+    /// centroids and so every later iteration repeat too, which
+    /// shows up as an unchanged loss even with zero tolerance.
+    fn converged(&self, prev: Option<Energy>, loss: Energy) -> bool {
+        match prev {
+            None => false,
+            Some(prev) => (prev - loss).abs() <= self.config.tolerance() * prev,
+        }
     }
 
     /// reference to the observed points
@@ -67,18 +177,9 @@ impl Layer {
     /// 2. choose nth centroid with probability proportional to squared distance of nearest neighbors
     /// 3. collect histograms and label with arbitrary (random) `Abstraction`s
     fn init(&self) -> Vec<Histogram> /* K */ {
-        use rand::rngs::SmallRng;
-        use rand::SeedableRng;
-        use rayon::iter::IntoParallelRefIterator;
-        use rayon::iter::ParallelIterator;
-        use std::hash::DefaultHasher;
-        use std::hash::Hash;
-        use std::hash::Hasher;
-        let ref mut hasher = DefaultHasher::default();
-        self.street().hash(hasher);
-        self.street().hash(hasher);
-        let ref mut rng = SmallRng::seed_from_u64(hasher.finish());
-        let k = self.street().k();
+        use crate::clustering::parallel::ParallelIterator;
+        let ref mut rng = self.rng(self.street());
+        let k = self.k();
         let n = self.points().len();
         let mut histograms = Vec::new();
         let mut potentials = vec![1.; n];
@@ -93,35 +194,67 @@ impl Layer {
                 .expect("sharing index with outer layer");
             histograms.push(x.clone());
             potentials[i] = 0.;
-            potentials = self
-                .points()
-                .par_iter()
-                .map(|h| self.emd(x, h))
+            potentials = crate::clustering::parallel::enumerate(self.points())
+                .inspect(|(i, _)| crate::tick(&progress, *i))
+                .map(|(_, h)| self.emd(x, h))
                 .map(|p| p * p)
-                .inspect(|_| progress.inc(1))
                 .collect::<Vec<Energy>>()
                 .iter()
                 .zip(potentials.iter())
                 .map(|(d0, d1)| Energy::min(*d0, *d1))
                 .collect::<Vec<Energy>>();
+            crate::settle(&progress, n);
         }
         progress.finish();
         histograms
     }
-    /// calculates the next step of the kmeans iteration by
+    /// calculates the next step of the kmeans iteration,
+    /// over every point or a sampled mini-batch depending
+    /// on the config. also returns the RMS loss of the assignment.
+    fn next(&self, iteration: usize) -> (Vec<Histogram> /* K */, Energy) {
+        match self.config.batch() {
+            b if b == 0 || b >= self.points().len() => self.full_batch(iteration),
+            b => self.mini_batch(iteration, b),
+        }
+    }
     /// determining K * N optimal transport calculations and
-    /// taking the nearest neighbor
-    fn next(&self) -> Vec<Histogram> /* K */ {
-        use rayon::iter::IntoParallelRefIterator;
-        use rayon::iter::ParallelIterator;
-        let k = self.street().k();
+    /// taking the nearest neighbor, then rebuilding each
+    /// centroid from exactly the points assigned to it.
+    fn full_batch(&self, iteration: usize) -> (Vec<Histogram> /* K */, Energy) {
+        use crate::clustering::parallel::ParallelIterator;
+        let k = self.k();
         let mut loss = 0f32;
         let mut centroids = vec![Histogram::default(); k];
         // assign points to nearest neighbors
-        for (point, (neighbor, distance)) in self
-            .points()
+        for (point, (neighbor, distance)) in crate::clustering::parallel::enumerate(self.points())
+            .map(|(i, h)| (h, self.neighboring(h, &mut self.rng((iteration, i)))))
+            .collect::<Vec<_>>()
+            .into_iter()
+        {
+            loss = loss + distance * distance;
+            centroids
+                .get_mut(neighbor)
+                .expect("index from neighbor calculation")
+                .absorb(point);
+        }
+        (centroids, (loss / self.points().len() as f32).sqrt())
+    }
+    /// Sculley's mini-batch step: assign b sampled points, then
+    /// absorb each into the centroid it hit. centroids are count
+    /// histograms, so absorbing keeps a running mean over every
+    /// point ever assigned, which is the per-center 1/v learning
+    /// rate: centroids move less as they accumulate points.
+    fn mini_batch(&self, iteration: usize, b: usize) -> (Vec<Histogram> /* K */, Energy) {
+        use crate::clustering::parallel::IntoParallelRefIterator;
+        use crate::clustering::parallel::ParallelIterator;
+        let ref mut rng = self.rng(("batch", iteration));
+        let mut loss = 0f32;
+        let mut centroids = self.kmeans().clone();
+        for (point, (neighbor, distance)) in rand::seq::index::sample(rng, self.points().len(), b)
+            .into_vec()
             .par_iter()
-            .map(|h| (h, self.neighboring(h)))
+            .map(|&i| (i, &self.points()[i]))
+            .map(|(i, h)| (h, self.neighboring(h, &mut self.rng((iteration, i)))))
             .collect::<Vec<_>>()
             .into_iter()
         {
@@ -131,12 +264,21 @@ impl Layer {
                 .expect("index from neighbor calculation")
                 .absorb(point);
         }
-        log::debug!(
-            "{:<32}{:<32}",
-            "abstraction cluster RMS error",
-            (loss / self.points().len() as f32).sqrt()
-        );
-        centroids
+        (centroids, (loss / b as f32).sqrt())
+    }
+
+    /// a stream determined by our seed, our street, and the salt.
+    /// callers salt with the iteration and point index, so no draw
+    /// depends on which rayon thread got to a point first, and a
+    /// given seed replays the same centroids and assignments.
+    fn rng(&self, salt: impl Hash) -> SmallRng {
+        use std::hash::DefaultHasher;
+        use std::hash::Hasher;
+        let ref mut hasher = DefaultHasher::default();
+        self.seed.hash(hasher);
+        self.street().hash(hasher);
+        salt.hash(hasher);
+        SmallRng::seed_from_u64(hasher.finish())
     }
 
     /// wrawpper for distance metric calculations
@@ -148,21 +290,74 @@ impl Layer {
     fn abstracting(&self, i: usize) -> Abstraction {
         Abstraction::from((self.street(), i))
     }
-    /// calculates nearest neighbor and separation distance for a Histogram
-    fn neighboring(&self, x: &Histogram) -> Neighbor {
+    /// calculates nearest neighbor and separation distance for a Histogram.
+    /// for large K we can trade accuracy for speed by only scanning
+    /// a random subset of centroids, see KMEANS_NEIGHBOR_SAMPLE.
+    fn neighboring(&self, x: &Histogram, rng: &mut SmallRng) -> Neighbor {
+        match crate::KMEANS_NEIGHBOR_SAMPLE {
+            0 => self.nearest(x),
+            m => self.sampled(x, m, rng),
+        }
+    }
+    /// exact nearest neighbor by full scan over all K centroids
+    fn nearest(&self, x: &Histogram) -> Neighbor {
         self.kmeans()
             .iter()
             .enumerate()
             .map(|(k, h)| (k, self.emd(x, h)))
-            .min_by(|(_, dx), (_, dy)| dx.partial_cmp(dy).unwrap())
+            .min_by(Self::closer)
             .expect("find nearest neighbor")
             .into()
     }
+    /// approximate nearest neighbor over m uniformly sampled centroids
+    fn sampled(&self, x: &Histogram, m: usize, rng: &mut SmallRng) -> Neighbor {
+        let k = self.kmeans().len();
+        rand::seq::index::sample(rng, k, m.min(k))
+            .into_iter()
+            .map(|i| (i, self.emd(x, &self.kmeans()[i])))
+            .min_by(Self::closer)
+            .expect("find nearest neighbor")
+    }
+    /// order neighbors by distance, breaking ties toward the smaller
+    /// centroid index so assignment doesn't depend on scan order
+    fn closer((i, dx): &Neighbor, (j, dy): &Neighbor) -> std::cmp::Ordering {
+        dx.partial_cmp(dy).unwrap().then(i.cmp(j))
+    }
+    /// report how much approximate assignment inflates the
+    /// final RMS loss relative to an exact scan. no-op in exact mode.
+    fn approximation(&self) {
+        use crate::clustering::parallel::ParallelIterator;
+        if crate::KMEANS_NEIGHBOR_SAMPLE == 0 {
+            return;
+        }
+        let n = self.points().len() as f32;
+        let t = self.t();
+        let (exact, approx) = crate::clustering::parallel::enumerate(self.points())
+            .map(|(i, h)| (h, self.neighboring(h, &mut self.rng((t, i)))))
+            .map(|(h, (_, approx))| (self.nearest(h).1, approx))
+            .map(|(e, a)| (e * e, a * a))
+            .collect::<Vec<(Energy, Energy)>>()
+            .into_iter()
+            .fold((0., 0.), |(e1, a1), (e2, a2)| (e1 + e2, a1 + a2));
+        log::info!(
+            "{:<32}{:<32}",
+            "approximate kmeans RMS error",
+            format!("{:.4} vs exact {:.4}", (approx / n).sqrt(), (exact / n).sqrt())
+        );
+    }
 
     /// reference to current street
     fn street(&self) -> Street {
         self.street
     }
+    /// number of clusters on this street
+    fn k(&self) -> usize {
+        self.config.k(self.street())
+    }
+    /// number of kmeans iterations on this street
+    fn t(&self) -> usize {
+        self.config.t(self.street())
+    }
     /// take outer product of current learned kmeans
     /// Histograms, using whatever is stored as the future metric
     fn metric(&self) -> Metric {
@@ -180,21 +375,36 @@ impl Layer {
                 }
             }
         }
-        Metric::from(metric)
+        let metric = Metric::from(metric);
+        let ref basis = (0..self.kmeans.len())
+            .map(|k| self.abstracting(k))
+            .collect::<Vec<Abstraction>>();
+        let metric = if self.config.repair() {
+            let (metric, delta) = metric.repair(basis);
+            log::info!("{:<32}{:<32}", "repaired    metric", delta);
+            metric
+        } else {
+            metric
+        };
+        let metric = match (self.config.neighbors(), self.config.far()) {
+            (k, Some(far)) => metric.sparsify(basis, k, far),
+            _ => metric,
+        };
+        match self.config.power() {
+            p if p == 1. => metric,
+            p => metric.power(p),
+        }
     }
     /// in ObsIterator order, get a mapping of
     /// Isomorphism -> Abstraction
     fn lookup(&self) -> Lookup {
         log::info!("{:<32}{:<32}", "calculating lookup", self.street());
-        use rayon::iter::IntoParallelRefIterator;
-        use rayon::iter::ParallelIterator;
+        use crate::clustering::parallel::ParallelIterator;
         let street = self.street();
         match street {
             Street::Pref | Street::Rive => Lookup::make(street),
-            Street::Flop | Street::Turn => self
-                .points()
-                .par_iter()
-                .map(|h| self.neighboring(h))
+            Street::Flop | Street::Turn => crate::clustering::parallel::enumerate(self.points())
+                .map(|(i, h)| self.neighboring(h, &mut self.rng((self.t(), i))))
                 .collect::<Vec<Neighbor>>()
                 .into_iter()
                 .map(|(k, _)| self.abstracting(k))
@@ -230,12 +440,16 @@ impl Save for Layer {
         match street {
             Street::Rive => Self {
                 street,
+                seed: crate::seeded(),
+                config: ClusterConfig::default(),
                 kmeans: Vec::default(),
                 points: Vec::default(),
                 metric: Metric::default(),
             },
             _ => Self {
                 street,
+                seed: crate::seeded(),
+                config: ClusterConfig::default(),
                 kmeans: Vec::default(),
                 points: Lookup::load(street.next()).projections(),
                 metric: Metric::load(street.next()),
@@ -243,11 +457,241 @@ impl Save for Layer {
         }
     }
     fn save(&self) {
-        self.metric().save();
+        self.metric().save_to(self.street());
         self.lookup().save();
         self.decomp().save();
+        Checkpoint::clear(self.street());
     }
     fn make(street: Street) -> Self {
-        Self::load(street).cluster()
+        Self::load(street).cluster(&mut |_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 64 two-card turn points spread over the river buckets,
+    /// clustered into 8 for at most 6 iterations
+    fn fixture() -> Layer {
+        Layer {
+            street: Street::Turn,
+            seed: 0,
+            config: ClusterConfig::default().with_turn(8, 6),
+            metric: Metric::default(),
+            points: (0..64)
+                .map(|i| (i % Abstraction::size(), (i * 7) % Abstraction::size()))
+                .map(|(a, b)| vec![(Street::Rive, a), (Street::Rive, b)])
+                .map(|pair| pair.into_iter().map(Abstraction::from).collect::<Vec<_>>())
+                .map(Histogram::from)
+                .collect(),
+            kmeans: Vec::default(),
+        }
+    }
+
+    #[test]
+    fn ties_resolve_to_smallest_index() {
+        let centroid = Histogram::from(vec![Abstraction::from(0.25), Abstraction::from(0.75)]);
+        let point = Histogram::from(vec![Abstraction::from(0.5)]);
+        let layer = Layer {
+            street: Street::Turn,
+            seed: 0,
+            config: ClusterConfig::default(),
+            metric: Metric::default(),
+            points: vec![point.clone()],
+            kmeans: vec![centroid.clone(), centroid.clone(), centroid],
+        };
+        assert!(layer.nearest(&point).0 == 0);
+        for i in 0..32 {
+            assert!(layer.sampled(&point, 3, &mut SmallRng::seed_from_u64(i)).0 == 0);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_clusters() {
+        use rand::Rng;
+        let points = (0..crate::KMEANS_TURN_CLUSTER_COUNT + 16)
+            .map(|_| {
+                (0..4)
+                    .map(|_| Abstraction::from(crate::rng().gen::<f32>()))
+                    .collect::<Vec<Abstraction>>()
+            })
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let run = |seed: u64| {
+            let mut layer = Layer {
+                street: Street::Turn,
+                seed,
+                config: ClusterConfig::default(),
+                metric: Metric::default(),
+                points: points.clone(),
+                kmeans: Vec::default(),
+            };
+            layer.kmeans = layer.init();
+            let losses = (0..2)
+                .map(|i| {
+                    let (kmeans, loss) = layer.next(i);
+                    layer.kmeans = kmeans;
+                    loss
+                })
+                .collect::<Vec<Energy>>();
+            (BTreeMap::from(layer.lookup()), losses)
+        };
+        assert!(run(7) == run(7));
+    }
+
+    #[test]
+    fn config_sets_counts() {
+        let config = ClusterConfig::default().with_turn(8, 2);
+        let mut layer = Layer {
+            street: Street::Turn,
+            seed: 0,
+            config: ClusterConfig::default(),
+            metric: Metric::default(),
+            points: (0..32)
+                .map(|i| vec![Abstraction::from((Street::Rive, i))])
+                .map(Histogram::from)
+                .collect(),
+            kmeans: Vec::default(),
+        }
+        .with_config(config);
+        layer.kmeans = layer.init();
+        assert!(layer.kmeans().len() == 8);
+        assert!(layer.iterate(0, false, &mut |_| ()) <= 2);
+        assert!(layer.next(0).0.len() == 8);
+        assert!(config.k(Street::Flop) == Street::Flop.k());
+    }
+    #[test]
+    fn mini_batch_reduces_loss() {
+        use rand::Rng;
+        let ref mut rng = SmallRng::seed_from_u64(0);
+        let points = (0..512)
+            .map(|i| 0.1 + 0.8 * (i % 8) as f32 / 7.)
+            .map(|mode| {
+                (0..8)
+                    .map(|_| (mode + rng.gen_range(-0.1..0.1)).clamp(0., 1.))
+                    .map(Abstraction::from)
+                    .collect::<Vec<Abstraction>>()
+            })
+            .map(Histogram::from)
+            .collect::<Vec<Histogram>>();
+        let layer = |batch: usize| Layer {
+            street: Street::Turn,
+            seed: 0,
+            config: ClusterConfig::default().with_turn(8, 16).with_batch(batch),
+            metric: Metric::default(),
+            points: points.clone(),
+            kmeans: Vec::default(),
+        };
+        let rms = |layer: &Layer| {
+            let n = layer.points().len() as f32;
+            let loss = layer
+                .points()
+                .iter()
+                .map(|h| layer.nearest(h).1)
+                .map(|d| d * d)
+                .sum::<f32>();
+            (loss / n).sqrt()
+        };
+        let mut mini = layer(32);
+        mini.kmeans = mini.init();
+        let mut losses = vec![rms(&mini)];
+        for i in 0..16 {
+            mini.kmeans = mini.next(i).0;
+            losses.push(rms(&mini));
+        }
+        assert!(losses.last() < losses.first());
+        assert!(losses.windows(2).all(|w| w[1] <= w[0] * 1.1));
+        let mut full = layer(0);
+        let mut same = layer(points.len());
+        full.kmeans = full.init();
+        same.kmeans = same.init();
+        for i in 0..2 {
+            let (f, fl) = full.next(i);
+            let (s, sl) = same.next(i);
+            assert!(fl == sl);
+            full.kmeans = f;
+            same.kmeans = s;
+        }
+        assert!(BTreeMap::from(full.lookup()) == BTreeMap::from(same.lookup()));
+    }
+    #[test]
+    fn resumes_from_checkpoint() {
+        let street = Street::Turn;
+        let mut original = fixture();
+        original.kmeans = original.init();
+        for i in 0..2 {
+            original.kmeans = original.next(i).0;
+        }
+        let ref path = std::env::temp_dir()
+            .join(format!("checkpoint.{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let (seed, config) = (original.seed, original.config);
+        original.checkpoint(2).save_to(path);
+        let checkpoint = Checkpoint::load_from(street, path);
+        std::fs::remove_file(path).expect("remove checkpoint");
+        assert!(checkpoint.iteration() == 2);
+        assert!(checkpoint.resumable(seed, config).is_ok());
+        assert!(checkpoint.resumable(seed + 1, config).is_err());
+        assert!(checkpoint.resumable(seed, config.with_turn(4, 4)).is_err());
+        assert!(checkpoint
+            .resumable(seed, config.with_tolerance(0.1))
+            .is_ok());
+        let mut resumed = fixture();
+        resumed.kmeans = checkpoint.kmeans(resumed.k());
+        for h in original.points().iter() {
+            assert!(original.nearest(h) == resumed.nearest(h));
+        }
+        assert!(original.next(2).1 == resumed.next(2).1);
+    }
+    #[test]
+    fn observes_every_iteration() {
+        let mut layer = fixture();
+        layer.kmeans = layer.init();
+        let mut seen = Vec::new();
+        let used = layer.iterate(0, false, &mut |stats| seen.push(stats));
+        assert!(used <= layer.t());
+        assert!(seen.len() == used);
+        assert!(seen.iter().enumerate().all(|(i, s)| s.iteration == i));
+        assert!(seen.iter().all(|s| s.street == Street::Turn));
+        let empty = layer.kmeans().iter().filter(|h| h.is_empty()).count();
+        assert!(seen.last().map(|s| s.empty) == Some(empty));
+    }
+    #[test]
+    fn metric_follows_config() {
+        let mut layer = fixture();
+        layer.kmeans = layer.init();
+        assert!(!layer.metric().is_sparse());
+        let config = layer.config.with_neighbors(2, 0.9).with_power(2.);
+        let layer = layer.with_config(config);
+        assert!(layer.metric().is_sparse());
+        assert!(config.resumes(&ClusterConfig::default().with_turn(8, 6)));
+    }
+    #[test]
+    fn stops_once_converged() {
+        let mut layer = Layer {
+            street: Street::Turn,
+            seed: 0,
+            config: ClusterConfig::default(),
+            metric: Metric::default(),
+            points: (0..crate::KMEANS_TURN_CLUSTER_COUNT + 16)
+                .map(|i| (i % Abstraction::size(), i / Abstraction::size()))
+                .map(|(a, b)| (a, (a + b) % Abstraction::size()))
+                .map(|(a, b)| vec![(Street::Rive, a), (Street::Rive, b)])
+                .map(|pair| pair.into_iter().map(Abstraction::from).collect::<Vec<_>>())
+                .map(Histogram::from)
+                .collect(),
+            kmeans: Vec::default(),
+        };
+        layer.kmeans = layer.init();
+        let used = layer.iterate(0, false, &mut |_| ());
+        assert!(used < Street::Turn.t() / 2);
+        assert!(layer.converged(Some(1.), 1.));
+        assert!(!layer.converged(Some(1.), 0.5));
+        assert!(!layer.converged(None, 0.));
+        let layer = layer.with_config(ClusterConfig::default().with_tolerance(0.5));
+        assert!(layer.converged(Some(1.), 0.5));
+        assert!(!layer.converged(Some(1.), 0.4));
     }
 }
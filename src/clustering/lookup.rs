@@ -4,8 +4,10 @@ use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::clustering::histogram::Histogram;
+use crate::clustering::parallel::ParallelIterator;
+use crate::clustering::store::Store;
+use crate::gameplay::game::Game;
 use crate::Save;
-use rayon::iter::ParallelIterator;
 use std::collections::BTreeMap;
 
 #[derive(Default)]
@@ -19,14 +21,110 @@ impl Lookup {
             .cloned()
             .expect(&format!("precomputed abstraction missing for {obs}"))
     }
+    /// manually merge two abstractions by reassigning every
+    /// observation of b to a. b no longer appears in the Lookup
+    /// afterwards, so it saves cleanly without a dangling cluster.
+    pub fn merge_abstractions(&mut self, a: &Abstraction, b: &Abstraction) {
+        assert!(a != b, "cannot merge abstraction into itself");
+        assert!(a.street() == b.street(), "cannot merge across streets");
+        self.0
+            .values_mut()
+            .filter(|abs| *abs == b)
+            .for_each(|abs| *abs = *a);
+        assert!(self.0.values().all(|abs| abs != b));
+    }
+    /// every isomorphism assigned to this abstraction, in order.
+    /// useful for eyeballing whether a bucket groups sensible hands.
+    pub fn members(&self, abs: &Abstraction) -> Vec<Isomorphism> {
+        self.0
+            .iter()
+            .filter(|(_, a)| *a == abs)
+            .map(|(iso, _)| *iso)
+            .collect()
+    }
+    /// members of every abstraction, grouped in a single pass
+    pub fn groups(&self) -> BTreeMap<Abstraction, Vec<Isomorphism>> {
+        self.0
+            .iter()
+            .fold(BTreeMap::new(), |mut groups, (iso, abs)| {
+                groups.entry(*abs).or_insert_with(Vec::new).push(*iso);
+                groups
+            })
+    }
+    /// remap the abstractions in use onto the dense range
+    /// 0..n on the same street, preserving their relative order,
+    /// and return the old -> new remapping. after merges or a
+    /// smaller retrain this keeps indices contiguous for anything
+    /// downstream that indexes dense arrays by abstraction.
+    /// river abstractions are equity buckets, so they stay put.
+    pub fn compact(&mut self) -> BTreeMap<Abstraction, Abstraction> {
+        let street = self.street();
+        assert!(street != Street::Rive, "river abstractions are equities");
+        let remapping = self
+            .0
+            .values()
+            .copied()
+            .collect::<std::collections::BTreeSet<Abstraction>>()
+            .into_iter()
+            .enumerate()
+            .map(|(i, abs)| (abs, Abstraction::from((street, i))))
+            .collect::<BTreeMap<Abstraction, Abstraction>>();
+        self.0.values_mut().for_each(|abs| *abs = remapping[abs]);
+        remapping
+    }
+    /// recompute the centroid Histogram of an abstraction
+    /// from scratch, by summing the projections of all its
+    /// member observations onto the next street's Lookup.
+    /// useful after merge_abstractions to re-derive transitions.
+    pub fn centroid(&self, abs: &Abstraction, future: &Lookup) -> Histogram {
+        self.0
+            .iter()
+            .filter(|(_, a)| *a == abs)
+            .map(|(iso, _)| future.future(iso))
+            .fold(Histogram::default(), |mut centroid, h| {
+                centroid.absorb(&h);
+                centroid
+            })
+    }
     /// generate the entire space of inner layers
     pub fn projections(&self) -> Vec<Histogram> {
-        use rayon::iter::IntoParallelIterator;
-        IsomorphismIterator::from(self.street().prev())
-            .collect::<Vec<Isomorphism>>()
-            .into_par_iter()
-            .map(|inner| self.future(&inner))
-            .collect::<Vec<Histogram>>()
+        let street = self.street().prev();
+        let n = street.n_isomorphisms();
+        let progress = crate::progress(n);
+        let ref inners = IsomorphismIterator::from(street).collect::<Vec<Isomorphism>>();
+        let projections = crate::clustering::parallel::enumerate(inners)
+            .inspect(|(i, _)| crate::tick(&progress, *i))
+            .map(|(_, inner)| self.future(inner))
+            .collect::<Vec<Histogram>>();
+        crate::settle(&progress, n);
+        progress.finish();
+        projections
+    }
+    /// transition Histogram of a game's current observation,
+    /// computed locally against this next-street Lookup rather
+    /// than round-tripping through the database.
+    pub fn histogram_of(&self, game: &Game) -> Histogram {
+        let observation = Observation::from(game);
+        assert!(
+            observation.street() != Street::Rive,
+            "no histogram beyond the river for {observation}"
+        );
+        self.future(&Isomorphism::from(observation))
+    }
+    /// stream one JSON object per line, {"obs", "abs", "street"},
+    /// for tools that read neither pgcopy nor postgres.
+    /// keys are the same i64s the database uses.
+    pub fn export_jsonl<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for (iso, abs) in self.0.iter() {
+            let line = serde_json::json!({
+                "obs": i64::from(*iso),
+                "abs": i64::from(*abs),
+                "street": iso.0.street().to_string(),
+            });
+            serde_json::to_writer(&mut writer, &line)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
     }
     /// distribution over potential next states. this "layer locality" is what
     /// makes imperfect recall hierarchical kmeans nice
@@ -43,12 +141,21 @@ impl Lookup {
     }
 }
 
+impl Store for Lookup {
+    fn abstraction(&self, iso: &Isomorphism) -> Option<Abstraction> {
+        self.0.abstraction(iso)
+    }
+    fn abstractions(&self, street: Street) -> Vec<Abstraction> {
+        self.0.abstractions(street)
+    }
+}
+
 impl Save for Lookup {
     fn name() -> &'static str {
         "pgcopy.encoder."
     }
     fn make(street: Street) -> Self {
-        use rayon::iter::IntoParallelIterator;
+        use crate::clustering::parallel::IntoParallelIterator;
         // abstractions for River are calculated once via obs.equity
         // abstractions for Preflop are cequivalent to just enumerating isomorphisms
         match street {
@@ -126,6 +233,69 @@ mod tests {
     use super::*;
     use crate::Save;
 
+    #[test]
+    fn merge() {
+        let street = Street::Pref;
+        let mut lookup = Lookup::make(street);
+        let ref a = Abstraction::from((street, 0));
+        let ref b = Abstraction::from((street, 1));
+        let n = lookup.0.values().filter(|x| *x == a || *x == b).count();
+        lookup.merge_abstractions(a, b);
+        assert!(lookup.0.values().all(|x| x != b));
+        assert!(lookup.0.values().filter(|x| *x == a).count() == n);
+        assert!(lookup.0.len() == street.n_isomorphisms());
+    }
+
+    #[test]
+    fn members() {
+        let street = Street::Pref;
+        let lookup = Lookup::make(street);
+        let ref abs = Abstraction::from((street, 0));
+        let groups = lookup.groups();
+        assert!(groups.values().map(|g| g.len()).sum::<usize>() == street.n_isomorphisms());
+        assert!(groups.get(abs) == Some(&lookup.members(abs)));
+        assert!(lookup.members(abs).windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn compact() {
+        let street = Street::Pref;
+        let mut lookup = Lookup::make(street);
+        for i in (0..street.n_isomorphisms()).step_by(3) {
+            let ref a = Abstraction::from((street, i + 1));
+            let ref b = Abstraction::from((street, i));
+            lookup.merge_abstractions(a, b);
+        }
+        let before = lookup.0.clone();
+        let remapping = lookup.compact();
+        let n = remapping.len();
+        assert!(remapping.values().all(|abs| abs.street() == street));
+        assert!(remapping
+            .values()
+            .copied()
+            .eq((0..n).map(|i| Abstraction::from((street, i)))));
+        assert!(before
+            .iter()
+            .all(|(iso, abs)| lookup.0[iso] == remapping[abs]));
+    }
+
+    #[test]
+    fn export_jsonl() {
+        let street = Street::Pref;
+        let lookup = Lookup::make(street);
+        let mut buffer = Vec::new();
+        lookup.export_jsonl(&mut buffer).unwrap();
+        let parsed = String::from_utf8(buffer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .inspect(|json| assert!(json["street"] == "preflop"))
+            .map(|json| (json["obs"].as_i64().unwrap(), json["abs"].as_i64().unwrap()))
+            .map(|(obs, abs)| (Isomorphism::from(obs), Abstraction::from(abs)))
+            .collect::<BTreeMap<Isomorphism, Abstraction>>();
+        assert!(parsed == lookup.0);
+    }
+
     #[test]
     fn persistence() {
         let street = Street::Pref;
@@ -1,3 +1,4 @@
+use super::config::ClusterConfig;
 use super::equity::Equity;
 use super::sinkhorn::Sinkhorn;
 use crate::cards::street::Street;
@@ -7,30 +8,259 @@ use crate::clustering::pair::Pair;
 use crate::transport::coupling::Coupling;
 use crate::transport::measure::Measure;
 use crate::Energy;
+use crate::Probability;
 use crate::Save;
 use std::collections::BTreeMap;
 
 /// Distance metric for kmeans clustering.
 /// encapsulates distance between `Abstraction`s of the "previous" hierarchy,
 /// as well as: distance between `Histogram`s of the "current" hierarchy.
+/// a sparse metric is allowed to be missing pairs, which it reads as
+/// the far distance it carries.
+/// river buckets are spaced by index unless with_centers places them.
 #[derive(Default)]
-pub struct Metric(BTreeMap<Pair, Energy>);
+pub struct Metric(
+    BTreeMap<Pair, Energy>,
+    Option<Energy>,
+    Option<Vec<Probability>>,
+);
 
 impl Metric {
+    /// pairs missing from a sparse metric are far apart,
+    /// see ClusterConfig::with_neighbors. a dense metric is missing
+    /// nothing, so a missing pair there is a bug upstream.
     fn lookup(&self, x: &Abstraction, y: &Abstraction) -> Energy {
-        self.0
-            .get(&Pair::from((x, y)))
-            .copied()
-            .expect("missing abstraction pair")
+        match (self.0.get(&Pair::from((x, y))), self.1) {
+            (Some(distance), _) => *distance,
+            (None, Some(far)) => far,
+            (None, None) => panic!("dense metric has no distance for {} {}", x, y),
+        }
+    }
+    pub fn is_sparse(&self) -> bool {
+        self.1.is_some()
+    }
+    /// mark a metric as sparse with missing pairs this far apart,
+    /// e.g. one read back from storage that was sparsified before
+    /// it was saved, or as dense with None
+    pub fn with_far(mut self, far: Option<Energy>) -> Self {
+        self.1 = far;
+        self
+    }
+    /// space river buckets by the mean equity of their members,
+    /// see Equity::centers, rather than evenly by bucket index
+    pub fn with_centers(mut self, centers: Vec<Probability>) -> Self {
+        assert!(centers.len() == Abstraction::size());
+        self.2 = Some(centers);
+        self
     }
 
     pub fn emd(&self, source: &Histogram, target: &Histogram) -> Energy {
+        #[cfg(debug_assertions)]
+        Self::validate(source, target).expect("valid transport inputs");
         match source.peek() {
             Abstraction::Learned(_) => Sinkhorn::from((source, target, self)).minimize().cost(),
+            Abstraction::Percent(_) if crate::KMEANS_TURN_POTENTIAL => {
+                Equity::potential(source, target)
+            }
             Abstraction::Percent(_) => Equity::variation(source, target),
             Abstraction::Preflop(_) => unreachable!("no preflop emd"),
         }
     }
+    /// emd() with double-precision accumulation, for comparing
+    /// nearly identical Histograms where f32 round-off would
+    /// show up as a spurious nonzero distance. emd() stays f32
+    /// for the clustering hot path.
+    pub fn emd_f64(&self, source: &Histogram, target: &Histogram) -> Energy {
+        #[cfg(debug_assertions)]
+        Self::validate(source, target).expect("valid transport inputs");
+        match source.peek() {
+            Abstraction::Learned(_) => Sinkhorn::from((source, target, self)).minimize().cost_f64(),
+            Abstraction::Percent(_) if crate::KMEANS_TURN_POTENTIAL => {
+                Equity::potential(source, target)
+            }
+            Abstraction::Percent(_) => Equity::variation_f64(source, target),
+            Abstraction::Preflop(_) => unreachable!("no preflop emd"),
+        }
+    }
+    /// first pair that emd() would transport between without a
+    /// stored distance. a sparse metric fills these in as far
+    /// apart, which callers that need real distances can refuse.
+    /// equity-based rivers never consult the metric.
+    pub fn missing(
+        &self,
+        source: &Histogram,
+        target: &Histogram,
+    ) -> Option<(Abstraction, Abstraction)> {
+        match source.peek() {
+            Abstraction::Learned(_) => source
+                .support()
+                .flat_map(|x| target.support().map(move |y| (*x, *y)))
+                .filter(|(x, y)| x != y)
+                .find(|(x, y)| !self.0.contains_key(&Pair::from((x, y)))),
+            _ => None,
+        }
+    }
+    /// both sides of a transport problem must live in the same
+    /// street's abstraction space. only checked in debug builds,
+    /// since emd is the hot path of clustering.
+    pub fn validate(source: &Histogram, target: &Histogram) -> Result<(), String> {
+        if source.is_empty() || target.is_empty() {
+            return Err("transport between empty histograms".to_string());
+        }
+        let street = source.peek().street();
+        source.validate_against(street)?;
+        target.validate_against(street)?;
+        Ok(())
+    }
+    /// shortest-path closure over the basis, so that every
+    /// distance satisfies the triangle inequality. distances
+    /// can only shrink. also returns the largest change made
+    /// to any pair, so callers can judge how non-metric the
+    /// input was.
+    pub fn repair(&self, basis: &[Abstraction]) -> (Self, Energy) {
+        let n = basis.len();
+        let mut d = vec![vec![0 as Energy; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                d[i][j] = self.distance(&basis[i], &basis[j]);
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    d[i][j] = d[i][j].min(d[i][k] + d[k][j]);
+                }
+            }
+        }
+        let mut delta = 0 as Energy;
+        let mut metric = BTreeMap::new();
+        for i in 0..n {
+            for j in 0..i {
+                let ref a = basis[i];
+                let ref b = basis[j];
+                delta = delta.max(self.distance(a, b) - d[i][j]);
+                metric.insert(Pair::from((a, b)), d[i][j]);
+            }
+        }
+        (Self(metric, None, self.2.clone()), delta)
+    }
+    /// keep only the k nearest distances from each abstraction
+    /// in the basis. a pair survives if either side keeps it,
+    /// everything else is looked up as far.
+    pub fn sparsify(&self, basis: &[Abstraction], k: usize, far: Energy) -> Self {
+        let mut metric = BTreeMap::new();
+        for a in basis.iter() {
+            let mut nearest = basis
+                .iter()
+                .filter(|b| *b != a)
+                .map(|b| (Pair::from((a, b)), self.distance(a, b)))
+                .collect::<Vec<(Pair, Energy)>>();
+            nearest.sort_by(|(_, dx), (_, dy)| dx.partial_cmp(dy).unwrap());
+            metric.extend(nearest.into_iter().take(k));
+        }
+        Self(metric, Some(far), self.2.clone())
+    }
+    /// raise every stored distance to a power. with p > 1 far
+    /// pairs are pushed apart more than near ones, so clusters
+    /// on the next layer come out tighter around their centroids
+    /// at the cost of outliers dominating the kmeans++ seeding.
+    /// distances are at most 1, so a pair dropped by sparsify
+    /// still reads as the farthest possible. any positive power
+    /// is monotone and preserves the ordering of distances.
+    pub fn power(&self, p: Energy) -> Self {
+        assert!(p > 0., "metric power must be positive");
+        Self(
+            self.0
+                .iter()
+                .map(|(pair, distance)| (*pair, distance.powf(p)))
+                .collect(),
+            self.1,
+            self.2.clone(),
+        )
+    }
+    /// classical (Torgerson) multidimensional scaling of the basis
+    /// into the plane, for visualization. we square the pairwise
+    /// distances, double-center them into a Gram matrix
+    /// B = -½ J D² J, and take its top two eigenpairs by power
+    /// iteration with deflation. each abstraction lands at
+    /// (√λ₁ v₁, √λ₂ v₂), so metric-close abstractions end up near
+    /// each other, exactly so when the metric is Euclidean in 2D.
+    pub fn embedding(&self, basis: &[Abstraction]) -> Vec<(Abstraction, (f32, f32))> {
+        let n = basis.len();
+        let mut b = vec![vec![0f64; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                b[i][j] = (self.distance(&basis[i], &basis[j]) as f64).powi(2);
+            }
+        }
+        let rows = b
+            .iter()
+            .map(|row| row.iter().sum::<f64>() / n as f64)
+            .collect::<Vec<f64>>();
+        let mean = rows.iter().sum::<f64>() / n as f64;
+        for i in 0..n {
+            for j in 0..n {
+                b[i][j] = -0.5 * (b[i][j] - rows[i] - rows[j] + mean);
+            }
+        }
+        let (l1, v1) = Self::eigen(&b);
+        for i in 0..n {
+            for j in 0..n {
+                b[i][j] -= l1 * v1[i] * v1[j];
+            }
+        }
+        let (l2, v2) = Self::eigen(&b);
+        let (s1, s2) = (l1.max(0.).sqrt(), l2.max(0.).sqrt());
+        basis
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, a)| (a, ((s1 * v1[i]) as f32, (s2 * v2[i]) as f32)))
+            .collect()
+    }
+    /// largest eigenpair of a symmetric matrix by power iteration,
+    /// from a fixed non-degenerate start so embeddings are reproducible.
+    /// non-Euclidean metrics give B negative eigenvalues, so we iterate
+    /// on B + cI with c a Gershgorin bound to land on the largest
+    /// algebraic eigenvalue rather than the largest in magnitude.
+    fn eigen(m: &[Vec<f64>]) -> (f64, Vec<f64>) {
+        const ITERATIONS: usize = 1024;
+        const TOLERANCE: f64 = 1e-12;
+        let n = m.len();
+        let shift = m
+            .iter()
+            .map(|row| row.iter().map(|x| x.abs()).sum::<f64>())
+            .fold(0., f64::max);
+        let mut v = (0..n)
+            .map(|i| 1. + i as f64 / n as f64)
+            .collect::<Vec<f64>>();
+        let mut lambda = 0.;
+        for _ in 0..ITERATIONS {
+            let w = m
+                .iter()
+                .zip(v.iter())
+                .map(|(row, vi)| {
+                    row.iter().zip(v.iter()).map(|(a, b)| a * b).sum::<f64>() + shift * vi
+                })
+                .collect::<Vec<f64>>();
+            let norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm < TOLERANCE {
+                return (0., vec![0.; n]);
+            }
+            let next = w.iter().map(|x| x / norm).collect::<Vec<f64>>();
+            let delta = next
+                .iter()
+                .zip(v.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0., f64::max);
+            v = next;
+            lambda = norm - shift;
+            if delta < TOLERANCE {
+                break;
+            }
+        }
+        (lambda, v)
+    }
     pub fn read() -> Self {
         log::info!("loading     metric");
         Self(
@@ -43,6 +273,8 @@ impl Metric {
                     map.extend(street);
                     map
                 }),
+            ClusterConfig::default().far(),
+            None,
         )
     }
 
@@ -62,6 +294,9 @@ impl Metric {
     /// we should either just store Street as Self.1 or determine from XOR hits what street we're on
     /// whichever solution should work with test case so we don't have to remove test case
     /// to not overwrite existing metric. we like overwriting river.metric bc it can be empty
+    ///
+    /// a sparse metric has no fixed size, so there we decode the street
+    /// from the signature of any stored pair instead.
     fn street(&self) -> Street {
         fn choose_2(k: usize) -> usize {
             k * (k.saturating_sub(1)) / 2
@@ -71,6 +306,16 @@ impl Metric {
             n if n == choose_2(Street::Turn.k()) => Street::Turn,
             n if n == choose_2(Street::Flop.k()) => Street::Flop,
             n if n == choose_2(Street::Pref.k()) => Street::Pref,
+            _ if self.is_sparse() => self
+                .0
+                .keys()
+                .next()
+                .and_then(|pair| {
+                    [Street::Turn, Street::Flop, Street::Pref]
+                        .into_iter()
+                        .find(|street| pair.components(*street).is_some())
+                })
+                .unwrap_or(Street::Rive),
             _ => Street::Rive, // assertion of no-collisions is convenient for tests
         }
     }
@@ -84,9 +329,12 @@ impl Measure for Metric {
         } else {
             match (x, y) {
                 (Self::X::Learned(_), Self::Y::Learned(_)) => self.lookup(x, y),
-                (Self::X::Percent(_), Self::Y::Percent(_)) => Equity.distance(x, y),
+                (Self::X::Percent(_), Self::Y::Percent(_)) => match self.2 {
+                    Some(ref centers) => (centers[x.index()] - centers[y.index()]).abs(),
+                    None => Equity.distance(x, y),
+                },
                 (Self::X::Preflop(_), Self::Y::Preflop(_)) => unreachable!("no preflop distance"),
-                _ => unreachable!(),
+                _ => unreachable!("distance across streets {} and {}", x.street(), y.street()),
             }
         }
     }
@@ -126,10 +374,18 @@ impl Save for Metric {
                 break;
             }
         }
-        Self(metric)
+        Self(metric, ClusterConfig::default().far(), None)
     }
     fn save(&self) {
-        let street = self.street();
+        self.save_to(self.street())
+    }
+}
+
+impl Metric {
+    /// save() under a street we already know. save() infers the
+    /// street from our size, which only works for the default
+    /// cluster counts, so Layer names it outright.
+    pub fn save_to(&self, street: Street) {
         log::info!("{:<32}{:<32}", "saving      metric", street);
         use byteorder::WriteBytesExt;
         use byteorder::BE;
@@ -160,6 +416,8 @@ impl From<BTreeMap<Pair, Energy>> for Metric {
                 .into_iter()
                 .map(|(index, distance)| (index, distance / max))
                 .collect(),
+            None,
+            None,
         )
     }
 }
@@ -171,6 +429,148 @@ mod tests {
     use crate::clustering::emd::EMD;
     use crate::{Arbitrary, Save};
 
+    #[test]
+    fn repair() {
+        let ref basis = (0..3)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let (a, b, c) = (&basis[0], &basis[1], &basis[2]);
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((a, b)), 0.1),
+                (Pair::from((b, c)), 0.1),
+                (Pair::from((a, c)), 1.0),
+            ]),
+            None,
+            None,
+        );
+        let (repaired, delta) = metric.repair(basis);
+        assert!((delta - 0.8).abs() < 1e-6);
+        assert!(repaired.distance(a, c) <= repaired.distance(a, b) + repaired.distance(b, c));
+    }
+
+    #[test]
+    fn sparsify() {
+        let ref basis = (0..4)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let (a, b, c, d) = (&basis[0], &basis[1], &basis[2], &basis[3]);
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((a, b)), 0.1),
+                (Pair::from((a, c)), 0.5),
+                (Pair::from((a, d)), 0.9),
+                (Pair::from((b, c)), 0.6),
+                (Pair::from((b, d)), 0.8),
+                (Pair::from((c, d)), 0.2),
+            ]),
+            None,
+            None,
+        );
+        let sparse = metric.sparsify(basis, 1, 0.75);
+        assert!(sparse.0.len() == 2);
+        assert!(sparse.distance(a, b) == 0.1);
+        assert!(sparse.distance(c, d) == 0.2);
+        assert!(sparse.distance(a, d) == 0.75);
+        assert!(sparse.distance(a, a) == 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dense_metric_has_every_pair() {
+        let a = Abstraction::from((Street::Flop, 0));
+        let b = Abstraction::from((Street::Flop, 1));
+        Metric::default().distance(&a, &b);
+    }
+
+    #[test]
+    fn missing_pairs_across_supports() {
+        let ref basis = (0..3)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let (a, b, c) = (basis[0], basis[1], basis[2]);
+        let metric = Metric(
+            BTreeMap::from([(Pair::from((&a, &b)), 0.5)]),
+            Some(1.),
+            None,
+        );
+        let ab = Histogram::from(vec![a, b]);
+        let bc = Histogram::from(vec![b, c]);
+        assert!(metric.missing(&ab, &ab).is_none());
+        assert!(metric.missing(&ab, &bc) == Some((a, c)));
+    }
+
+    #[test]
+    fn power_preserves_ordering() {
+        let ref basis = (0..4)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let (a, b, c, d) = (&basis[0], &basis[1], &basis[2], &basis[3]);
+        let metric = Metric(
+            BTreeMap::from([
+                (Pair::from((a, b)), 0.1),
+                (Pair::from((a, c)), 0.5),
+                (Pair::from((a, d)), 0.9),
+                (Pair::from((b, c)), 0.6),
+                (Pair::from((b, d)), 0.8),
+                (Pair::from((c, d)), 0.2),
+            ]),
+            None,
+            None,
+        );
+        let squared = metric.power(2.);
+        assert!((squared.distance(a, c) - 0.25).abs() < 1e-6);
+        let order = |m: &Metric| {
+            let mut pairs = m.0.iter().collect::<Vec<_>>();
+            pairs.sort_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap());
+            pairs.into_iter().map(|(p, _)| *p).collect::<Vec<Pair>>()
+        };
+        assert!(order(&metric) == order(&squared));
+        assert!(order(&metric) == order(&metric.power(0.5)));
+    }
+
+    #[test]
+    fn centers_move_buckets() {
+        let a = Abstraction::from(0.5);
+        let b = Abstraction::from(0.6);
+        let centers = Equity::centers([0.5, 0.496, 0.6, 0.604]);
+        let spaced = Metric::default();
+        let placed = Metric::default().with_centers(centers);
+        assert!((placed.distance(&a, &b) - 0.104).abs() < 1e-6);
+        assert!((placed.distance(&a, &b) - spaced.distance(&a, &b)).abs() > 1e-3);
+        let c = Abstraction::from(0.9);
+        assert!((placed.distance(&a, &c) - (c.equity_of() - 0.498)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn embedding() {
+        let ref basis = (0..4)
+            .map(|i| Abstraction::from((Street::Flop, i)))
+            .collect::<Vec<Abstraction>>();
+        let points = [(0., 0.), (3., 0.), (0., 1.), (3., 1.)];
+        let euclid = |(x1, y1): (f32, f32), (x2, y2): (f32, f32)| {
+            ((x1 - x2) * (x1 - x2) + (y1 - y2) * (y1 - y2)).sqrt()
+        };
+        let metric = Metric(
+            (0..4)
+                .flat_map(|i| (0..i).map(move |j| (i, j)))
+                .map(|(i, j)| {
+                    let pair = Pair::from((&basis[i], &basis[j]));
+                    (pair, euclid(points[i], points[j]))
+                })
+                .collect(),
+            None,
+            None,
+        );
+        let embedding = metric.embedding(basis);
+        for i in 0..4 {
+            for j in 0..4 {
+                let d = euclid(embedding[i].1, embedding[j].1);
+                assert!((d - metric.distance(&basis[i], &basis[j])).abs() < 1e-3);
+            }
+        }
+    }
+
     #[test]
     fn persistence() {
         let street = Street::Rive;
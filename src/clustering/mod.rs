@@ -1,13 +1,67 @@
 pub mod abstraction;
+pub mod checkpoint;
+pub mod config;
 pub mod emd;
 pub mod equity;
+pub mod exact;
 pub mod heuristic;
 pub mod histogram;
 pub mod kmeans;
 pub mod lookup;
 pub mod metric;
+pub mod monotone;
 pub mod pair;
+pub mod parallel;
 pub mod potential;
 pub mod progress;
 pub mod sinkhorn;
+pub mod stats;
+pub mod store;
 pub mod transitions;
+
+use crate::transport::coupling::Coupling;
+use crate::Energy;
+use histogram::Histogram;
+use metric::Metric;
+
+/// which optimal transport solver an EMD runs on. Metric::emd
+/// picks for us, by street; these pin it down, e.g. to compare
+/// solvers on the same pair of Histograms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solver {
+    /// entropically regularized, what clustering uses
+    Sinkhorn,
+    /// greedy nearest target, an upper bound
+    Heuristic,
+    /// min cost flow, slow but optimal
+    Exact,
+}
+
+/// earth mover's distance between two Histograms over the same
+/// street's Abstractions, under the ground metric between them.
+/// equity Histograms get a closed form, learned ones Sinkhorn.
+///
+/// ```
+/// use robopoker::cards::observation::Observation;
+/// use robopoker::clustering::histogram::Histogram;
+/// use robopoker::clustering::metric::Metric;
+/// use robopoker::clustering::emd;
+///
+/// let metric = Metric::default();
+/// let ref aces = Histogram::from("AsAh~KdTs8c6h".parse::<Observation>().unwrap());
+/// let ref low = Histogram::from("7c9d~KdTs8c6h".parse::<Observation>().unwrap());
+/// assert!(emd(aces, aces, &metric) < 1e-6);
+/// assert!(emd(aces, low, &metric) > 0.);
+/// ```
+pub fn emd(x: &Histogram, y: &Histogram, metric: &Metric) -> Energy {
+    metric.emd(x, y)
+}
+
+/// emd() on whichever Solver the caller chooses
+pub fn emd_with(x: &Histogram, y: &Histogram, metric: &Metric, solver: Solver) -> Energy {
+    match solver {
+        Solver::Sinkhorn => sinkhorn::Sinkhorn::from((x, y, metric)).minimize().cost(),
+        Solver::Heuristic => heuristic::Heuristic::from((x, y, metric)).minimize().cost(),
+        Solver::Exact => exact::Exact::from((x, y, metric)).minimize().cost(),
+    }
+}
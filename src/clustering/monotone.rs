@@ -0,0 +1,119 @@
+use super::abstraction::Abstraction;
+use super::equity::Equity;
+use super::histogram::Histogram;
+use crate::transport::coupling::Coupling;
+use crate::transport::measure::Measure;
+use crate::Energy;
+use crate::Probability;
+use std::collections::BTreeMap;
+
+/// the optimal transport plan between two equity Histograms.
+///
+/// on the line, the optimal coupling is the monotone one: walk
+/// both supports in increasing equity and move mass from the
+/// lowest unspent source to the lowest unfilled target. this
+/// is exactly the difference of sorted CDFs that
+/// Equity::variation integrates, but stored pair by pair, so
+/// flow(x, y) can be read off for any two buckets.
+///
+/// cost() is the Wasserstein-1 distance under Equity's ground
+/// metric, which is twice Equity::variation, since that one
+/// halves its total.
+pub struct Monotone<'a> {
+    plan: BTreeMap<(Abstraction, Abstraction), Probability>,
+    source: &'a Histogram,
+    target: &'a Histogram,
+}
+
+impl Monotone<'_> {
+    /// probability mass moved from x to y
+    pub fn mass(&self, x: &Abstraction, y: &Abstraction) -> Probability {
+        self.plan.get(&(*x, *y)).copied().unwrap_or(0.)
+    }
+    /// every (source, target) pair that carries mass
+    pub fn pairs(&self) -> impl Iterator<Item = &(Abstraction, Abstraction)> {
+        self.plan.keys()
+    }
+    /// weighted support in increasing equity. Abstraction's Ord
+    /// sees the signature bits first, so we sort by index instead.
+    fn sorted(h: &Histogram) -> Vec<(Abstraction, Probability)> {
+        let mut support = h
+            .support()
+            .map(|x| (*x, h.density(x)))
+            .filter(|(_, dx)| *dx > 0.)
+            .collect::<Vec<_>>();
+        support.sort_unstable_by_key(|(x, _)| x.index());
+        support
+    }
+}
+
+impl Coupling for Monotone<'_> {
+    type X = Abstraction;
+    type Y = Abstraction;
+    type P = Histogram;
+    type Q = Histogram;
+    type M = Equity;
+
+    fn minimize(mut self) -> Self {
+        self.plan.clear();
+        let mut sources = Self::sorted(self.source).into_iter().peekable();
+        let mut targets = Self::sorted(self.target).into_iter().peekable();
+        while let (Some((x, dx)), Some((y, dy))) = (sources.peek_mut(), targets.peek_mut()) {
+            let mass = Probability::min(*dx, *dy);
+            *self.plan.entry((*x, *y)).or_default() += mass;
+            *dx -= mass;
+            *dy -= mass;
+            // whichever side ran out moves on. rounding can leave
+            // the other a sliver, which the next pair absorbs.
+            match *dx <= *dy {
+                true => sources.next(),
+                false => targets.next(),
+            };
+        }
+        self
+    }
+    fn flow(&self, x: &Self::X, y: &Self::Y) -> Energy {
+        self.mass(x, y) * Equity.distance(x, y)
+    }
+    fn cost(&self) -> Energy {
+        self.pairs().map(|(x, y)| self.flow(x, y)).sum()
+    }
+}
+
+impl<'a> From<(&'a Histogram, &'a Histogram)> for Monotone<'a> {
+    fn from((source, target): (&'a Histogram, &'a Histogram)) -> Self {
+        Self {
+            plan: BTreeMap::default(),
+            source,
+            target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::observation::Observation;
+    use crate::cards::street::Street;
+
+    #[test]
+    fn flows_sum_to_cost_and_marginals_match() {
+        for _ in 0..16 {
+            let ref h1 = Histogram::from(Observation::from(Street::Turn));
+            let ref h2 = Histogram::from(Observation::from(Street::Turn));
+            let plan = Monotone::from((h1, h2)).minimize();
+            let total = Abstraction::range()
+                .flat_map(|x| Abstraction::range().map(move |y| (x, y)))
+                .map(|(x, y)| plan.flow(&x, &y))
+                .sum::<Energy>();
+            assert!((total - plan.cost()).abs() < 1e-5);
+            assert!((plan.cost() - 2. * Equity::variation(h1, h2)).abs() < 1e-4);
+            for x in Abstraction::range() {
+                let outgoing = Abstraction::range().map(|y| plan.mass(&x, &y)).sum::<f32>();
+                let incoming = Abstraction::range().map(|y| plan.mass(&y, &x)).sum::<f32>();
+                assert!((outgoing - h1.density(&x)).abs() < 1e-5);
+                assert!((incoming - h2.density(&x)).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -1,10 +1,38 @@
+use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::transport::support::Support;
 
 /// A unique identifier for a pair of abstractions.
+///
+/// the key is the bitwise XOR of both abstractions' u64 encodings,
+/// so it is symmetric by construction. reinterpreting as i64 is a
+/// bit-for-bit cast, which is exactly what Postgres `a1.abs # a2.abs`
+/// computes over BIGINT columns, so Rust and SQL keys always agree.
+///
+/// the street tag cancels under XOR, so decoding needs it supplied.
 #[derive(Default, Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Debug)]
 pub struct Pair(u64);
 
+impl Pair {
+    /// recover the two abstractions, smaller index first, by
+    /// searching indices on the given street whose signatures
+    /// XOR to this key. None if no such pair exists.
+    pub fn components(&self, street: Street) -> Option<(Abstraction, Abstraction)> {
+        let n = Abstraction::bound(street);
+        let mask = self.0 & 0xFFF;
+        (0..n)
+            .map(|i| (i, i ^ mask as usize))
+            .filter(|(i, j)| i < j && *j < n)
+            .map(|(i, j)| {
+                (
+                    Abstraction::from((street, i)),
+                    Abstraction::from((street, j)),
+                )
+            })
+            .find(|(a, b)| Self::from((a, b)) == *self)
+    }
+}
+
 impl From<(&Abstraction, &Abstraction)> for Pair {
     fn from((a, b): (&Abstraction, &Abstraction)) -> Self {
         Self(u64::from(*a) ^ u64::from(*b))
@@ -22,3 +50,41 @@ impl From<i64> for Pair {
 }
 
 impl Support for Pair {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Arbitrary;
+
+    fn pairs() -> impl Iterator<Item = (Abstraction, Abstraction)> {
+        (0..256)
+            .map(|_| (Abstraction::random(), Abstraction::random()))
+            .filter(|(a, b)| a != b)
+    }
+
+    #[test]
+    fn symmetric() {
+        assert!(pairs().all(|(a, b)| Pair::from((&a, &b)) == Pair::from((&b, &a))));
+    }
+
+    #[test]
+    fn sql_xor() {
+        assert!(
+            pairs().all(|(a, b)| i64::from(Pair::from((&a, &b))) == i64::from(a) ^ i64::from(b))
+        );
+        assert!(
+            pairs().all(|(a, b)| Pair::from(i64::from(a) ^ i64::from(b)) == Pair::from((&a, &b)))
+        );
+    }
+
+    #[test]
+    fn components() {
+        assert!(pairs()
+            .map(|(a, b)| if a.index() < b.index() {
+                (a, b)
+            } else {
+                (b, a)
+            })
+            .all(|(a, b)| Pair::from((&a, &b)).components(a.street()) == Some((a, b))));
+    }
+}
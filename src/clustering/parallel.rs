@@ -0,0 +1,62 @@
+//! parallel iterator traits for the clustering hot paths.
+//!
+//! by default these are rayon's. with the `sequential` feature
+//! they resolve to plain std iterators instead, so every
+//! par_iter in kmeans and lookup runs on the calling thread,
+//! in order, which is what you want under a debugger.
+//!
+//! each parallel section only maps and collects; float sums
+//! are taken afterwards over the collected Vec, so both modes
+//! produce bit-identical results. what remains order-sensitive
+//! is incidental: progress bar ticks and trace logs interleave
+//! differently, and rayon's work-stealing order is not stable.
+
+#[cfg(not(feature = "sequential"))]
+pub use rayon::iter::IntoParallelIterator;
+#[cfg(not(feature = "sequential"))]
+pub use rayon::iter::IntoParallelRefIterator;
+#[cfg(not(feature = "sequential"))]
+pub use rayon::iter::ParallelIterator;
+
+#[cfg(feature = "sequential")]
+pub use sequential::*;
+
+/// pair each element with its index, e.g. for batched progress ticks
+#[cfg(not(feature = "sequential"))]
+pub fn enumerate<T: Sync>(items: &[T]) -> impl ParallelIterator<Item = (usize, &T)> {
+    use rayon::iter::IndexedParallelIterator;
+    items.par_iter().enumerate()
+}
+#[cfg(feature = "sequential")]
+pub fn enumerate<T: Sync>(items: &[T]) -> impl ParallelIterator<Item = (usize, &T)> {
+    items.iter().enumerate()
+}
+
+#[cfg(feature = "sequential")]
+mod sequential {
+    /// by-value stand-in for rayon's into_par_iter
+    pub trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+
+    /// by-reference stand-in for rayon's par_iter
+    pub trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+    impl<'a, I: 'a + ?Sized> IntoParallelRefIterator<'a> for I
+    where
+        &'a I: IntoIterator,
+    {
+        type Iter = <&'a I as IntoIterator>::IntoIter;
+        fn par_iter(&'a self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// std iterators already have every adapter we use
+    pub use std::iter::Iterator as ParallelIterator;
+}
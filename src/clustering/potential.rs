@@ -1,6 +1,6 @@
 use super::abstraction::Abstraction;
-use super::histogram::Histogram;
 use crate::transport::density::Density;
+use crate::transport::support::Support;
 use crate::Entropy;
 use crate::Probability;
 use std::collections::BTreeMap;
@@ -8,14 +8,18 @@ use std::ops::AddAssign;
 
 /// using this to represent an arbitrary instance of the Kontorovich-Rubinstein
 /// potential formulation of the optimal transport problem.
-/// this structure can also be treated as a normalized distribution over Abstractions.
-pub struct Potential(BTreeMap<Abstraction, Entropy>);
+/// this structure can also be treated as a normalized distribution over its Support,
+/// which defaults to Abstraction but may be any ordered Support.
+pub struct Potential<S = Abstraction>(BTreeMap<S, Entropy>);
 
-impl Potential {
+impl<S> Potential<S>
+where
+    S: Support + Ord,
+{
     /// useful for Heuristic where we don't need to allocate.
     /// i guess we don't need to allocate in Sinkhorn either. but it's
     /// nbd, + we might want to calaculate deltas between new and old potentials
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Abstraction, &mut Entropy)> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&S, &mut Entropy)> {
         self.0.iter_mut()
     }
 
@@ -24,7 +28,12 @@ impl Potential {
         self.0.values()
     }
 
-    pub fn increment(&mut self, i: &Abstraction, delta: Entropy) {
+    /// also only useful for Heuristic
+    pub fn iter(&self) -> impl Iterator<Item = (&S, &Entropy)> {
+        self.0.iter()
+    }
+
+    pub fn increment(&mut self, i: &S, delta: Entropy) {
         self.0
             .get_mut(i)
             .expect("fixed abstraction space")
@@ -32,42 +41,47 @@ impl Potential {
     }
 
     /// zero potential over the support, in log prob space
-    pub fn zeroes(h: &Histogram) -> Self {
-        Self(h.support().copied().map(|x| (x, 0.)).collect())
+    pub fn zeroes<D: Density<S = S>>(h: &D) -> Self {
+        Self(h.support().cloned().map(|x| (x, 0.)).collect())
     }
 
-    /// uniform distribution over the support, in log prob space
-    pub fn uniform(h: &Histogram) -> Self {
+    /// uniform distribution over the support, in log prob space.
+    /// zero-mass buckets carry no flow, so we leave them out entirely
+    /// rather than iterate over (and take the log of) empty mass.
+    pub fn uniform<D: Density<S = S>>(h: &D) -> Self {
+        let n = h.support().filter(|x| h.density(x) > 0.).count();
         Self(
             h.support()
-                .copied()
-                .map(|x| (x, h.n()))
-                .map(|(x, y)| (x, 1. / y as Probability))
+                .filter(|x| h.density(x) > 0.)
+                .cloned()
+                .map(|x| (x, 1. / n as Probability))
                 .map(|(x, y)| (x, y.ln() as Entropy))
                 .collect::<BTreeMap<_, _>>(),
         )
     }
 
     /// unit normalized distribution over the support
-    pub fn normalize(h: &Histogram) -> Self {
+    pub fn normalize<D: Density<S = S>>(h: &D) -> Self {
         Self(
             h.support()
-                .copied()
-                .map(|x| (x, h.density(&x)))
+                .map(|x| (x.clone(), h.density(x)))
                 .collect::<BTreeMap<_, _>>(),
         )
     }
 }
 
-impl From<BTreeMap<Abstraction, Entropy>> for Potential {
-    fn from(potential: BTreeMap<Abstraction, Entropy>) -> Self {
+impl<S> From<BTreeMap<S, Entropy>> for Potential<S> {
+    fn from(potential: BTreeMap<S, Entropy>) -> Self {
         assert!(potential.len() > 0);
         Self(potential)
     }
 }
 
-impl Density for Potential {
-    type S = Abstraction;
+impl<S> Density for Potential<S>
+where
+    S: Support + Ord,
+{
+    type S = S;
     fn density(&self, x: &Self::S) -> Entropy {
         self.0
             .get(x)
@@ -1,4 +1,3 @@
-use super::abstraction::Abstraction;
 use super::histogram::Histogram;
 use super::metric::Metric;
 use super::potential::Potential;
@@ -11,15 +10,50 @@ use std::collections::BTreeMap;
 
 /// using this to represent an arbitrary instance of the Kontorovich-Rubinstein
 /// potential formulation of the optimal transport problem.
-pub struct Sinkhorn<'a> {
-    metric: &'a Metric,
-    mu: &'a Histogram,
-    nu: &'a Histogram,
-    lhs: Potential,
-    rhs: Potential,
+///
+/// generic over any Density D whose Support is ordered, and any
+/// Measure M on that Support. defaults to the Histogram and Metric
+/// over Abstractions that we use for clustering.
+///
+/// the hyperparameters default to SINKHORN_* in lib.rs and can be
+/// overridden per instance with the with_* builders before calling
+/// ::minimize(). temperature is the entropic blur: a smaller value
+/// approaches the exact EMD but needs more iterations to converge
+/// and risks underflow in the exponentials, a larger value
+/// converges quickly to a smoother, overestimated cost.
+pub struct Sinkhorn<'a, M = Metric, D = Histogram>
+where
+    D: Density,
+    D::S: Ord,
+    M: Measure<X = D::S, Y = D::S>,
+{
+    metric: &'a M,
+    mu: &'a D,
+    nu: &'a D,
+    lhs: Potential<D::S>,
+    rhs: Potential<D::S>,
+    temperature: Entropy,
+    iterations: usize,
+    tolerance: Energy,
 }
 
-impl Sinkhorn<'_> {
+/// entropically regularized EMD between any two distributions
+/// over a shared discrete Support, under the given Measure.
+pub fn emd<M, D>(p: &D, q: &D, metric: &M) -> Energy
+where
+    D: Density,
+    D::S: Ord,
+    M: Measure<X = D::S, Y = D::S>,
+{
+    Sinkhorn::from((p, q, metric)).minimize().cost()
+}
+
+impl<M, D> Sinkhorn<'_, M, D>
+where
+    D: Density,
+    D::S: Ord,
+    M: Measure<X = D::S, Y = D::S>,
+{
     /// calculate ε-minimizing coupling by scaling potentials
     fn sinkhorn(&mut self) {
         for _ in 0..self.iterations() {
@@ -37,38 +71,36 @@ impl Sinkhorn<'_> {
         }
     }
     /// calculate next iteration of LHS and RHS potentials after Sinkhorn scaling
-    fn lhs(&self) -> Potential {
+    fn lhs(&self) -> Potential<D::S> {
         Potential::from(
             self.lhs
                 .support()
-                .copied()
-                .map(|x| (x, self.divergence(&x, &self.mu, &self.rhs)))
+                .map(|x| (x.clone(), self.divergence(x, self.mu, &self.rhs)))
                 .inspect(|(_, dx)| assert!(dx.is_finite(), "lhs entropy overflow"))
                 .collect::<BTreeMap<_, _>>(),
         )
     }
     /// calculate next iteration of LHS and RHS potentials after Sinkhorn scaling
-    fn rhs(&self) -> Potential {
+    fn rhs(&self) -> Potential<D::S> {
         Potential::from(
             self.rhs
                 .support()
-                .copied()
-                .map(|x| (x, self.divergence(&x, &self.nu, &self.lhs)))
+                .map(|x| (x.clone(), self.divergence(x, self.nu, &self.lhs)))
                 .inspect(|(_, dx)| assert!(dx.is_finite(), "rhs entropy overflow"))
                 .collect::<BTreeMap<_, _>>(),
         )
     }
     /// the coupling formed by joint distribution of LHS and RHS potentials
-    fn coupling(&self, x: &Abstraction, y: &Abstraction) -> Energy {
+    fn coupling(&self, x: &D::S, y: &D::S) -> Energy {
         (self.lhs.density(x) + self.rhs.density(y) - self.regularization(x, y)).exp()
     }
     /// update the potential energy on a given side
-    /// histogram is where a: Abstraction is supported
+    /// histogram is where a: D::S is supported
     /// potential is the distribution that is being integrated against
     /// so we scale PDF(A::histogram | t) by the mass of the PDF(B::potential | t, x == a)
     /// not sure yet why i'm calling it entropy but it's giving partition function.
     /// actually now that i think of it this might be KL div / relative entropy
-    fn divergence(&self, x: &Abstraction, histogram: &Histogram, potential: &Potential) -> Entropy {
+    fn divergence(&self, x: &D::S, histogram: &D, potential: &Potential<D::S>) -> Entropy {
         histogram.density(x).ln()
             - potential
                 .support()
@@ -79,36 +111,64 @@ impl Sinkhorn<'_> {
                 .ln()
     }
     /// distance in fixed temperature exponent space
-    fn regularization(&self, x: &Abstraction, y: &Abstraction) -> Entropy {
+    fn regularization(&self, x: &D::S, y: &D::S) -> Entropy {
         self.metric.distance(x, y) / self.temperature()
     }
     /// stopping criteria
-    fn error(last: &Potential, next: &Potential) -> Energy {
+    fn error(last: &Potential<D::S>, next: &Potential<D::S>) -> Energy {
         next.support()
             .map(|x| next.density(x).exp() - last.density(x).exp())
             .map(|e| e.abs())
             .fold(0f32, f32::max)
     }
+    /// cost() summed in double precision. each flow is still f32,
+    /// but thousands of tiny terms no longer swamp one another.
+    pub fn cost_f64(&self) -> Energy {
+        self.lhs
+            .support()
+            .flat_map(|x| self.rhs.support().map(move |y| (x, y)))
+            .map(|(x, y)| self.flow(x, y) as f64)
+            .inspect(|x| assert!(x.is_finite()))
+            .sum::<f64>() as Energy
+    }
     /// hyperparameter that determines strength of entropic regularization. incorrect units but whatever
     const fn temperature(&self) -> Entropy {
-        crate::SINKHORN_TEMPERATURE
+        self.temperature
     }
     /// hyperparameter that determines maximum number of iterations
     const fn iterations(&self) -> usize {
-        crate::SINKHORN_ITERATIONS
+        self.iterations
     }
     /// hyperparameter that determines stopping criteria
     const fn tolerance(&self) -> Energy {
-        crate::SINKHORN_TOLERANCE
+        self.tolerance
+    }
+    pub fn with_temperature(mut self, temperature: Entropy) -> Self {
+        assert!(temperature > 0., "temperature must be positive");
+        self.temperature = temperature;
+        self
+    }
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+    pub fn with_tolerance(mut self, tolerance: Energy) -> Self {
+        self.tolerance = tolerance;
+        self
     }
 }
 
-impl Coupling for Sinkhorn<'_> {
-    type X = Abstraction;
-    type Y = Abstraction;
-    type P = Potential;
-    type Q = Potential;
-    type M = Metric;
+impl<M, D> Coupling for Sinkhorn<'_, M, D>
+where
+    D: Density,
+    D::S: Ord,
+    M: Measure<X = D::S, Y = D::S>,
+{
+    type X = D::S;
+    type Y = D::S;
+    type P = Potential<D::S>;
+    type Q = Potential<D::S>;
+    type M = M;
 
     fn minimize(mut self) -> Self {
         self.sinkhorn();
@@ -127,14 +187,87 @@ impl Coupling for Sinkhorn<'_> {
     }
 }
 
-impl<'a> From<(&'a Histogram, &'a Histogram, &'a Metric)> for Sinkhorn<'a> {
-    fn from((mu, nu, metric): (&'a Histogram, &'a Histogram, &'a Metric)) -> Self {
+impl<'a, M, D> From<(&'a D, &'a D, &'a M)> for Sinkhorn<'a, M, D>
+where
+    D: Density,
+    D::S: Ord,
+    M: Measure<X = D::S, Y = D::S>,
+{
+    fn from((mu, nu, metric): (&'a D, &'a D, &'a M)) -> Self {
         Self {
             metric,
             mu,
             nu,
             lhs: Potential::uniform(mu),
             rhs: Potential::uniform(nu),
+            temperature: crate::SINKHORN_TEMPERATURE,
+            iterations: crate::SINKHORN_ITERATIONS,
+            tolerance: crate::SINKHORN_TOLERANCE,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::support::Support;
+
+    /// toy distributions over integer points on a line,
+    /// with distance scaled down to the unit interval
+    impl Support for i32 {}
+
+    struct Line;
+    impl Measure for Line {
+        type X = i32;
+        type Y = i32;
+        fn distance(&self, x: &i32, y: &i32) -> f32 {
+            (x - y).abs() as f32 / 10.
+        }
+    }
+
+    struct Points(BTreeMap<i32, f32>);
+    impl Density for Points {
+        type S = i32;
+        fn density(&self, x: &i32) -> f32 {
+            self.0.get(x).copied().unwrap_or(0.)
+        }
+        fn support(&self) -> impl Iterator<Item = &i32> {
+            self.0.keys()
+        }
+    }
+
+    #[test]
+    fn is_generic_emd_translation() {
+        const TOLERANCE: f32 = 0.01;
+        let p = Points(BTreeMap::from([(0, 0.5), (1, 0.5)]));
+        let q = Points(BTreeMap::from([(2, 0.5), (3, 0.5)]));
+        let d = emd(&p, &q, &Line);
+        assert!((d - 0.2).abs() <= TOLERANCE, "{}", d);
+    }
+    #[test]
+    fn colder_is_closer_to_exact() {
+        // optimal plan sends 0 -> 1 and 3 -> 2, but a warm
+        // coupling leaks mass along the costlier crossing
+        let p = Points(BTreeMap::from([(0, 0.5), (3, 0.5)]));
+        let q = Points(BTreeMap::from([(1, 0.5), (2, 0.5)]));
+        let exact = 0.1;
+        let cost = |temperature: Entropy| {
+            Sinkhorn::from((&p, &q, &Line))
+                .with_temperature(temperature)
+                .with_iterations(4096)
+                .with_tolerance(1e-4)
+                .minimize()
+                .cost()
+        };
+        let warm = (cost(0.1) - exact).abs();
+        let cold = (cost(0.01) - exact).abs();
+        assert!(cold < warm, "{} !< {}", cold, warm);
+    }
+    #[test]
+    fn is_generic_emd_zero() {
+        const TOLERANCE: f32 = 0.01;
+        let p = Points(BTreeMap::from([(0, 0.25), (4, 0.75)]));
+        let d = emd(&p, &p, &Line);
+        assert!(d <= TOLERANCE, "{}", d);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::cards::street::Street;
+use crate::Energy;
+
+/// what one kmeans iteration did, reported to whoever is
+/// watching Layer::learn_with alongside the progress bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationStats {
+    pub street: Street,
+    /// zero-based, counting from the start of the run even
+    /// when we resumed from a Checkpoint partway through
+    pub iteration: usize,
+    /// RMS distance from each point assigned this iteration
+    /// to its nearest centroid
+    pub loss: Energy,
+    /// centroids holding no points after this iteration, which
+    /// full batch kmeans leaves empty until some point lands on
+    /// them again. mini-batch centroids keep their old points.
+    pub empty: usize,
+}
@@ -0,0 +1,46 @@
+use crate::cards::isomorphism::Isomorphism;
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use std::collections::BTreeMap;
+
+/// wherever the learned encoder lives. consumers only need
+/// the Abstraction of an Isomorphism, or every Abstraction
+/// on a Street, so PGCOPY files, Postgres, and plain memory
+/// are interchangeable behind this.
+pub trait Store {
+    /// precomputed abstraction for this isomorphism, if any
+    fn abstraction(&self, iso: &Isomorphism) -> Option<Abstraction>;
+    /// distinct abstractions assigned on this street, in order
+    fn abstractions(&self, street: Street) -> Vec<Abstraction>;
+}
+
+/// in-memory store, as loaded for training
+impl Store for BTreeMap<Isomorphism, Abstraction> {
+    fn abstraction(&self, iso: &Isomorphism) -> Option<Abstraction> {
+        self.get(iso).copied()
+    }
+    fn abstractions(&self, street: Street) -> Vec<Abstraction> {
+        self.iter()
+            .filter(|(iso, _)| iso.0.street() == street)
+            .map(|(_, abs)| *abs)
+            .collect::<std::collections::BTreeSet<Abstraction>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::observation::Observation;
+
+    #[test]
+    fn in_memory() {
+        let ref iso = Isomorphism::from(Observation::from(Street::Flop));
+        let ref abs = Abstraction::from((Street::Flop, 7));
+        let store = BTreeMap::from([(*iso, *abs)]);
+        assert!(store.abstraction(iso) == Some(*abs));
+        assert!(store.abstractions(Street::Flop) == vec![*abs]);
+        assert!(store.abstractions(Street::Turn).is_empty());
+    }
+}
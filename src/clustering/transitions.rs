@@ -5,7 +5,15 @@ use crate::Save;
 use std::collections::BTreeMap;
 
 pub struct Decomp(BTreeMap<Abstraction, Histogram>);
-impl Decomp {}
+impl Decomp {
+    /// companion to Lookup::merge_abstractions. drop b from the
+    /// transition table and replace a with the re-derived centroid.
+    pub fn merge(&mut self, a: &Abstraction, b: &Abstraction, centroid: Histogram) {
+        assert!(a != b, "cannot merge abstraction into itself");
+        self.0.remove(b);
+        self.0.insert(*a, centroid);
+    }
+}
 impl Save for Decomp {
     fn name() -> &'static str {
         "pgcopy.transitions."
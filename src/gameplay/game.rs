@@ -1,34 +1,55 @@
 use super::action::Action;
+use super::replay::ReplayError;
 use super::seat::Seat;
 use super::seat::State;
 use super::settlement::Settlement;
+use super::structure::BettingStructure;
 use crate::cards::board::Board;
 use crate::cards::deck::Deck;
 use crate::cards::hand::Hand;
+use crate::cards::hands::HandIterator;
+use crate::cards::isomorphism::Isomorphism;
 use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::cards::strength::Strength;
 use crate::gameplay::ply::Ply;
 use crate::gameplay::showdown::Showdown;
+use crate::mccfr::edge::Edge;
+use crate::mccfr::odds::Odds;
 use crate::players::human::Human;
+use crate::Arbitrary;
 use crate::Chips;
+use crate::Probability;
 use crate::N;
 use crate::STACK;
 
 type Position = usize;
+/// a single recorded decision: the actor's canonical Observation,
+/// every legal Edge available to them, and the Edge they took.
+pub type Decision = (Observation, Vec<Edge>, Edge);
 /// Rotation represents the memoryless state of the game in between actions.
 ///
 /// It records both public and private data structs, and is responsible for managing the
 /// rotation of players, the pot, and the board. Its immutable methods reveal
 /// pure functions representing the rules of how the game may proceed.
 /// This full game state will also be our CFR node representation.
+///
+/// P is the number of seats. the solver only ever plays heads-up,
+/// so P defaults to N and Game alone means the heads-up table;
+/// Game::<6>::from([STACK; 6]) deals a six-handed hand instead.
+///
+/// games are no-limit and rake free unless with_structure
+/// and with_rake say otherwise.
 #[derive(Debug, Clone, Copy)]
-pub struct Game {
-    seats: [Seat; N],
+pub struct Game<const P: usize = N> {
+    seats: [Seat; P],
     pot: Chips,
     board: Board,
     dealer: Position,
     ticker: Position,
+    structure: BettingStructure,
+    rate: Probability,
+    cap: Chips,
 }
 
 impl Game {
@@ -38,26 +59,31 @@ impl Game {
     /// these should not matter too much in the MCCFR algorithm,
     /// as long as we alternate the traverser/paths explored
     pub fn root() -> Self {
-        let mut root = Self {
-            pot: 0 as Chips,
-            dealer: 0usize,
-            ticker: 0usize,
-            board: Board::empty(),
-            seats: [Seat::from(STACK); N],
-        };
-        root.next_player();
-        root.deal_cards();
-        root.post_blinds(Self::sblind());
-        root.post_blinds(Self::bblind());
-        root
+        Self::from([STACK; N])
     }
-    pub fn n(&self) -> usize {
-        self.seats.len()
-    }
-    pub fn apply(&self, action: Action) -> Self {
-        let mut child = self.clone();
-        child.act(action);
-        child
+    /// replay abstract actions from the root, checking each one
+    /// is legal where it is taken. Draw deals random cards, and
+    /// Raise snaps to the legal range as it does in the tree.
+    pub fn from_actions(actions: &[Edge]) -> Result<Self, ReplayError> {
+        actions
+            .iter()
+            .enumerate()
+            .try_fold(Self::root(), |game, (index, edge)| {
+                let legal = game.legal().into_iter().any(|a| match a {
+                    Action::Raise(_) => edge.is_raise(),
+                    Action::Blind(_) => false,
+                    a => Edge::from(a) == *edge,
+                });
+                match (game.is_terminal(), legal) {
+                    (true, _) => Err(ReplayError::Terminal { index, edge: *edge }),
+                    (_, true) => Ok(game.apply(edge.actionize(&game))),
+                    (_, false) => Err(ReplayError::Illegal {
+                        index,
+                        edge: *edge,
+                        legal: game.legal().into_iter().map(|a| game.edgify(a)).collect(),
+                    }),
+                }
+            })
     }
     pub fn play() -> ! {
         let mut node = Self::root();
@@ -65,7 +91,10 @@ impl Game {
             match node.player() {
                 Ply::Chance => todo!(), // node.show_revealed(),
                 Ply::Choice(_) => {
-                    node.act(Human::decide(&node));
+                    let clock = std::time::Instant::now();
+                    let action = Human::decide(&node);
+                    node.actor_mut().tick(clock.elapsed());
+                    node.act(action);
                 }
                 Ply::Terminal => {
                     node.conclude();
@@ -74,11 +103,143 @@ impl Game {
             }
         }
     }
+    /// blinds don't depend on the number of seats. living here,
+    /// Game::bblind() resolves without anyone having to name P.
+    const fn bblind() -> Chips {
+        crate::B_BLIND
+    }
+    const fn sblind() -> Chips {
+        crate::S_BLIND
+    }
+}
+
+impl<const P: usize> Game<P> {
+    pub fn n(&self) -> usize {
+        self.seats.len()
+    }
+    /// play this hand under a different BettingStructure
+    pub fn with_structure(mut self, structure: BettingStructure) -> Self {
+        self.structure = structure;
+        self
+    }
+    pub fn structure(&self) -> BettingStructure {
+        self.structure
+    }
+    /// have the house take this fraction of every pot, up to cap.
+    /// zero rate, the default, keeps the game zero-sum.
+    pub fn with_rake(mut self, rate: Probability, cap: Chips) -> Self {
+        assert!((0. ..=1.).contains(&rate));
+        self.rate = rate;
+        self.cap = cap;
+        self
+    }
+    pub fn apply(&self, action: Action) -> Self {
+        let mut child = self.clone();
+        child.act(action);
+        child
+    }
+    /// same as apply, but also charge the actor for the time
+    /// they took to decide. purely bookkeeping for hand histories,
+    /// the solver never calls this so its seats stay untimed.
+    pub fn apply_timed(&self, action: Action, elapsed: std::time::Duration) -> Self {
+        let mut child = self.clone();
+        child.actor_mut().tick(elapsed);
+        child.act(action);
+        child
+    }
+    /// play one hand from the root, asking agents[seat] for every
+    /// decision and dealing chance nodes at random. records the full
+    /// decision trajectory alongside the final settlements, with
+    /// observations canonicalized to match the encoder's keys.
+    pub fn play_and_record(
+        agents: &mut [Box<dyn FnMut(&Game<P>) -> Action>],
+    ) -> (Vec<Decision>, Vec<Settlement>) {
+        assert!(agents.len() == P, "one agent per seat");
+        let mut node = Self::from([STACK; P]);
+        let mut trajectory = Vec::new();
+        loop {
+            match node.player() {
+                Ply::Chance => node.act(Action::Draw(node.draw())),
+                Ply::Choice(seat) => {
+                    let action = agents[seat](&node);
+                    let observation =
+                        Observation::from(Isomorphism::from(Observation::from(&node)));
+                    let choices = node
+                        .legal()
+                        .into_iter()
+                        .flat_map(|a| node.edgifications(a))
+                        .collect::<Vec<Edge>>();
+                    trajectory.push((observation, choices, node.edgify(action)));
+                    node.act(action);
+                }
+                Ply::Terminal => return (trajectory, node.settlements()),
+            }
+        }
+    }
+    /// uniformly random legal actions from here to the end of
+    /// the hand. every proper prefix leads to a live state.
+    pub fn rollout(&self) -> Vec<Action> {
+        use rand::seq::SliceRandom;
+        let ref mut rng = crate::rng();
+        let mut node = *self;
+        let mut actions = Vec::new();
+        while !node.is_terminal() {
+            let action = *node.legal().choose(rng).expect("legal action");
+            node.act(action);
+            actions.push(action);
+        }
+        actions
+    }
+    /// any raise size above the minimum is legal,
+    /// so a Raise opens up the whole Odds grid
+    fn edgifications(&self, action: Action) -> Vec<Edge> {
+        match action {
+            Action::Raise(_) => Odds::GRID.map(Edge::Raise).to_vec(),
+            action => vec![Edge::from(action)],
+        }
+    }
+    /// snap a concrete raise to the nearest pot-relative Odds
+    fn edgify(&self, action: Action) -> Edge {
+        match action {
+            Action::Raise(chips) => Edge::Raise(Odds::nearest((chips, self.pot()))),
+            action => Edge::from(action),
+        }
+    }
 
     //
+    /// the canonical database key for the acting seat's view of
+    /// the game. every lookup keyed by observation goes through
+    /// Isomorphism first, so derive it once here in that order.
+    pub fn canonical_key(&self) -> i64 {
+        Isomorphism::key(Observation::from(self))
+    }
+    /// every chip committed this hand, across all streets. bets go
+    /// in as soon as they're made, so a bet or raise that hasn't been
+    /// called yet is already counted in full, uncalled excess included.
     pub fn pot(&self) -> Chips {
         self.pot
     }
+    /// chips this seat has committed so far this hand
+    pub fn spent(&self, position: usize) -> Chips {
+        self.seats
+            .get(position)
+            .map(Seat::spent)
+            .expect("position in bounds")
+    }
+    /// the most any one player can still win or lose this street:
+    /// the second-largest of stack plus current stake among seats
+    /// still in the hand. heads-up this is just the smaller stack.
+    pub fn effective_stack(&self) -> Chips {
+        let mut totals = self
+            .seats
+            .iter()
+            .filter(|s| s.state() != State::Folding)
+            .map(|s| s.stack() + s.stake())
+            .collect::<Vec<Chips>>();
+        totals.sort_unstable();
+        totals.pop().unwrap_or(0);
+        totals.pop().unwrap_or(0)
+    }
     pub fn board(&self) -> Board {
         self.board
     }
@@ -107,7 +268,7 @@ impl Game {
             return options;
         }
         if self.is_blinding() {
-            options.push(Action::Blind(Self::sblind()));
+            options.push(Action::Blind(Game::sblind()));
             return options;
         }
         if self.can_raise() {
@@ -138,8 +299,8 @@ impl Game {
         self.wipe_board();
         self.deal_cards();
         self.move_button();
-        self.post_blinds(Self::sblind());
-        self.post_blinds(Self::bblind());
+        self.post_blinds(Game::sblind());
+        self.post_blinds(Game::bblind());
     }
     fn give_chips(&mut self) {
         log::trace!("::::::::::::::");
@@ -167,14 +328,15 @@ impl Game {
             seat.reset_cards(deck.hole());
             seat.reset_stake();
             seat.reset_spent();
+            seat.reset_clock();
         }
     }
     fn move_button(&mut self) {
-        assert!(self.seats.len() == N);
+        assert!(self.seats.len() == P);
         assert!(self.board.street() == Street::Pref);
         self.dealer += 1;
-        self.dealer %= N;
-        self.ticker = self.dealer;
+        self.dealer %= P;
+        self.ticker = 0;
         self.next_player();
     }
     fn post_blinds(&mut self, blind: Chips) {
@@ -209,7 +371,17 @@ impl Game {
                 self.actor_mut().reset_state(State::Folding);
                 self.next_player();
             }
-            &Action::Blind(chips) | &Action::Raise(chips) | &Action::Call(chips) => {
+            &Action::Raise(chips) => {
+                assert!(chips >= self.to_raise(), "raise below minimum");
+                assert!(
+                    chips <= self.to_max_raise(),
+                    "raise above {}",
+                    self.structure
+                );
+                self.bet(chips);
+                self.next_player();
+            }
+            &Action::Blind(chips) | &Action::Call(chips) => {
                 self.bet(chips);
                 self.next_player();
             }
@@ -227,7 +399,7 @@ impl Game {
     }
     fn reveal(&mut self, hand: Hand) {
         // tightly coupled with next_street?
-        self.ticker = self.dealer;
+        self.ticker = 0;
         self.board.add(hand);
     }
     fn next_street(&mut self) {
@@ -256,6 +428,12 @@ impl Game {
             self.is_everyone_folding()
         }
     }
+    /// hands are only compared if more than one player is left.
+    /// when everyone folds to a single player, including the
+    /// preflop walk to the big blind, the pot is uncontested.
+    pub fn is_showdown(&self) -> bool {
+        self.is_terminal() && !self.is_everyone_folding()
+    }
     /// we're waiting for a card to be revealed
     fn is_sampling(&self) -> bool {
         if self.board.street() == Street::Rive {
@@ -267,7 +445,7 @@ impl Game {
     /// blinds have not yet been posted // TODO some edge case of all in blinds
     fn is_blinding(&self) -> bool {
         if self.board.street() == Street::Pref {
-            self.pot() < Self::sblind() + Self::bblind()
+            self.pot() < Game::sblind() + Game::bblind()
         } else {
             false
         }
@@ -284,9 +462,9 @@ impl Game {
     fn is_everyone_touched(&self) -> bool {
         self.ticker
             > if self.board.street() == Street::Pref {
-                N + 2
+                P + 2
             } else {
-                N
+                P
             }
     }
     /// all players betting are in for the effective stake
@@ -324,19 +502,55 @@ impl Game {
         self.effective_stake() == self.actor_ref().stake()
     }
     fn can_raise(&self) -> bool {
-        self.to_raise() < self.to_shove()
+        self.to_raise() < self.to_shove() && self.to_raise() <= self.to_max_raise()
     }
     fn can_shove(&self) -> bool {
-        self.to_shove() > 0
+        self.to_shove() > 0 && self.to_shove() <= self.to_max_raise()
     }
 
     //
+    /// chips the actor must add to match the largest stake this street
     pub fn to_call(&self) -> Chips {
         self.effective_stake() - self.actor_ref().stake()
     }
     pub fn to_shove(&self) -> Chips {
         self.actor_ref().stack()
     }
+    /// the most chips the actor may put in right now, as the
+    /// BettingStructure allows and the stack covers. under a
+    /// limit, a short stack may still go all in for less,
+    /// including calling all in for less than the bet.
+    pub fn to_max_raise(&self) -> Chips {
+        let cap = self
+            .structure
+            .cap(self.street(), self.to_call(), self.pot());
+        match self.to_shove() {
+            stack if stack <= self.to_call() => stack,
+            stack => stack.min(cap),
+        }
+    }
+    /// equity needed for calling to break even: the call, capped at
+    /// our stack, over everything that call can win. chips we put in
+    /// earlier are already in the pot and sunk, so they count toward
+    /// the prize but not the price. if we can't cover the bet, the
+    /// excess we can't match is returned and so isn't part of the prize.
+    pub fn continue_threshold(&self) -> Probability {
+        let owed = self.to_call();
+        let call = owed.min(self.to_shove());
+        match call {
+            0 => 0.,
+            _ => call as Probability / (self.pot() - owed + call + call) as Probability,
+        }
+    }
+    /// minimum defense frequency against the bet we face, i.e. how
+    /// often we must continue so that an opponent bluffing with any
+    /// two cards doesn't profit. 1 when there is nothing to defend.
+    pub fn minimum_defense(&self) -> Probability {
+        match self.to_call() {
+            0 => 1.,
+            bet => (self.pot() - bet) as Probability / self.pot() as Probability,
+        }
+    }
     pub fn to_raise(&self) -> Chips {
         let (most_large_stake, next_large_stake) = self
             .seats
@@ -354,14 +568,31 @@ impl Game {
             });
         let relative_raise = most_large_stake - self.actor().stake();
         let marginal_raise = most_large_stake - next_large_stake;
-        let required_raise = std::cmp::max(marginal_raise, Self::bblind());
+        let required_raise = match self.structure.bet(self.street()) {
+            Some(bet) => bet,
+            None => std::cmp::max(marginal_raise, Game::bblind()),
+        };
         relative_raise + required_raise
     }
 
     //
+    /// Showdown hands odd chips to whoever comes first in its
+    /// ledger, so we list seats from the small blind around to
+    /// the dealer and then rotate the results back into seat order.
     pub fn settlements(&self) -> Vec<Settlement> {
         assert!(self.is_terminal(), "non terminal game state:\n{}", self);
-        Showdown::from(self.ledger()).settle()
+        let first = (self.dealer + 1) % P;
+        let mut ledger = self.ledger();
+        ledger.rotate_left(first);
+        let mut settlements = Showdown::from(ledger)
+            .with_rake(self.rate, self.cap)
+            .settle();
+        settlements.rotate_right(first);
+        settlements
+    }
+    /// chips the house takes from this pot at settlement
+    pub fn rake(&self) -> Chips {
+        Showdown::rake(self.pot(), self.rate, self.cap)
     }
     fn ledger(&self) -> Vec<Settlement> {
         self.seats
@@ -374,7 +605,11 @@ impl Game {
             reward: 0,
             risked: seat.spent(),
             status: seat.state(),
-            strength: self.strength(seat),
+            strength: match self.is_showdown() && seat.state() != State::Folding {
+                true => Some(self.strength(seat)),
+                false => None,
+            },
+            time_used: seat.time_used(),
         }
     }
     fn strength(&self, seat: &Seat) -> Strength {
@@ -388,6 +623,12 @@ impl Game {
     pub fn draw(&self) -> Hand {
         self.deck().deal(self.board().street())
     }
+    /// every Hand the dealer could reveal on this street
+    pub fn draws(&self) -> Vec<Hand> {
+        let street = self.board().street();
+        let blocked = Hand::from(self.deck()).complement();
+        HandIterator::from((street.n_revealed(), blocked)).collect()
+    }
     fn deck(&self) -> Deck {
         let mut removed = Hand::from(self.board);
         for seat in self.seats.iter() {
@@ -397,7 +638,7 @@ impl Game {
         Deck::from(removed.complement())
     }
     fn actor_idx(&self) -> Position {
-        (self.dealer + self.ticker) % N
+        (self.dealer + self.ticker) % P
     }
     fn actor_ref(&self) -> &Seat {
         let index = self.actor_idx();
@@ -411,19 +652,6 @@ impl Game {
             .get_mut(index)
             .expect("index should be in bounds bc modulo")
     }
-
-    //
-    #[allow(dead_code)]
-    fn effective_stack(&self) -> Chips {
-        let mut totals = self
-            .seats
-            .iter()
-            .map(|s| s.stack() + s.stake())
-            .collect::<Vec<Chips>>();
-        totals.sort_unstable();
-        totals.pop().unwrap_or(0);
-        totals.pop().unwrap_or(0)
-    }
     fn effective_stake(&self) -> Chips {
         self.seats
             .iter()
@@ -431,16 +659,50 @@ impl Game {
             .max()
             .expect("non-empty seats")
     }
+}
 
-    const fn bblind() -> Chips {
-        crate::B_BLIND
+/// a fresh hand with these stacks, dealer in seat 0. the seats
+/// after the dealer post the small and then the big blind, and
+/// the first decision falls to the seat after the big blind.
+/// heads-up that wraps around: seat 1 posts the small blind and
+/// acts first, and the dealer in seat 0 posts the big blind.
+impl<const P: usize> From<[Chips; P]> for Game<P> {
+    fn from(stacks: [Chips; P]) -> Self {
+        assert!(P >= 2, "at least two seats");
+        let mut root = Self {
+            pot: 0 as Chips,
+            dealer: 0usize,
+            ticker: 0usize,
+            board: Board::empty(),
+            seats: stacks.map(Seat::from),
+            structure: BettingStructure::default(),
+            rate: 0.,
+            cap: Chips::MAX,
+        };
+        root.next_player();
+        root.deal_cards();
+        root.post_blinds(Game::sblind());
+        root.post_blinds(Game::bblind());
+        root
     }
-    const fn sblind() -> Chips {
-        crate::S_BLIND
+}
+
+/// a live state reached by random legal play from the root,
+/// so stacks, pot, street and actor are always consistent
+impl Arbitrary for Game {
+    fn random() -> Self {
+        use rand::Rng;
+        let root = Self::root();
+        let actions = root.rollout();
+        let depth = crate::rng().gen_range(0..actions.len());
+        actions
+            .into_iter()
+            .take(depth)
+            .fold(root, |game, action| game.apply(action))
     }
 }
 
-impl std::fmt::Display for Game {
+impl<const P: usize> std::fmt::Display for Game<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         use colored::Colorize;
         for seat in self.seats.iter() {
@@ -455,8 +717,8 @@ impl std::fmt::Display for Game {
     }
 }
 
-impl From<&Game> for Observation {
-    fn from(game: &Game) -> Self {
+impl<const P: usize> From<&Game<P>> for Observation {
+    fn from(game: &Game<P>) -> Self {
         Observation::from((
             Hand::from(game.actor().cards()), //
             Hand::from(game.board()),         //
@@ -464,9 +726,34 @@ impl From<&Game> for Observation {
     }
 }
 
+/// fixtures for tests that need a Game deep into a hand
+#[cfg(test)]
+impl Game {
+    /// check if we can, otherwise call
+    pub fn passive(&self) -> Action {
+        self.legal()
+            .into_iter()
+            .find(|a| matches!(a, Action::Check | Action::Call(_)))
+            .expect("passive action")
+    }
+    /// check and call from the root, dealing at random, until
+    /// the first decision on this street
+    pub fn checked_down(street: Street) -> Self {
+        let mut game = Self::root();
+        while game.street() != street || game.player() == Ply::Chance {
+            game = match game.player() {
+                Ply::Chance => game.apply(Action::Draw(game.draw())),
+                _ => game.apply(game.passive()),
+            };
+        }
+        game
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cards::hole::Hole;
 
     #[test]
     fn test_root() {
@@ -488,6 +775,262 @@ mod tests {
         assert!(game.is_terminal() == true);
     }
     #[test]
+    fn time_used() {
+        use std::time::Duration;
+        let game = Game::root();
+        let sb = game.actor_idx();
+        let solver = game.apply(Action::Fold);
+        assert!(solver.settlements().iter().all(|s| s.time_used.is_none()));
+        let timed = game.apply_timed(Action::Fold, Duration::from_millis(1500));
+        let settlements = timed.settlements();
+        assert!(settlements[sb].time_used == Some(Duration::from_millis(1500)));
+        assert!(settlements[(sb + 1) % N].time_used.is_none());
+    }
+    #[test]
+    fn play_and_record() {
+        let passive = |game: &Game| {
+            let legal = game.legal();
+            legal
+                .iter()
+                .find(|a| matches!(a, Action::Check))
+                .or_else(|| legal.iter().find(|a| matches!(a, Action::Call(_))))
+                .copied()
+                .expect("check or call")
+        };
+        let mut agents = (0..N)
+            .map(|_| Box::new(passive) as Box<dyn FnMut(&Game) -> Action>)
+            .collect::<Vec<_>>();
+        let (trajectory, settlements) = Game::play_and_record(&mut agents);
+        assert!(trajectory.len() >= 2 * 4 - 1);
+        assert!(trajectory
+            .iter()
+            .all(|(_, legal, edge)| legal.contains(edge)));
+        assert!(trajectory
+            .iter()
+            .all(|(o, _, _)| *o == Observation::from(Isomorphism::from(*o))));
+        let pot = settlements.iter().map(|s| s.risked).sum::<Chips>();
+        assert!(pot > 0);
+        assert!(settlements.iter().map(|s| s.pnl()).sum::<Chips>() == 0);
+    }
+    #[test]
+    fn random_is_reachable() {
+        for _ in 0..256 {
+            let game = Game::random();
+            let stacks = game.seats.iter().map(|s| s.stack()).sum::<Chips>();
+            assert!(!game.is_terminal());
+            assert!(game.pot() + stacks == STACK * N as Chips);
+            assert!(!game.legal().is_empty());
+            let end = game
+                .rollout()
+                .into_iter()
+                .fold(game, |game, action| game.apply(action));
+            assert!(end.settlements().iter().map(|s| s.pnl()).sum::<Chips>() == -end.rake());
+        }
+    }
+    #[test]
+    fn from_actions() {
+        let line = [Edge::Call, Edge::Check, Edge::Draw, Edge::Raise(Odds(1, 2))];
+        let game = Game::from_actions(&line).unwrap();
+        assert!(game.street() == Street::Flop);
+        assert!(game.pot() == 4 + 2);
+        let line = [Edge::Call, Edge::Check, Edge::Draw, Edge::Call];
+        let error = Game::from_actions(&line).unwrap_err();
+        assert!(matches!(error, ReplayError::Illegal { index: 3, .. }));
+        let line = [Edge::Fold, Edge::Check];
+        let error = Game::from_actions(&line).unwrap_err();
+        assert!(matches!(error, ReplayError::Terminal { index: 1, .. }));
+    }
+    #[test]
+    fn canonical_key() {
+        use rand::seq::SliceRandom;
+        for _ in 0..64 {
+            let mut game = Game::root();
+            while !game.is_terminal() {
+                let key = i64::from(Observation::from(Isomorphism::from(Observation::from(
+                    &game,
+                ))));
+                assert!(game.canonical_key() == key);
+                let action = *game
+                    .legal()
+                    .choose(&mut crate::rng())
+                    .expect("legal action");
+                game = game.apply(action);
+            }
+        }
+    }
+    #[test]
+    fn continue_threshold() {
+        let game = Game::root();
+        assert!(game.continue_threshold() == 1. / 4.);
+        assert!(game.minimum_defense() == 2. / 3.);
+        let game = game.apply(Action::Call(1));
+        assert!(game.continue_threshold() == 0.);
+        assert!(game.minimum_defense() == 1.);
+        let game = game.apply(Action::Raise(4));
+        assert!(game.continue_threshold() == 4. / 12.);
+        assert!(game.minimum_defense() == 4. / 8.);
+    }
+    #[test]
+    fn effective_stack() {
+        let game = Game::root();
+        assert!(game.effective_stack() == STACK);
+        let game = game.apply(Action::Raise(9));
+        assert!(game.pot() == 12);
+        assert!(game.to_call() == 8);
+        assert!(game.effective_stack() == STACK);
+    }
+    #[test]
+    fn walk_to_big_blind() {
+        let game = Game::root();
+        let sb = game.actor_idx();
+        let game = game.apply(Action::Fold);
+        assert!(game.player() == Ply::Terminal);
+        assert!(game.is_showdown() == false);
+        assert!(game.board().street() == Street::Pref);
+        let settlements = game.settlements();
+        assert!(settlements.iter().all(|s| s.strength.is_none()));
+        assert!(settlements[sb].pnl() == -Game::sblind());
+        assert!(settlements[(sb + 1) % N].pnl() == Game::sblind());
+        assert!(settlements[sb].to_string().ends_with("folded"));
+        assert!(settlements[(sb + 1) % N]
+            .to_string()
+            .ends_with("uncontested"));
+    }
+    #[test]
+    fn rake_up_to_cap() {
+        let showdown = |game: Game| {
+            let mut game = game;
+            while !game.is_terminal() {
+                game = match game.player() {
+                    Ply::Chance => game.apply(Action::Draw(game.draw())),
+                    _ => game.apply(game.passive()),
+                };
+            }
+            game
+        };
+        let game = Game::checked_down(Street::Rive);
+        let free = showdown(game);
+        let raked = showdown(game.with_rake(0.5, 1));
+        assert!(free.pot() == raked.pot());
+        assert!(free.rake() == 0);
+        assert!(raked.rake() == 1);
+        assert!(free.settlements().iter().map(|s| s.pnl()).sum::<Chips>() == 0);
+        assert!(raked.settlements().iter().map(|s| s.pnl()).sum::<Chips>() == -1);
+    }
+    #[test]
+    fn heads_up_blinds() {
+        let game = Game::root();
+        assert!(game.spent(0) == Game::bblind());
+        assert!(game.spent(1) == Game::sblind());
+        assert!(game.player() == Ply::Choice(1));
+    }
+    #[test]
+    fn button_moves_blinds() {
+        let mut game = Game::<3>::from([STACK; 3])
+            .apply(Action::Fold)
+            .apply(Action::Fold);
+        assert!(game.is_terminal());
+        game.conclude();
+        game.commence();
+        assert!(game.dealer == 1);
+        assert!(game.spent(2) == Game::sblind());
+        assert!(game.spent(0) == Game::bblind());
+        assert!(game.player() == Ply::Choice(1));
+        let game = game.apply(Action::Call(2)).apply(Action::Call(1));
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Draw(game.draw()));
+        assert!(game.player() == Ply::Choice(2));
+    }
+    #[test]
+    fn three_handed_rotation() {
+        let game = Game::<3>::from([STACK; 3]);
+        assert!(game.pot() == 3);
+        assert!(game.spent(1) == Game::sblind());
+        assert!(game.spent(2) == Game::bblind());
+        assert!(game.player() == Ply::Choice(0));
+        let game = game.apply(Action::Call(2));
+        assert!(game.player() == Ply::Choice(1));
+        let game = game.apply(Action::Fold);
+        assert!(game.player() == Ply::Choice(2));
+        let game = game.apply(Action::Check);
+        assert!(game.player() == Ply::Chance);
+        let flop = game.draw();
+        let game = game.apply(Action::Draw(flop));
+        assert!(game.player() == Ply::Choice(2));
+        let game = game.apply(Action::Check);
+        assert!(game.player() == Ply::Choice(0));
+        let game = game.apply(Action::Check);
+        assert!(game.player() == Ply::Chance);
+    }
+    #[test]
+    fn three_handed_side_pots() {
+        let hole = |cards: &str| Hole::from(Hand::try_from(cards).unwrap());
+        let board = |cards: &str| Hand::try_from(cards).unwrap();
+        let mut game = Game::<3>::from([100, 40, 70]);
+        game.seats[0].reset_cards(hole("Ac Ad"));
+        game.seats[1].reset_cards(hole("Ks Kh"));
+        game.seats[2].reset_cards(hole("Js Jh"));
+        let game = game.apply(Action::Shove(100));
+        let game = game.apply(Action::Shove(39));
+        let game = game.apply(Action::Shove(68));
+        assert!(game.player() == Ply::Chance);
+        let game = game.apply(Action::Draw(board("6c 7d 9h")));
+        let game = game.apply(Action::Draw(board("Jd")));
+        let game = game.apply(Action::Draw(board("Kc")));
+        assert!(game.player() == Ply::Terminal);
+        assert!(game.is_showdown());
+        let settlements = game.settlements();
+        assert!(settlements[1].reward == 40 * 3);
+        assert!(settlements[2].reward == 30 * 2);
+        assert!(settlements[0].reward == 30);
+        assert!(settlements.iter().map(|s| s.pnl()).sum::<Chips>() == 0);
+    }
+    #[test]
+    fn odd_chip_goes_left_of_dealer() {
+        let hole = |cards: &str| Hole::from(Hand::try_from(cards).unwrap());
+        let board = |cards: &str| Hand::try_from(cards).unwrap();
+        let mut game = Game::<3>::from([STACK; 3]);
+        game.seats[0].reset_cards(hole("6s 7s"));
+        game.seats[1].reset_cards(hole("6h 7h"));
+        game.seats[2].reset_cards(hole("8d 9d"));
+        let game = game.apply(Action::Call(2));
+        let game = game.apply(Action::Fold);
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Draw(board("Ac Kc Qd")));
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Draw(board("Jh")));
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Draw(board("Tc")));
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Check);
+        let settlements = game.settlements();
+        assert!(game.pot() == 5);
+        assert!(settlements[0].reward == 2);
+        assert!(settlements[1].reward == 0);
+        assert!(settlements[2].reward == 3);
+    }
+    #[test]
+    fn multiway_rollouts_conserve_chips() {
+        fn rollouts<const P: usize>() {
+            for _ in 0..64 {
+                let root = Game::<P>::from([STACK; P]);
+                let end = root
+                    .rollout()
+                    .into_iter()
+                    .fold(root, |game, action| game.apply(action));
+                let stacks = end.seats.iter().map(|s| s.stack()).sum::<Chips>();
+                assert!(end.pot() + stacks == STACK * P as Chips);
+                assert!(end.settlements().iter().map(|s| s.pnl()).sum::<Chips>() == -end.rake());
+            }
+        }
+        rollouts::<3>();
+        rollouts::<4>();
+        rollouts::<5>();
+        rollouts::<6>();
+    }
+    #[test]
     fn everyone_folds_flop() {
         let game = Game::root();
         let flop = game.deck().deal(Street::Pref);
@@ -663,4 +1206,60 @@ mod tests {
         assert!(game.is_everyone_touched() == true); //
         assert!(game.is_everyone_matched() == true); //
     }
+
+    #[test]
+    fn no_limit_raises_to_the_stack() {
+        let game = Game::root();
+        assert!(game.structure() == BettingStructure::NoLimit);
+        assert!(game.to_max_raise() == STACK - Game::sblind());
+        assert!(game.legal().contains(&Action::Shove(game.to_shove())));
+    }
+    #[test]
+    fn pot_limit_raises_the_pot() {
+        let game = Game::root().with_structure(BettingStructure::PotLimit);
+        // call 1 to make the pot 4, then raise 4
+        assert!(game.pot() == 3);
+        assert!(game.to_max_raise() == 1 + 4);
+        assert!(!game.legal().iter().any(|a| matches!(a, Action::Shove(_))));
+        let max = Edge::Raise(Odds(4, 1)).actionize(&game);
+        assert!(max == Action::Raise(5));
+        // call 4 to make the pot 12, then raise 12
+        let game = game.apply(max);
+        assert!(game.pot() == 8);
+        assert!(game.to_max_raise() == 4 + 12);
+        // nothing to call on the flop, so a pot-sized bet
+        let game = game.apply(Action::Call(4));
+        let game = game.apply(Action::Draw(game.draw()));
+        assert!(game.pot() == 12);
+        assert!(game.to_max_raise() == 12);
+    }
+    #[test]
+    fn pot_limit_still_calls_all_in_short() {
+        // heads-up the dealer posts the small blind, so seat 0 is a 4 chip big blind
+        let game = Game::from([4, STACK]).with_structure(BettingStructure::PotLimit);
+        let game = game.apply(Action::Raise(5));
+        assert!(game.to_call() == 4);
+        assert!(game.to_max_raise() == 2);
+        assert!(game.legal().contains(&Action::Shove(2)));
+    }
+    #[test]
+    fn fixed_limit_raises_one_bet() {
+        let structure = BettingStructure::FixedLimit { small: 2, big: 4 };
+        let game = Game::root().with_structure(structure);
+        assert!(game.to_raise() == 1 + 2);
+        assert!(game.to_max_raise() == 1 + 2);
+        let game = game.apply(Action::Raise(3));
+        assert!(game.to_raise() == 2 + 2);
+        assert!(game.to_max_raise() == 2 + 2);
+        assert!(Edge::Raise(Odds(4, 1)).actionize(&game) == Action::Raise(4));
+        // the big bet from the turn on
+        let game = game.apply(Action::Call(2));
+        let game = game.apply(Action::Draw(game.draw()));
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Check);
+        let game = game.apply(Action::Draw(game.draw()));
+        assert!(game.street() == Street::Turn);
+        assert!(game.to_raise() == 4);
+        assert!(game.to_max_raise() == 4);
+    }
 }
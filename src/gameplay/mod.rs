@@ -1,6 +1,8 @@
 pub mod action;
 pub mod game;
 pub mod ply;
+pub mod replay;
 pub mod seat;
 pub mod settlement;
 pub mod showdown;
+pub mod structure;
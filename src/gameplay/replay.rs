@@ -0,0 +1,33 @@
+use crate::mccfr::edge::Edge;
+
+/// why a sequence of Edges could not be replayed from the root.
+/// index points at the first offending Edge in the sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    Illegal {
+        index: usize,
+        edge: Edge,
+        legal: Vec<Edge>,
+    },
+    Terminal {
+        index: usize,
+        edge: Edge,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Illegal { index, edge, legal } => write!(
+                f,
+                "action #{} {:?} is illegal, expected one of {:?}",
+                index, edge, legal
+            ),
+            Self::Terminal { index, edge } => {
+                write!(f, "action #{} {:?} comes after the hand ended", index, edge)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
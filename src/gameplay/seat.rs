@@ -1,6 +1,7 @@
 use crate::cards::hole::Hole;
 use crate::Chips;
 use colored::Colorize;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Seat {
@@ -9,6 +10,9 @@ pub struct Seat {
     stack: Chips,
     stake: Chips,
     spent: Chips,
+    /// decision time consumed this hand. only bookkeeping for
+    /// hand histories, so it stays None unless someone times us.
+    clock: Option<Duration>,
 }
 
 impl From<Chips> for Seat {
@@ -25,6 +29,7 @@ impl Seat {
             stake: 0,
             state: State::Betting,
             cards: Hole::empty(),
+            clock: None,
         }
     }
     pub fn stack(&self) -> Chips {
@@ -42,6 +47,9 @@ impl Seat {
     pub fn cards(&self) -> Hole {
         self.cards
     }
+    pub fn time_used(&self) -> Option<Duration> {
+        self.clock
+    }
 
     pub fn win(&mut self, win: Chips) {
         self.stack += win;
@@ -63,6 +71,12 @@ impl Seat {
     pub fn reset_spent(&mut self) {
         self.spent = 0;
     }
+    pub fn reset_clock(&mut self) {
+        self.clock = None;
+    }
+    pub fn tick(&mut self, elapsed: Duration) {
+        self.clock = Some(self.clock.unwrap_or_default() + elapsed);
+    }
 }
 
 impl std::fmt::Display for Seat {
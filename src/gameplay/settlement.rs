@@ -2,13 +2,18 @@ use crate::cards::strength::Strength;
 use crate::gameplay::seat::State;
 use crate::Chips;
 use colored::Colorize;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct Settlement {
     pub reward: Chips,
     pub risked: Chips,
     pub status: State,
-    pub strength: Strength,
+    /// None for anyone who folded, and for the last player
+    /// standing, since there was no showdown to evaluate
+    pub strength: Option<Strength>,
+    /// decision time the seat used this hand, if it was timed
+    pub time_used: Option<Duration>,
 }
 
 impl Settlement {
@@ -23,18 +28,24 @@ impl From<(Chips, State, Strength)> for Settlement {
             reward: 0,
             risked,
             status,
-            strength,
+            strength: Some(strength),
+            time_used: None,
         }
     }
 }
 
 impl std::fmt::Display for Settlement {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let strength = match (self.strength, self.status) {
+            (Some(strength), _) => strength.to_string(),
+            (None, State::Folding) => String::from("folded"),
+            (None, _) => String::from("uncontested"),
+        };
         if self.reward > 0 {
             let reward = format!("+{}", self.reward).green();
-            write!(f, "{:<5}{}", reward, self.strength)
+            write!(f, "{:<5}{}", reward, strength)
         } else {
-            write!(f, "     {}", self.strength)
+            write!(f, "     {}", strength)
         }
     }
 }
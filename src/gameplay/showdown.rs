@@ -4,6 +4,7 @@ use crate::cards::strength::Strength;
 use crate::gameplay::seat::State;
 use crate::gameplay::settlement::Settlement;
 use crate::Chips;
+use crate::Probability;
 
 // ephemeral data structure that is used to calculate the results of a hand by iterating over hand.actions to calculate side pots, handling every edge case with generalized zero-cost logic
 pub struct Showdown {
@@ -11,6 +12,8 @@ pub struct Showdown {
     distributing: Chips,
     distributed: Chips,
     best: Strength,
+    rate: Probability,
+    cap: Chips,
 }
 
 impl From<Vec<Settlement>> for Showdown {
@@ -20,12 +23,86 @@ impl From<Vec<Settlement>> for Showdown {
             distributing: 0 as Chips,
             distributed: 0 as Chips,
             best: Strength::from((Ranking::MAX, Kickers::default())),
+            rate: 0.,
+            cap: Chips::MAX,
         }
     }
 }
 
 impl Showdown {
-    pub fn settle(mut self) -> Vec<Settlement> {
+    /// take this fraction of the pot, up to cap, at settlement
+    pub fn with_rake(mut self, rate: Probability, cap: Chips) -> Self {
+        self.rate = rate;
+        self.cap = cap;
+        self
+    }
+    /// order seats from strongest to weakest hand, grouping
+    /// ties together. this is the same Strength ordering that
+    /// settle walks through, so the first group is exactly who
+    /// splits the main pot, independently of chips and side pots.
+    pub fn ranking(hands: &[(usize, Strength)]) -> Vec<(Strength, Vec<usize>)> {
+        let mut hands = hands.to_vec();
+        hands.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+        hands.into_iter().fold(
+            Vec::<(Strength, Vec<usize>)>::new(),
+            |mut ranks, (seat, strength)| {
+                match ranks.last_mut() {
+                    Some((best, seats)) if *best == strength => seats.push(seat),
+                    _ => ranks.push((strength, vec![seat])),
+                }
+                ranks
+            },
+        )
+    }
+    /// human readable account of who wins and with what
+    pub fn explain(hands: &[(usize, Strength)]) -> String {
+        Self::ranking(hands)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (strength, seats))| {
+                format!("{:>2}. seats {:?} with {}", i + 1, seats, strength)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    /// award the pot, side pots and all, then take the rake
+    /// out of the winners' rewards. the raked chips leave the
+    /// table, so rewards fall short of risked chips by the rake.
+    pub fn settle(self) -> Vec<Settlement> {
+        let pot = self.payouts.iter().map(|p| p.risked).sum::<Chips>();
+        let rake = Self::rake(pot, self.rate, self.cap);
+        Self::deduct(self.award(), rake)
+    }
+    /// house cut of a pot, rounded down and capped
+    pub fn rake(pot: Chips, rate: Probability, cap: Chips) -> Chips {
+        assert!((0. ..=1.).contains(&rate));
+        ((pot as Probability * rate).floor() as Chips).min(cap)
+    }
+    /// split the rake across winners in proportion to their
+    /// rewards, handing leftover chips to the biggest winners
+    fn deduct(mut payouts: Vec<Settlement>, rake: Chips) -> Vec<Settlement> {
+        let total = payouts.iter().map(|p| p.reward as i32).sum::<i32>();
+        if rake == 0 || total == 0 {
+            return payouts;
+        }
+        assert!(rake as i32 <= total);
+        let mut taken = 0;
+        for payout in payouts.iter_mut() {
+            let cut = (rake as i32 * payout.reward as i32 / total) as Chips;
+            payout.reward -= cut;
+            taken += cut;
+        }
+        let mut order = (0..payouts.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(payouts[i].reward));
+        for i in order.into_iter().cycle().take((rake - taken) as usize) {
+            payouts[i].reward -= 1;
+        }
+        payouts
+    }
+    fn award(mut self) -> Vec<Settlement> {
+        if self.is_uncontested() {
+            return self.walk();
+        }
         'winners: while let Some(strength) = self.strongest() {
             self.best = strength;
             'pots: while let Some(amount) = self.remaining() {
@@ -43,16 +120,16 @@ impl Showdown {
     fn strongest(&self) -> Option<Strength> {
         self.payouts
             .iter()
-            .filter(|p| p.strength < self.best)
             .filter(|p| p.status != State::Folding)
-            .map(|p| p.strength)
+            .filter_map(|p| p.strength)
+            .filter(|s| *s < self.best)
             .max()
     }
     fn remaining(&mut self) -> Option<Chips> {
         self.distributed = self.distributing;
         self.payouts
             .iter()
-            .filter(|p| p.strength == self.best)
+            .filter(|p| p.strength == Some(self.best))
             .filter(|p| p.risked > self.distributed)
             .filter(|p| p.status != State::Folding)
             .map(|p| p.risked)
@@ -66,13 +143,16 @@ impl Showdown {
             .map(|s| (s - self.distributed).max(0))
             .sum()
     }
+    /// split the current layer among everyone tied for best who
+    /// reached it. chips that don't divide evenly go one apiece to
+    /// the earliest winners in the ledger, which Game orders by position.
     fn distribute(&mut self) {
         let chips = self.winnings();
         let mut winners = self
             .payouts
             .iter_mut()
             .filter(|p| p.status != State::Folding)
-            .filter(|p| p.strength == self.best)
+            .filter(|p| p.strength == Some(self.best))
             .filter(|p| p.risked > self.distributed)
             .collect::<Vec<&mut Settlement>>();
         let n = winners.len();
@@ -85,6 +165,24 @@ impl Showdown {
             winner.reward += 1;
         }
     }
+    /// everyone but one player folded, so they take
+    /// the whole pot without anyone's hand being compared
+    fn is_uncontested(&self) -> bool {
+        self.payouts
+            .iter()
+            .filter(|p| p.status != State::Folding)
+            .count()
+            == 1
+    }
+    fn walk(mut self) -> Vec<Settlement> {
+        let pot = self.payouts.iter().map(|p| p.risked).sum::<Chips>();
+        self.payouts
+            .iter_mut()
+            .find(|p| p.status != State::Folding)
+            .map(|p| p.reward = pot)
+            .expect("one player remaining");
+        self.payouts
+    }
     fn is_complete(&self) -> bool {
         let staked = self.payouts.iter().map(|p| p.risked).sum::<Chips>();
         let reward = self.payouts.iter().map(|p| p.reward).sum::<Chips>();
@@ -113,6 +211,77 @@ mod tests {
         Strength::from((Ranking::Straight(Rank::Ace), Kickers::default()))
     }
 
+    #[test]
+    fn ranking_groups_ties() {
+        let ranking = Showdown::ranking(&[
+            (0, one_pair()),
+            (1, the_nuts()),
+            (2, ace_high()),
+            (3, the_nuts()),
+            (4, one_pair()),
+        ]);
+        assert!(ranking.len() == 3);
+        assert!(ranking[0] == (the_nuts(), vec![1, 3]));
+        assert!(ranking[1] == (one_pair(), vec![0, 4]));
+        assert!(ranking[2] == (ace_high(), vec![2]));
+    }
+
+    #[test]
+    fn ranking_agrees_with_settle() {
+        let hands = [(0, triplets()), (1, two_pair()), (2, triplets())];
+        let settlement = Showdown::from(
+            hands
+                .iter()
+                .map(|(_, s)| Settlement::from((100, State::Betting, *s)))
+                .collect::<Vec<Settlement>>(),
+        )
+        .settle();
+        let (_, winners) = Showdown::ranking(&hands).remove(0);
+        for (seat, payout) in settlement.iter().enumerate() {
+            assert!(winners.contains(&seat) == (payout.reward > 0));
+        }
+    }
+
+    #[test]
+    fn rake_is_capped() {
+        assert!(Showdown::rake(200, 0.05, 3) == 3);
+        assert!(Showdown::rake(40, 0.05, 3) == 2);
+        assert!(Showdown::rake(200, 0., 3) == 0);
+    }
+
+    #[test]
+    fn rake_conserves_chips() {
+        let settlement = Showdown::from(vec![
+            Settlement::from((100, State::Betting, one_pair())),
+            Settlement::from((075, State::Betting, one_pair())),
+            Settlement::from((100, State::Betting, ace_high())),
+        ])
+        .award();
+        let staked = settlement.iter().map(|p| p.risked).sum::<Chips>();
+        let rake = Showdown::rake(staked, 0.05, 10);
+        let raked = Showdown::deduct(settlement.clone(), rake);
+        let reward = raked.iter().map(|p| p.reward).sum::<Chips>();
+        assert!(rake == 10);
+        assert!(reward + rake == staked);
+        assert!(raked
+            .iter()
+            .zip(settlement.iter())
+            .all(|(after, before)| after.reward <= before.reward));
+        assert!(raked[2].reward == 0);
+    }
+
+    #[test]
+    fn rake_comes_from_the_winner() {
+        let ledger = vec![
+            Settlement::from((100, State::Betting, ace_high())),
+            Settlement::from((100, State::Betting, one_pair())),
+        ];
+        let raked = Showdown::from(ledger.clone()).with_rake(0.05, 3).settle();
+        let free = Showdown::from(ledger).settle();
+        assert!(raked[1].reward == 197);
+        assert!(free[1].reward == 200);
+    }
+
     #[test]
     fn heads_up_showdown() {
         let settlement = Showdown::from(vec![
@@ -199,6 +368,32 @@ mod tests {
         assert!(settlement[3].reward == 0);
     }
 
+    #[test]
+    fn three_way_all_in_with_distinct_stacks() {
+        let settlement = Showdown::from(vec![
+            Settlement::from((030, State::Shoving, the_nuts())),
+            Settlement::from((080, State::Shoving, two_pair())),
+            Settlement::from((100, State::Shoving, one_pair())),
+        ])
+        .settle();
+        assert!(settlement[0].reward == 090);
+        assert!(settlement[1].reward == 100);
+        assert!(settlement[2].reward == 020);
+    }
+
+    #[test]
+    fn two_way_tie_splits_odd_pot() {
+        let settlement = Showdown::from(vec![
+            Settlement::from((001, State::Folding, the_nuts())),
+            Settlement::from((010, State::Betting, two_pair())),
+            Settlement::from((010, State::Betting, two_pair())),
+        ])
+        .settle();
+        assert!(settlement[0].reward == 0);
+        assert!(settlement[1].reward == 11);
+        assert!(settlement[2].reward == 10);
+    }
+
     #[test]
     fn singular_all_in_with_side_pot() {
         let settlement = Showdown::from(vec![
@@ -0,0 +1,54 @@
+use crate::cards::street::Street;
+use crate::Chips;
+
+/// how much a player may raise, on top of whatever their stack
+/// allows. no-limit caps a raise at the stack alone. pot-limit
+/// caps it at the pot after the raiser has called, so the most
+/// anyone can put in is the call plus a pot that already counts
+/// that call. fixed-limit makes every raise exactly one bet: the
+/// small bet preflop and on the flop, the big bet on the turn and
+/// river. the usual cap on the number of fixed-limit raises per
+/// street is not modeled, since a Game keeps no betting history.
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum BettingStructure {
+    #[default]
+    NoLimit,
+    PotLimit,
+    FixedLimit {
+        small: Chips,
+        big: Chips,
+    },
+}
+
+impl BettingStructure {
+    /// the size of one fixed-limit bet on this street
+    pub fn bet(&self, street: Street) -> Option<Chips> {
+        match self {
+            Self::FixedLimit { small, .. } if matches!(street, Street::Pref | Street::Flop) => {
+                Some(*small)
+            }
+            Self::FixedLimit { big, .. } => Some(*big),
+            _ => None,
+        }
+    }
+    /// the most chips a raise may add, given what the raiser
+    /// owes to call and the pot before they act. stacks are
+    /// clamped by the caller, so no-limit has no cap here.
+    pub fn cap(&self, street: Street, call: Chips, pot: Chips) -> Chips {
+        match self {
+            Self::NoLimit => Chips::MAX,
+            Self::PotLimit => call + (pot + call),
+            Self::FixedLimit { .. } => call + self.bet(street).expect("fixed limit bet"),
+        }
+    }
+}
+
+impl std::fmt::Display for BettingStructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoLimit => write!(f, "NL"),
+            Self::PotLimit => write!(f, "PL"),
+            Self::FixedLimit { small, big } => write!(f, "FL {}/{}", small, big),
+        }
+    }
+}
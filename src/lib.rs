@@ -33,6 +33,21 @@ const KMEANS_TURN_TRAINING_ITERATIONS: usize = 32; // eyeball test seems to conv
 const KMEANS_FLOP_CLUSTER_COUNT: usize = 128;
 const KMEANS_TURN_CLUSTER_COUNT: usize = 144;
 const KMEANS_EQTY_CLUSTER_COUNT: usize = 101;
+const KMEANS_TURN_POTENTIAL: bool = false; // cluster the turn on (mean, std) of river equity rather than its full distribution
+const KMEANS_NEIGHBOR_SAMPLE: usize = 0; // approximate nearest-centroid search over this many sampled centroids, 0 for exact scan
+const KMEANS_METRIC_REPAIR: bool = false; // shortest-path closure so learned distances obey the triangle inequality
+const KMEANS_METRIC_NEIGHBORS: usize = 0; // keep only this many nearest distances per abstraction, 0 for dense metric
+const KMEANS_METRIC_FAR: Energy = 1.; // distance assumed for pairs dropped from a sparse metric, i.e. the normalized max
+const KMEANS_METRIC_POWER: Energy = 1.; // store emd^p between centroids, > 1 sharpens separation of far clusters, 1 for linear emd
+const KMEANS_HEURISTIC_PARALLEL: usize = usize::MAX; // greedy transport searches targets in parallel once the target has this many abstractions. benched slower at K = 144, so off
+const KMEANS_BATCH_SIZE: usize = 0; // points sampled per kmeans iteration for mini-batch updates, 0 for full batch
+const KMEANS_PROGRESS_BATCH: usize = 4096; // advance progress bars once per this many items in hot parallel loops
+const KMEANS_LOSS_INTERVAL: usize = 1; // log kmeans RMS loss every this many iterations
+
+// analysis server parameters
+const ANALYSIS_VERSION_INTERVAL: Option<std::time::Duration> =
+    Some(std::time::Duration::from_secs(300)); // how often cached metrics check for a retrain, None to never check
+const RUNOUT_CACHE_CAPACITY: usize = 1024; // complete boards whose holding strength tables are kept for equity distributions
 
 // mccfr parameters
 const CFR_BATCH_SIZE: usize = 256;
@@ -40,6 +55,10 @@ const CFR_TREE_COUNT: usize = 1_048_576;
 const CFR_ITERATIONS: usize = CFR_TREE_COUNT / CFR_BATCH_SIZE;
 const CFR_PRUNNING_PHASE: usize = 100_000_000 / CFR_BATCH_SIZE;
 const CFR_DISCOUNT_PHASE: usize = 100_000 / CFR_BATCH_SIZE;
+const CFR_EXACT_NODES: usize = 1 << 16;
+const CFR_EXACT_PLUS: bool = false; // floor regrets at zero and weight the average by iteration in exact CFR
+const CFR_LOG_INTERVAL: u64 = 64; // log the average regret every this many training iterations
+const CFR_CONVERGENCE_WINDOW: usize = 0; // regret deltas kept per bucket for convergence monitoring, 0 to skip the extra memory
 
 // regret matching parameters
 const REGRET_MIN: Utility = -3e5;
@@ -69,6 +88,84 @@ pub trait Save: Sized {
     }
 }
 
+/// crate-wide source of randomness, see Random.
+pub fn rng() -> Random {
+    Random
+}
+
+/// make every subsequent draw from rng() reproducible.
+/// reseeding restarts every thread's stream from the new seed.
+pub fn seed(seed: u64) {
+    SEED.store(seed, std::sync::atomic::Ordering::SeqCst);
+    EPOCH.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// the seed last passed to seed(), or 0 if it was never called.
+/// for consumers that derive streams of their own, like kmeans.
+pub fn seeded() -> u64 {
+    SEED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+static SEED: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+static EPOCH: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+static THREADS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+thread_local! {
+    static THREAD: u64 = THREADS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    static STREAM: std::cell::RefCell<(u64, rand::rngs::SmallRng)> =
+        std::cell::RefCell::new((0, rand::SeedableRng::seed_from_u64(0)));
+}
+
+/// handle to the crate-wide RNG. everything stochastic draws from here:
+/// - dealing cards in Deck::draw, and so Game chance nodes
+/// - random Human actions
+/// - every Arbitrary implementation, e.g. EMD::random
+///
+/// until seed() is called this is just thread_rng. once seeded, each
+/// thread draws from its own SmallRng derived from the seed and the
+/// order in which threads were spawned, so single-threaded runs replay
+/// exactly and rayon-parallel runs replay as far as rayon's scheduling does.
+/// kmeans draws from streams of its own derived from seeded(), and
+/// Profile::rng seeds itself from its inputs, so both stay deterministic
+/// either way.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Random;
+
+impl Random {
+    /// the stream a thread draws from once seeded, spread
+    /// apart from its siblings' by the thread's spawn order
+    fn stream(seed: u64, thread: u64) -> rand::rngs::SmallRng {
+        let seed = seed ^ thread.wrapping_mul(0x9E3779B97F4A7C15);
+        rand::SeedableRng::seed_from_u64(seed)
+    }
+    fn with<T>(&mut self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        match EPOCH.load(std::sync::atomic::Ordering::SeqCst) {
+            0 => f(&mut rand::thread_rng()),
+            epoch => STREAM.with(|stream| {
+                let ref mut stream = *stream.borrow_mut();
+                if stream.0 != epoch {
+                    *stream = (epoch, Self::stream(seeded(), THREAD.with(|t| *t)));
+                }
+                f(&mut stream.1)
+            }),
+        }
+    }
+}
+
+impl rand::RngCore for Random {
+    fn next_u32(&mut self) -> u32 {
+        self.with(|rng| rng.next_u32())
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.with(|rng| rng.next_u64())
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with(|rng| rng.fill_bytes(dest))
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
 /// progress bar
 pub fn progress(n: usize) -> indicatif::ProgressBar {
     let tick = std::time::Duration::from_secs(60);
@@ -80,6 +177,20 @@ pub fn progress(n: usize) -> indicatif::ProgressBar {
     progress
 }
 
+/// advance a progress bar once per KMEANS_PROGRESS_BATCH items
+/// instead of once per item, keeping atomic contention on the bar
+/// out of hot parallel loops. callers settle the remainder with
+/// settle once the loop is done, so the final count stays exact.
+pub fn tick(progress: &indicatif::ProgressBar, i: usize) {
+    if (i + 1) % KMEANS_PROGRESS_BATCH == 0 {
+        progress.inc(KMEANS_PROGRESS_BATCH as u64);
+    }
+}
+/// account for the items a batched tick hasn't reported yet
+pub fn settle(progress: &indicatif::ProgressBar, n: usize) {
+    progress.inc((n % KMEANS_PROGRESS_BATCH) as u64);
+}
+
 /// initialize logging
 pub fn logs() {
     std::fs::create_dir_all("logs").expect("create logs directory");
@@ -105,3 +216,21 @@ pub fn logs() {
     );
     simplelog::CombinedLogger::init(vec![term, file]).expect("initialize logger");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_rng_replays() {
+        use rand::RngCore;
+        let draws = |seed: u64, thread: u64| {
+            let ref mut rng = Random::stream(seed, thread);
+            (0..8).map(|_| rng.next_u64()).collect::<Vec<u64>>()
+        };
+        assert!(draws(0xC0FFEE, 0) == draws(0xC0FFEE, 0));
+        assert!(draws(0xC0FFEE, 1) == draws(0xC0FFEE, 1));
+        assert!(draws(0xC0FFEE, 0) != draws(0xC0FFEE, 1));
+        assert!(draws(0xC0FFEE, 0) != draws(0xDECAF, 0));
+    }
+}
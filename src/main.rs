@@ -54,7 +54,9 @@ async fn main() {
     // The k-means earth mover's distance hand-clustering algorithm.
     crate::clustering::kmeans::Layer::learn();
     // Monte Carlo counter-factual regret minimization. External sampling, alternating regret updates, linear weighting schedules.
-    crate::mccfr::blueprint::Solver::train();
+    if !crate::mccfr::blueprint::Solver::done(crate::cards::street::Street::random()) {
+        crate::mccfr::blueprint::Solver::train(Default::default());
+    }
     // Let's upload the data to the database.
     crate::analysis::upload::Upload::upload().await.unwrap();
     // Let's see what we've learned.
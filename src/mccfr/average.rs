@@ -0,0 +1,177 @@
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::exact::Exact;
+use super::profile::Profile;
+use crate::cards::street::Street;
+use crate::Probability;
+use crate::Save;
+use std::collections::BTreeMap;
+
+/// the trained average strategy, exported for inference.
+///
+/// a Profile keeps cumulative regrets and unnormalized policy
+/// sums, since that's what training needs to resume. once
+/// training is done, all anyone queries is the normalized
+/// distribution over Edges at each Bucket, so that's all we
+/// keep, and all we write to disk.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Average(BTreeMap<Bucket, Vec<(Edge, Probability)>>);
+
+impl Average {
+    pub fn inner(&self) -> &BTreeMap<Bucket, Vec<(Edge, Probability)>> {
+        &self.0
+    }
+    /// action distribution at a Bucket, if we trained one
+    pub fn get(&self, bucket: &Bucket) -> Option<&[(Edge, Probability)]> {
+        self.0.get(bucket).map(Vec::as_slice)
+    }
+    pub fn load_from(path: &str) -> Self {
+        log::info!("{:<32}{:<32}", "loading     average", path);
+        use crate::clustering::abstraction::Abstraction;
+        use crate::mccfr::path::Path;
+        use byteorder::ReadBytesExt;
+        use byteorder::BE;
+        use std::fs::File;
+        use std::io::BufReader;
+        use std::io::Read;
+        use std::io::Seek;
+        use std::io::SeekFrom;
+        let ref file = File::open(path).expect(&format!("open {}", path));
+        let mut strategies = BTreeMap::<Bucket, Vec<(Edge, Probability)>>::new();
+        let mut reader = BufReader::new(file);
+        let mut buffer = [0u8; 2];
+        reader.seek(SeekFrom::Start(19)).expect("seek past header");
+        while reader.read_exact(&mut buffer).is_ok() {
+            if u16::from_be_bytes(buffer) == 5 {
+                reader.read_u32::<BE>().expect("past path length");
+                let past = Path::from(reader.read_u64::<BE>().expect("read past path"));
+                reader.read_u32::<BE>().expect("abstraction length");
+                let abs = Abstraction::from(reader.read_u64::<BE>().expect("read abstraction"));
+                reader.read_u32::<BE>().expect("future path length");
+                let future = Path::from(reader.read_u64::<BE>().expect("read future path"));
+                reader.read_u32::<BE>().expect("edge length");
+                let edge = Edge::from(reader.read_u64::<BE>().expect("read edge"));
+                reader.read_u32::<BE>().expect("policy length");
+                let policy = reader.read_f32::<BE>().expect("read policy");
+                let bucket = Bucket::from((past, abs, future));
+                strategies.entry(bucket).or_default().push((edge, policy));
+                continue;
+            } else {
+                break;
+            }
+        }
+        Self(strategies)
+    }
+    pub fn save_to(&self, path: &str) {
+        log::info!("{:<32}{:<32}", "saving      average", path);
+        use byteorder::WriteBytesExt;
+        use byteorder::BE;
+        use std::fs::File;
+        use std::io::Write;
+        let ref mut file = File::create(path).expect(&format!("touch {}", path));
+        file.write_all(b"PGCOPY\n\xFF\r\n\0").expect("header");
+        file.write_u32::<BE>(0).expect("flags");
+        file.write_u32::<BE>(0).expect("extension");
+        for (bucket, strategy) in self.0.iter() {
+            for (edge, policy) in strategy.iter() {
+                const N_FIELDS: u16 = 5;
+                file.write_u16::<BE>(N_FIELDS).unwrap();
+                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+                file.write_u64::<BE>(u64::from(bucket.0)).unwrap();
+                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+                file.write_u64::<BE>(u64::from(bucket.1)).unwrap();
+                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+                file.write_u64::<BE>(u64::from(bucket.2)).unwrap();
+                file.write_u32::<BE>(size_of::<u64>() as u32).unwrap();
+                file.write_u64::<BE>(u64::from(*edge)).unwrap();
+                file.write_u32::<BE>(size_of::<f32>() as u32).unwrap();
+                file.write_f32::<BE>(*policy).unwrap();
+            }
+        }
+        file.write_u16::<BE>(0xFFFF).expect("trailer");
+    }
+}
+
+impl From<BTreeMap<Bucket, BTreeMap<Edge, Probability>>> for Average {
+    fn from(distributions: BTreeMap<Bucket, BTreeMap<Edge, Probability>>) -> Self {
+        Self(
+            distributions
+                .into_iter()
+                .map(|(bucket, policy)| (bucket, policy.into_iter().collect()))
+                .collect(),
+        )
+    }
+}
+
+impl From<&Profile> for Average {
+    fn from(profile: &Profile) -> Self {
+        Self::from(profile.distributions())
+    }
+}
+
+impl From<&Exact> for Average {
+    fn from(exact: &Exact) -> Self {
+        Self::from(exact.distributions())
+    }
+}
+
+impl From<Average> for BTreeMap<Bucket, Vec<(Edge, Probability)>> {
+    fn from(average: Average) -> Self {
+        average.0
+    }
+}
+
+impl Save for Average {
+    fn name() -> &'static str {
+        "pgcopy.profile.average"
+    }
+    fn make(_: Street) -> Self {
+        unreachable!("must be learned in MCCFR minimization")
+    }
+    fn path(_: Street) -> String {
+        Self::name().to_string()
+    }
+    fn load(street: Street) -> Self {
+        Self::load_from(&Self::path(street))
+    }
+    fn save(&self) {
+        self.save_to(&Self::path(Street::Pref))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::observation::Observation;
+    use crate::clustering::abstraction::Abstraction;
+    use crate::gameplay::game::Game;
+    use crate::mccfr::data::Data;
+
+    /// a river subgame small enough to solve exactly
+    fn trained() -> Exact {
+        let encoder = |game: &Game| Abstraction::from(Observation::from(game).equity());
+        let game = Game::checked_down(Street::Rive);
+        let tree = Exact::expand(Data::from((game, encoder(&game))), encoder);
+        let mut exact = Exact::try_from(tree).unwrap();
+        exact.solve(4).unwrap();
+        exact
+    }
+
+    #[test]
+    fn persistence() {
+        let save = Average::from(&trained());
+        let ref path = std::env::temp_dir()
+            .join(format!("{}.{}", Average::name(), std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        save.save_to(path);
+        let load = Average::load_from(path);
+        std::fs::remove_file(path).unwrap();
+        assert!(!save.inner().is_empty());
+        assert!(save == load);
+        assert!(load.inner().values().all(|policy| {
+            let total = policy.iter().map(|(_, p)| p).sum::<Probability>();
+            (total - 1.).abs() < 1e-5
+        }));
+    }
+}
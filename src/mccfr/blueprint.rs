@@ -1,8 +1,10 @@
+use super::budget::Budget;
 use super::counterfactual::Counterfactual;
+use super::discount::Discount;
 use super::info::Info;
+use super::kind::NodeKind;
 use super::node::Node;
 use super::partition::Partition;
-use super::player::Player;
 use super::policy::Policy;
 use super::profile::Profile;
 use super::sampler::Encoding;
@@ -49,23 +51,28 @@ impl Solver {
     /// for the traverser. regret and policy updates are
     /// encapsulated by Profile, but we are yet to impose
     /// a learning schedule for regret or policy.
-    pub fn train() {
-        if Self::done() {
-            log::info!("skipping regret minimization");
+    ///
+    /// resumes from the saved Profile if there is one, spends
+    /// the Budget, then checkpoints so a later call can extend it.
+    pub fn train(budget: Budget) -> Profile {
+        let mut solver = if Self::done(Street::random()) {
+            log::info!("resuming regret minimization for {}", budget);
+            Self::load(Street::random())
         } else {
-            log::info!("starting regret minimization");
-            Self::make(Street::random()).solve();
-        }
-    }
-    /// check (by filename) if a blueprint solver has been saved to disk.
-    fn done() -> bool {
-        Encoding::done(Street::random()) && Profile::done(Street::random())
+            log::info!("starting regret minimization for {}", budget);
+            Self::make(Street::random())
+        };
+        solver.solve(budget);
+        solver.profile
     }
     /// the main training loop.
-    fn solve(&mut self) {
+    fn solve(&mut self, budget: Budget) {
         log::info!("beginning training loop");
-        let progress = crate::progress(crate::CFR_ITERATIONS);
-        while self.profile.next() <= crate::CFR_ITERATIONS {
+        let start = std::time::Instant::now();
+        let progress = crate::progress(budget.length() as usize);
+        let mut iterations = 0;
+        while !budget.spent(iterations, start) {
+            self.profile.next();
             for counterfactual in self.updates() {
                 let ref regret = counterfactual.regret();
                 let ref policy = counterfactual.policy();
@@ -73,11 +80,17 @@ impl Solver {
                 self.profile.add_regret(bucket, regret);
                 self.profile.add_policy(bucket, policy);
             }
-            progress.inc(1);
+            iterations += 1;
+            progress.set_position(budget.position(iterations, start));
             let count = self.profile.size();
             let epoch = self.profile.epochs();
             log::debug!("epochs {:<10} buckets {:<10}", epoch, count);
+            if iterations % crate::CFR_LOG_INTERVAL == 0 {
+                let regret = self.profile.average_regret();
+                log::info!("epochs {:<10} average regret {:<10.4}", epoch, regret);
+            }
         }
+        progress.finish();
         self.save();
     }
 
@@ -105,7 +118,7 @@ impl Solver {
     /// Build the Tree iteratively starting from the root node.
     /// This function uses a stack to simulate recursion and builds the tree in a depth-first manner.
     fn sample(&mut self) -> Tree {
-        let mut tree = Tree::empty(self.profile.walker());
+        let mut tree = self.sampler.tree(self.profile.walker());
         let ref root = tree.insert(self.sampler.root());
         let mut todo = self.explore(root);
         while let Some(branch) = todo.pop() {
@@ -121,26 +134,27 @@ impl Solver {
     /// continuing Edge Actions.
     /// fn explore(&mut self, tree: &mut Tree,node: &Node) -> Vec<Branch> {
     fn explore(&mut self, node: &Node) -> Vec<Branch> {
-        let branches = self.sampler.branches(node);
         let walker = self.profile.walker();
-        let chance = Player::chance();
-        let player = node.player();
-        match (branches.len(), player) {
-            (0, _) => {
+        match node.kind() {
+            NodeKind::Terminal => {
                 vec![] //
             }
-            (_, p) if p == chance => {
-                self.profile.explore_any(branches, node) //
-            }
-            (_, p) if p == walker => {
+            NodeKind::Chance => self
+                .profile
+                .explore_any(node)
+                .into_iter()
+                .map(|(e, g)| self.sampler.branch(node, e, g))
+                .collect(),
+            NodeKind::Decision(p) if p == walker => {
+                let branches = self.sampler.branches(node);
                 self.profile.witness(node, &branches);
                 self.profile.explore_all(branches, node)
             }
-            (_, p) if p != walker => {
+            NodeKind::Decision(_) => {
+                let branches = self.sampler.branches(node);
                 self.profile.witness(node, &branches);
                 self.profile.explore_one(branches, node)
             }
-            _ => panic!("kyle walker"),
         }
     }
 }
@@ -150,9 +164,19 @@ impl Save for Solver {
         unreachable!()
     }
     fn make(street: Street) -> Self {
+        let sampler = Encoding::load(street);
+        if let Err(missing) = sampler.verify() {
+            panic!(
+                "encoder is missing {} isomorphisms, starting with {:?}",
+                missing.len(),
+                missing.first()
+            );
+        }
         Self {
-            profile: Profile::default(),
-            sampler: Encoding::load(street),
+            profile: Profile::default()
+                .with_convergence(crate::CFR_CONVERGENCE_WINDOW)
+                .with_discount(*Discount::default()),
+            sampler,
         }
     }
     fn save(&self) {
@@ -164,7 +188,9 @@ impl Save for Solver {
     fn load(street: Street) -> Self {
         Self {
             sampler: Encoding::load(street),
-            profile: Profile::load(street),
+            profile: Profile::load(street)
+                .with_convergence(crate::CFR_CONVERGENCE_WINDOW)
+                .with_discount(*Discount::default()),
         }
     }
 }
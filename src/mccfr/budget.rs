@@ -0,0 +1,69 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// how much regret minimization to run in one call to
+/// Solver::train. either a count of training iterations
+/// or a wall-clock allowance. both are measured from the
+/// start of the call, so training resumed from a checkpoint
+/// extends the prior run rather than replaying it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Iterations(u64),
+    Duration(Duration),
+}
+
+impl Budget {
+    /// has this budget been exhausted
+    pub fn spent(&self, iterations: u64, start: Instant) -> bool {
+        self.position(iterations, start) >= self.length()
+    }
+    /// total length, in iterations or seconds, for progress bars
+    pub fn length(&self) -> u64 {
+        match self {
+            Self::Iterations(n) => *n,
+            Self::Duration(d) => d.as_secs(),
+        }
+    }
+    /// how far along we are, in the same units as length
+    pub fn position(&self, iterations: u64, start: Instant) -> u64 {
+        match self {
+            Self::Iterations(_) => iterations,
+            Self::Duration(_) => start.elapsed().as_secs(),
+        }
+    }
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::Iterations(crate::CFR_ITERATIONS as u64)
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Iterations(n) => write!(f, "{} iterations", n),
+            Self::Duration(d) => write!(f, "{:?}", d),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterations_spent() {
+        let start = Instant::now();
+        let budget = Budget::Iterations(10);
+        assert!(!budget.spent(9, start));
+        assert!(budget.spent(10, start));
+    }
+
+    #[test]
+    fn duration_spent() {
+        let start = Instant::now();
+        assert!(!Budget::Duration(Duration::from_secs(60)).spent(1_000_000, start));
+        assert!(Budget::Duration(Duration::ZERO).spent(0, start));
+    }
+}
@@ -0,0 +1,70 @@
+use super::bucket::Bucket;
+use crate::Utility;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+
+/// rolling window of recent regret deltas at each Bucket.
+/// a Bucket whose updates have settled shows low variance
+/// here, while one that is still learning keeps swinging.
+#[derive(Debug, Default)]
+pub struct Convergence {
+    window: usize,
+    deltas: BTreeMap<Bucket, VecDeque<Utility>>,
+}
+
+impl Convergence {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0);
+        Self {
+            window,
+            deltas: BTreeMap::new(),
+        }
+    }
+    /// push the latest delta, evicting the oldest once full
+    pub fn record(&mut self, bucket: &Bucket, delta: Utility) {
+        let deltas = self
+            .deltas
+            .entry(*bucket)
+            .or_insert_with(|| VecDeque::with_capacity(self.window));
+        if deltas.len() == self.window {
+            deltas.pop_front();
+        }
+        deltas.push_back(delta);
+    }
+    /// variance of the windowed deltas at each Bucket
+    pub fn variances(&self) -> Vec<(Bucket, f32)> {
+        self.deltas
+            .iter()
+            .map(|(bucket, deltas)| (*bucket, Self::variance(deltas)))
+            .collect()
+    }
+    fn variance(deltas: &VecDeque<Utility>) -> f32 {
+        let n = deltas.len() as f32;
+        let mean = deltas.iter().sum::<Utility>() / n;
+        deltas
+            .iter()
+            .map(|d| (d - mean) * (d - mean))
+            .sum::<Utility>()
+            / n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Arbitrary;
+
+    #[test]
+    fn window_forgets_early_swings() {
+        let ref bucket = Bucket::random();
+        let mut convergence = Convergence::new(4);
+        for delta in [100., -100., 100., -100.] {
+            convergence.record(bucket, delta);
+        }
+        assert!(convergence.variances()[0].1 > 1.);
+        for _ in 0..4 {
+            convergence.record(bucket, 1.);
+        }
+        assert!(convergence.variances()[0].1 == 0.);
+    }
+}
@@ -11,6 +11,13 @@ pub struct Data {
     /// because it requires global information as a
     /// rank-1 hypergraph quantity
     partition: Option<Bucket>,
+    /// whether every child has been attached, which only
+    /// Tree::expand knows. sampled Trees never set this.
+    expanded: bool,
+    /// raises already made on this street. Buckets are assigned
+    /// before a Node is linked to its parent, so the Tree counts
+    /// these for us rather than reading them off our history.
+    raises: usize,
 }
 
 impl From<(Game, Abstraction)> for Data {
@@ -19,6 +26,8 @@ impl From<(Game, Abstraction)> for Data {
             game,
             info,
             partition: None,
+            expanded: false,
+            raises: 0,
         }
     }
 }
@@ -44,6 +53,21 @@ impl Data {
             Some(_) => panic!("don't overwrite bucket"),
         }
     }
+    /// for Data reached after some raises on its street,
+    /// e.g. a Spot partway through a betting round
+    pub fn with_raises(mut self, raises: usize) -> Self {
+        self.raises = raises;
+        self
+    }
+    pub fn raises(&self) -> usize {
+        self.raises
+    }
+    pub fn expanded(&self) -> bool {
+        self.expanded
+    }
+    pub fn expand(&mut self) {
+        self.expanded = true;
+    }
     pub fn abstraction(&self) -> &Abstraction {
         &self.info
     }
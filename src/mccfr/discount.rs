@@ -1,10 +1,15 @@
 use crate::Utility;
 
-#[derive(Debug)]
+/// discounted CFR weighting. after iteration t, positive
+/// cumulative regrets are scaled by t^α / (t^α + 1), negative
+/// ones by t^β / (t^β + 1), and the strategy sum by
+/// (t / (t + 1))^γ. an infinite exponent never discounts, so
+/// vanilla() is plain CFR, and (1.5, 0, 2) is the usual DCFR.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Discount {
     period: usize, // interval between strategy updates.
     alpha: f32,    // α parameter. controls recency bias.
-    omega: f32,    // ω parameter. controls recency bias.
+    beta: f32,     // β parameter. controls recency bias.
     gamma: f32,    // γ parameter. controls recency bias.
 }
 
@@ -13,10 +18,19 @@ impl Discount {
         &Self {
             period: 1,
             alpha: 1.5,
-            omega: 0.5,
+            beta: 0.5,
             gamma: 2.0,
         }
     }
+    /// no discounting at all
+    pub const fn vanilla() -> Self {
+        Self {
+            period: 1,
+            alpha: f32::INFINITY,
+            beta: f32::INFINITY,
+            gamma: 0.,
+        }
+    }
 
     pub fn policy(&self, t: usize) -> f32 {
         (t as f32 / (t as f32 + 1.)).powf(self.gamma)
@@ -26,13 +40,32 @@ impl Discount {
         if t % self.period != 0 {
             1.
         } else if regret > 0. {
-            let x = (t as f32 / self.period as f32).powf(self.alpha);
-            x / (x + 1.)
+            self.scale(t, self.alpha)
         } else if regret < 0. {
-            let x = (t as f32 / self.period as f32).powf(self.omega);
-            x / (x + 1.)
+            self.scale(t, self.beta)
         } else {
             1.
         }
     }
+
+    fn scale(&self, t: usize, exponent: f32) -> Utility {
+        if exponent == f32::INFINITY {
+            1.
+        } else {
+            let x = (t as f32 / self.period as f32).powf(exponent);
+            x / (x + 1.)
+        }
+    }
+}
+
+/// (α, β, γ), updating every iteration
+impl From<(f32, f32, f32)> for Discount {
+    fn from((alpha, beta, gamma): (f32, f32, f32)) -> Self {
+        Self {
+            period: 1,
+            alpha,
+            beta,
+            gamma,
+        }
+    }
 }
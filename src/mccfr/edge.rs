@@ -1,7 +1,9 @@
 use crate::gameplay::action::Action;
+use crate::gameplay::game::Game;
 use crate::mccfr::odds::Odds;
 use crate::Arbitrary;
 use crate::Chips;
+use crate::Utility;
 use std::hash::Hash;
 
 #[derive(Debug, Clone, Copy, Hash, Ord, PartialOrd, PartialEq, Eq)]
@@ -30,6 +32,45 @@ impl Edge {
     pub fn is_choice(&self) -> bool {
         !self.is_chance()
     }
+    /// resolve into a concrete Action using Game state to
+    /// determine free parameters (stack size, pot size, etc).
+    /// raises are snapped into the legal range of bet sizes,
+    /// so a large enough Raise resolves to a Shove, or under a
+    /// limit, to the largest raise the BettingStructure allows.
+    pub fn actionize(&self, game: &Game) -> Action {
+        match self {
+            Edge::Check => Action::Check,
+            Edge::Fold => Action::Fold,
+            Edge::Draw => Action::Draw(game.draw()),
+            Edge::Call => Action::Call(game.to_call()),
+            Edge::Shove => Action::Shove(game.to_shove()),
+            Edge::Raise(o) => {
+                let min = game.to_raise();
+                let max = game.to_max_raise();
+                let bet = (game.pot() as Utility * Utility::from(*o)) as Chips;
+                match bet.max(min).min(max) {
+                    bet if bet >= game.to_shove() => Action::Shove(game.to_shove()),
+                    bet => Action::Raise(bet),
+                }
+            }
+        }
+    }
+    /// human-readable rendering with chip sizes resolved
+    /// against the Game, e.g. "raise 150". Display stays
+    /// context-free for when there is no Game at hand.
+    pub fn format_in_context(&self, game: &Game) -> String {
+        match self {
+            Edge::Draw => String::from("draw"),
+            edge => match edge.actionize(game) {
+                Action::Check => String::from("check"),
+                Action::Fold => String::from("fold"),
+                Action::Call(chips) => format!("call {}", chips),
+                Action::Raise(chips) => format!("raise {}", chips),
+                Action::Shove(chips) => format!("shove {}", chips),
+                Action::Draw(_) | Action::Blind(_) => unreachable!(),
+            },
+        }
+    }
 }
 
 impl From<Action> for Edge {
@@ -132,6 +173,16 @@ impl std::fmt::Display for Edge {
 mod bijection_tests {
     use super::*;
 
+    #[test]
+    fn format_in_context() {
+        let game = Game::root();
+        assert!(Edge::Fold.format_in_context(&game) == "fold");
+        assert!(Edge::Call.format_in_context(&game) == format!("call {}", game.to_call()));
+        assert!(Edge::Shove.format_in_context(&game) == format!("shove {}", game.to_shove()));
+        assert!(Edge::Raise(Odds(1, 1)).format_in_context(&game) == "raise 3");
+        assert!(Edge::Raise(Odds(1, 1)).to_string().contains("R1^1"));
+    }
+
     #[test]
     fn bijective_usize() {
         let raise = Odds::GRID.map(Edge::Raise);
@@ -156,7 +207,7 @@ mod bijection_tests {
 impl Arbitrary for Edge {
     fn random() -> Self {
         use rand::Rng;
-        match rand::thread_rng().gen_range(0..6) {
+        match crate::rng().gen_range(0..6) {
             0 => Self::Draw,
             1 => Self::Fold,
             2 => Self::Check,
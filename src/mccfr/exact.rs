@@ -0,0 +1,649 @@
+use super::bucket::Bucket;
+use super::data::Data;
+use super::discount::Discount;
+use super::edge::Edge;
+use super::node::Node;
+use super::player::Player;
+use super::sizing::BetAbstraction;
+use super::strategy::Strategy;
+use super::tree::Branch;
+use super::tree::Tree;
+use crate::clustering::abstraction::Abstraction;
+use crate::gameplay::game::Game;
+use crate::gameplay::ply::Ply;
+use crate::Probability;
+use crate::Utility;
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeMap;
+
+type Encoder = Box<dyn Fn(&Game) -> Abstraction>;
+
+/// vanilla two-player zero-sum CFR over a fully expanded Tree.
+/// where the Solver samples Trees and estimates counterfactual
+/// values, here we walk every Node on every iteration and compute
+/// them exactly. this is only tractable for small subgames, so we
+/// refuse Trees above a configurable node count.
+///
+/// chance Nodes are averaged uniformly over whichever
+/// children the Tree holds for them, and that 1/n is part
+/// of the counterfactual reach of everything below.
+///
+/// in lazy mode we start from a lone root and only generate
+/// a Node's children when the walk first reaches it.
+///
+/// with_plus switches to CFR+: cumulative regrets are floored
+/// at zero after every update, and iteration t adds to the
+/// average strategy with weight t, so early noise washes out.
+/// with_discount scales what has accumulated before each
+/// update instead, as in DCFR. both default to vanilla CFR.
+pub struct Exact {
+    tree: Tree,
+    profile: BTreeMap<Bucket, Strategy>,
+    epochs: usize,
+    limit: usize,
+    plus: bool,
+    discount: Discount,
+    encoder: Option<Encoder>,
+}
+
+impl Exact {
+    /// expand every Branch reachable from the root Data,
+    /// using the provided encoder to abstract each Game.
+    pub fn expand<F>(root: Data, encoder: F) -> Tree
+    where
+        F: Fn(&Game) -> Abstraction,
+    {
+        Self::expand_with(root, encoder, BetAbstraction::default())
+    }
+    /// expand, branching only on the given raise sizes
+    pub fn expand_with<F>(root: Data, encoder: F, sizes: BetAbstraction) -> Tree
+    where
+        F: Fn(&Game) -> Abstraction,
+    {
+        let mut tree = Tree::empty(Player(Ply::Choice(0))).with_sizes(sizes);
+        let mut todo = vec![tree.insert(root).index()];
+        while let Some(index) = todo.pop() {
+            let branches = Self::branches(&tree.at(index), &encoder);
+            let children = tree.expand(index, branches);
+            todo.extend(children.iter().map(Node::index));
+        }
+        tree
+    }
+    fn branches<F>(node: &Node, encoder: &F) -> Vec<Branch>
+    where
+        F: Fn(&Game) -> Abstraction,
+    {
+        node.branches()
+            .into_iter()
+            .map(|(e, g)| (e, Data::from((g, encoder(&g)))))
+            .map(|(e, d)| Branch(d, e, node.index()))
+            .collect()
+    }
+
+    /// start from a lone root and expand during the walk.
+    pub fn lazy<F>(root: Data, encoder: F, limit: usize) -> Self
+    where
+        F: Fn(&Game) -> Abstraction + 'static,
+    {
+        Self::lazy_with(root, encoder, limit, BetAbstraction::default())
+    }
+    /// lazy, branching only on the given raise sizes
+    pub fn lazy_with<F>(root: Data, encoder: F, limit: usize, sizes: BetAbstraction) -> Self
+    where
+        F: Fn(&Game) -> Abstraction + 'static,
+    {
+        let mut tree = Tree::empty(Player(Ply::Choice(0))).with_sizes(sizes);
+        tree.insert(root);
+        Self {
+            tree,
+            profile: BTreeMap::default(),
+            epochs: 0,
+            limit,
+            plus: crate::CFR_EXACT_PLUS,
+            discount: Discount::vanilla(),
+            encoder: Some(Box::new(encoder)),
+        }
+    }
+
+    /// override whether updates follow CFR+ or vanilla CFR
+    pub fn with_plus(mut self, plus: bool) -> Self {
+        self.plus = plus;
+        self
+    }
+    /// discount regrets and strategy sums between iterations
+    pub fn with_discount(mut self, discount: Discount) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+    pub fn epochs(&self) -> usize {
+        self.epochs
+    }
+
+    /// run a fixed number of full-tree iterations,
+    /// alternating the traverser within each one. only
+    /// fails in lazy mode, once the Tree outgrows its limit.
+    pub fn solve(&mut self, iterations: usize) -> Result<(), String> {
+        for _ in 0..iterations {
+            for i in 0..crate::N {
+                let ref walker = Player(Ply::Choice(i));
+                let ref mut deltas = BTreeMap::default();
+                self.walk(NodeIndex::new(0), walker, (1., 1.), deltas)?;
+                self.apply(deltas);
+            }
+            self.epochs += 1;
+        }
+        Ok(())
+    }
+
+    /// regret-matched strategy for the current iteration.
+    pub fn current(&self, bucket: &Bucket, edge: &Edge) -> Probability {
+        let strategy = self.profile.get(bucket).expect("bucket in profile");
+        let regret = |r: Utility| r.max(0.);
+        let denom = strategy
+            .values()
+            .map(|m| regret(m.regret()))
+            .sum::<Utility>();
+        let numer = strategy
+            .get(edge)
+            .map(|m| regret(m.regret()))
+            .expect("edge in bucket");
+        if denom > 0. {
+            numer / denom
+        } else {
+            1. / strategy.keys().count() as Probability
+        }
+    }
+    /// time-averaged strategy, which is what converges to equilibrium.
+    pub fn average(&self, bucket: &Bucket, edge: &Edge) -> Probability {
+        let strategy = self.profile.get(bucket).expect("bucket in profile");
+        let denom = strategy.values().map(|m| m.policy()).sum::<Probability>();
+        if denom > 0. {
+            strategy.weight(edge)
+        } else {
+            1. / strategy.keys().count() as Probability
+        }
+    }
+    /// average strategy at every Bucket, normalized over its Edges
+    pub fn distributions(&self) -> BTreeMap<Bucket, BTreeMap<Edge, Probability>> {
+        self.profile
+            .iter()
+            .map(|(bucket, strategy)| {
+                let policy = strategy
+                    .keys()
+                    .map(|edge| (*edge, self.average(bucket, edge)))
+                    .collect::<BTreeMap<Edge, Probability>>();
+                (*bucket, policy)
+            })
+            .collect()
+    }
+    /// expected payoff to a player when both play the average strategy.
+    pub fn value(&self, player: &Player) -> Utility {
+        self.evaluate(&self.root(), player)
+    }
+    /// how much a best response gains over the average strategy,
+    /// averaged over both seats. zero exactly at equilibrium, and
+    /// unlike regret() it compares vanilla and CFR+ fairly.
+    pub fn exploitability(&self) -> Utility {
+        (0..crate::N)
+            .map(|i| Player(Ply::Choice(i)))
+            .map(|ref player| self.best_response(player) - self.value(player))
+            .sum::<Utility>()
+            / crate::N as Utility
+    }
+    /// largest positive cumulative regret, averaged over iterations.
+    /// CFR bounds this by O(1/sqrt(T)), so it should shrink as we train.
+    pub fn regret(&self) -> Utility {
+        self.profile
+            .values()
+            .flat_map(|strategy| strategy.values())
+            .map(|memory| memory.regret().max(0.))
+            .fold(0., Utility::max)
+            / self.epochs.max(1) as Utility
+    }
+
+    fn root(&self) -> Node<'_> {
+        self.tree.at(NodeIndex::new(0))
+    }
+    /// generate children on first visit in lazy mode.
+    /// eager Trees arrive fully expanded, so this is a no-op.
+    fn visit(&mut self, index: NodeIndex) -> Result<(), String> {
+        if let Some(ref encoder) = self.encoder {
+            let ref node = self.tree.at(index);
+            if !node.data().expanded() {
+                let branches = Self::branches(node, encoder);
+                self.tree.expand(index, branches);
+                self.witness(index);
+                return Self::gate(&self.tree, self.limit);
+            }
+        }
+        Ok(())
+    }
+    /// refuse Trees above the node limit
+    fn gate(tree: &Tree, limit: usize) -> Result<(), String> {
+        match tree.graph().node_count() {
+            n if n > limit => Err(format!("tree of {} nodes exceeds limit {}", n, limit)),
+            _ => Ok(()),
+        }
+    }
+    /// register the decision point of a Node in the profile.
+    fn witness(&mut self, index: NodeIndex) {
+        let ref node = self.tree.at(index);
+        if node.children().is_empty() || node.player() == Player::chance() {
+            return;
+        }
+        let strategy = self.profile.entry(*node.bucket()).or_default();
+        for edge in node.outgoing() {
+            strategy.entry(*edge).or_default();
+        }
+    }
+    fn walk(
+        &mut self,
+        index: NodeIndex,
+        walker: &Player,
+        reach: (Probability, Probability),
+        deltas: &mut BTreeMap<Bucket, Strategy>,
+    ) -> Result<Utility, String> {
+        self.visit(index)?;
+        let ref node = self.tree.at(index);
+        let player = node.player();
+        let bucket = *node.bucket();
+        let children = node
+            .children()
+            .iter()
+            .map(|child| (child.index(), *child.incoming().expect("child has edge")))
+            .collect::<Vec<(NodeIndex, Edge)>>();
+        if children.is_empty() {
+            return Ok(node.payoff(walker));
+        }
+        if player == Player::chance() {
+            let n = children.len() as Utility;
+            let reach = (reach.0, reach.1 / n);
+            return Ok(children
+                .into_iter()
+                .map(|(child, _)| self.walk(child, walker, reach, deltas))
+                .sum::<Result<Utility, String>>()?
+                / n);
+        }
+        let values = children
+            .into_iter()
+            .map(|(child, edge)| (child, edge, self.current(&bucket, &edge)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(child, edge, p)| match player == *walker {
+                true => (child, edge, p, (reach.0 * p, reach.1)),
+                false => (child, edge, p, (reach.0, reach.1 * p)),
+            })
+            .map(|(child, edge, p, r)| Ok((edge, p, self.walk(child, walker, r, deltas)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let expected = values.iter().map(|(_, p, v)| p * v).sum::<Utility>();
+        if player == *walker {
+            let strategy = deltas.entry(bucket).or_default();
+            for (edge, p, v) in values {
+                let memory = strategy.entry(edge).or_default();
+                memory.add_regret(1., reach.1 * (v - expected));
+                memory.add_policy(1., reach.0 * p);
+            }
+        }
+        Ok(expected)
+    }
+    fn evaluate(&self, node: &Node, player: &Player) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            return node.payoff(player);
+        }
+        if node.player() == Player::chance() {
+            return children
+                .iter()
+                .map(|child| self.evaluate(child, player))
+                .sum::<Utility>()
+                / children.len() as Utility;
+        }
+        children
+            .iter()
+            .map(|child| (child, child.incoming().expect("child has edge")))
+            .map(|(child, edge)| self.average(node.bucket(), edge) * self.evaluate(child, player))
+            .sum::<Utility>()
+    }
+    fn apply(&mut self, deltas: &BTreeMap<Bucket, Strategy>) {
+        let weight = match self.plus {
+            true => (self.epochs + 1) as Probability,
+            false => 1.,
+        };
+        for (bucket, delta) in deltas {
+            let strategy = self.profile.get_mut(bucket).expect("bucket in profile");
+            for (edge, memory) in delta.iter() {
+                let m = strategy.get_mut(edge).expect("edge in bucket");
+                let discount = self.discount.regret(self.epochs, m.regret());
+                m.add_regret(discount, memory.regret());
+                let discount = self.discount.policy(self.epochs);
+                m.add_policy(discount, memory.policy() * weight);
+                if self.plus {
+                    m.set_regret(m.regret().max(0.));
+                }
+            }
+        }
+    }
+    /// value to player of a best response against the opponent's
+    /// average strategy. a Bucket's best action depends on what we
+    /// do further down, so we settle the deepest Buckets first,
+    /// weighing each of their Nodes by how likely chance and the
+    /// opponent are to reach it. abstraction can put one Bucket
+    /// both above and below another, so a Bucket that isn't
+    /// settled yet plays its average strategy in the meantime.
+    fn best_response(&self, player: &Player) -> Utility {
+        let mut reach = BTreeMap::new();
+        self.reach(&self.root(), player, 1., &mut reach);
+        let mut buckets = BTreeMap::<Bucket, (usize, Vec<Node>)>::new();
+        for node in self.tree.all() {
+            if node.player() == *player && !node.children().is_empty() {
+                let (depth, nodes) = buckets.entry(*node.bucket()).or_default();
+                *depth = (*depth).max(node.history().len());
+                nodes.push(node);
+            }
+        }
+        let mut order = buckets.into_iter().collect::<Vec<_>>();
+        order.sort_by_key(|(_, (depth, _))| std::cmp::Reverse(*depth));
+        let mut choice = BTreeMap::new();
+        for (bucket, (_, nodes)) in order {
+            let strategy = self.profile.get(&bucket).expect("bucket in profile");
+            let best = strategy
+                .keys()
+                .map(|edge| {
+                    let value = nodes
+                        .iter()
+                        .map(|node| {
+                            let child = node.follow(edge).expect("edge in node");
+                            reach[&node.index()] * self.respond(&child, player, &choice)
+                        })
+                        .sum::<Utility>();
+                    (*edge, value)
+                })
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .map(|(edge, _)| edge)
+                .expect("bucket has edges");
+            choice.insert(bucket, best);
+        }
+        self.respond(&self.root(), player, &choice)
+    }
+    /// probability that chance and the opponent lead to each Node
+    fn reach(
+        &self,
+        node: &Node,
+        player: &Player,
+        p: Probability,
+        reach: &mut BTreeMap<NodeIndex, Probability>,
+    ) {
+        reach.insert(node.index(), p);
+        let children = node.children();
+        for child in children.iter() {
+            let q = match node.player() {
+                x if x == Player::chance() => p / children.len() as Probability,
+                x if x == *player => p,
+                _ => p * self.average(node.bucket(), child.incoming().expect("child has edge")),
+            };
+            self.reach(child, player, q, reach);
+        }
+    }
+    /// value to player when they follow choice where it's
+    /// settled and everyone plays the average strategy elsewhere
+    fn respond(&self, node: &Node, player: &Player, choice: &BTreeMap<Bucket, Edge>) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            return node.payoff(player);
+        }
+        if node.player() == Player::chance() {
+            return children
+                .iter()
+                .map(|child| self.respond(child, player, choice))
+                .sum::<Utility>()
+                / children.len() as Utility;
+        }
+        if let (true, Some(edge)) = (node.player() == *player, choice.get(node.bucket())) {
+            let child = node.follow(edge).expect("edge in node");
+            return self.respond(&child, player, choice);
+        }
+        children
+            .iter()
+            .map(|child| (child, child.incoming().expect("child has edge")))
+            .map(|(child, edge)| (child, self.average(node.bucket(), edge)))
+            .map(|(child, p)| p * self.respond(child, player, choice))
+            .sum::<Utility>()
+    }
+}
+
+impl TryFrom<(Tree, usize)> for Exact {
+    type Error = String;
+    fn try_from((tree, limit): (Tree, usize)) -> Result<Self, Self::Error> {
+        Self::gate(&tree, limit)?;
+        let mut exact = Self {
+            tree,
+            profile: BTreeMap::default(),
+            epochs: 0,
+            limit,
+            plus: crate::CFR_EXACT_PLUS,
+            discount: Discount::vanilla(),
+            encoder: None,
+        };
+        for index in exact.tree.graph().node_indices() {
+            exact.witness(index);
+        }
+        Ok(exact)
+    }
+}
+
+impl TryFrom<Tree> for Exact {
+    type Error = String;
+    fn try_from(tree: Tree) -> Result<Self, Self::Error> {
+        Self::try_from((tree, crate::CFR_EXACT_NODES))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::observation::Observation;
+    use crate::cards::street::Street;
+    use crate::mccfr::info::Info;
+    use crate::mccfr::kind::NodeKind;
+    use crate::mccfr::odds::Odds;
+    use crate::mccfr::partition::Partition;
+    use crate::mccfr::profile::Profile;
+
+    fn encoder(game: &Game) -> Abstraction {
+        Abstraction::from(Observation::from(game).equity())
+    }
+    fn tree() -> Tree {
+        let root = Game::checked_down(Street::Rive);
+        Exact::expand(Data::from((root, encoder(&root))), encoder)
+    }
+
+    #[test]
+    fn gated() {
+        assert!(Exact::try_from((tree(), 1)).is_err());
+    }
+
+    #[test]
+    fn lazy_gated() {
+        let root = Game::checked_down(Street::Rive);
+        let mut lazy = Exact::lazy(Data::from((root, encoder(&root))), encoder, 2);
+        assert!(lazy.solve(1).is_err());
+    }
+
+    #[test]
+    fn eager_trees_are_expanded() {
+        assert!(tree().frontier().is_empty());
+    }
+
+    #[test]
+    fn zero_sum() {
+        let mut exact = Exact::try_from(tree()).unwrap();
+        exact.solve(8).unwrap();
+        let hero = exact.value(&Player(Ply::Choice(0)));
+        let hate = exact.value(&Player(Ply::Choice(1)));
+        assert!((hero + hate).abs() < 1e-3);
+    }
+
+    #[test]
+    fn converges() {
+        let mut exact = Exact::try_from(tree()).unwrap();
+        exact.solve(8).unwrap();
+        let early = exact.regret();
+        exact.solve(120).unwrap();
+        let later = exact.regret();
+        assert!(later < early);
+    }
+
+    /// doubling iteration counts until exploitability drops below
+    /// half the pot, or None if that takes more than 128. both
+    /// variants solve the same river, since deals vary per call.
+    fn iterations(variant: impl Fn(Exact) -> Exact) -> (Option<usize>, Option<usize>) {
+        let root = Game::checked_down(Street::Rive);
+        let threshold = root.pot() as Utility / 2.;
+        let solve = |mut exact: Exact| {
+            [2, 4, 8, 16, 32, 64, 128].into_iter().find(|&t| {
+                exact.solve(t - exact.epochs()).unwrap();
+                exact.exploitability() < threshold
+            })
+        };
+        let tree = || Exact::expand(Data::from((root, encoder(&root))), encoder);
+        let vanilla = solve(Exact::try_from(tree()).unwrap());
+        let variant = solve(variant(Exact::try_from(tree()).unwrap()));
+        (vanilla, variant)
+    }
+
+    #[test]
+    fn plus_converges_faster() {
+        let (vanilla, plus) = iterations(|exact| exact.with_plus(true));
+        let vanilla = vanilla.expect("cfr converges within 128 iterations");
+        let plus = plus.expect("cfr+ converges within 128 iterations");
+        assert!(plus < vanilla, "cfr+ {} vs cfr {}", plus, vanilla);
+    }
+
+    #[test]
+    fn discount_converges_as_fast() {
+        let dcfr = Discount::from((1.5, 0., 2.));
+        let (vanilla, dcfr) = iterations(|exact| exact.with_discount(dcfr));
+        let vanilla = vanilla.expect("cfr converges within 128 iterations");
+        let dcfr = dcfr.expect("dcfr converges within 128 iterations");
+        assert!(dcfr <= vanilla, "dcfr {} vs cfr {}", dcfr, vanilla);
+    }
+
+    /// external sampling MCCFR through Profile, one Tree per
+    /// epoch, branching on the same sizes as the exact Tree
+    fn sampled<F>(root: Game, encoder: F, sizes: BetAbstraction, epochs: usize) -> Profile
+    where
+        F: Fn(&Game) -> Abstraction,
+    {
+        let mut profile = Profile::default();
+        for _ in 0..epochs {
+            profile.next();
+            let walker = profile.walker();
+            let mut tree = Tree::empty(walker).with_sizes(sizes.clone());
+            let root = Data::from((root, encoder(&root)));
+            let mut todo = vec![tree.insert(root).index()];
+            while let Some(index) = todo.pop() {
+                let ref node = tree.at(index);
+                let branches = match node.kind() {
+                    NodeKind::Terminal => vec![],
+                    NodeKind::Chance => profile
+                        .explore_any(node)
+                        .into_iter()
+                        .map(|(e, g)| Branch(Data::from((g, encoder(&g))), e, index))
+                        .collect(),
+                    NodeKind::Decision(p) => {
+                        let branches = Exact::branches(node, &encoder);
+                        profile.witness(node, &branches);
+                        match p == walker {
+                            true => profile.explore_all(branches, node),
+                            false => profile.explore_one(branches, node),
+                        }
+                    }
+                };
+                for branch in branches {
+                    todo.push(tree.attach(branch).index());
+                }
+            }
+            for info in Vec::<Info>::from(Partition::from(tree)) {
+                let counterfactual = profile.counterfactual(info);
+                let ref bucket = counterfactual.info().node().bucket().clone();
+                profile.add_regret(bucket, counterfactual.regret());
+                profile.add_policy(bucket, counterfactual.policy());
+            }
+        }
+        profile
+    }
+
+    /// both solve the same river, and in a zero-sum game every
+    /// strategy is worth within twice its exploitability of the
+    /// equilibrium value, so the two values must be that close
+    #[test]
+    fn agrees_with_mccfr() {
+        let ref cache = std::cell::RefCell::new(BTreeMap::new());
+        let encoder = |game: &Game| {
+            let obs = Observation::from(game);
+            *cache
+                .borrow_mut()
+                .entry(obs)
+                .or_insert_with(|| Abstraction::from(obs.equity()))
+        };
+        let sizes = BetAbstraction::uniform(&[Odds(1, 1)])
+            .with_limit(0)
+            .with_shove(false);
+        let game = Game::checked_down(Street::Rive);
+        let root = || Data::from((game, encoder(&game)));
+        let tree = Exact::expand_with(root(), encoder, sizes.clone());
+        let mut exact = Exact::try_from(tree).unwrap();
+        exact.solve(256).unwrap();
+        let profile = sampled(game, encoder, sizes.clone(), 4096);
+        let tree = Exact::expand_with(root(), encoder, sizes);
+        let mut mccfr = Exact::try_from(tree).unwrap();
+        for (bucket, policy) in profile.distributions() {
+            let strategy = mccfr.profile.get_mut(&bucket).expect("bucket in tree");
+            for (edge, p) in policy {
+                strategy
+                    .get_mut(&edge)
+                    .expect("edge in bucket")
+                    .set_policy(p);
+            }
+        }
+        let ref hero = Player(Ply::Choice(0));
+        let slack = 2. * (exact.exploitability() + mccfr.exploitability());
+        let gap = (exact.value(hero) - mccfr.value(hero)).abs();
+        assert!(mccfr.exploitability() < game.pot() as Utility);
+        assert!(gap <= slack, "values differ by {} beyond {}", gap, slack);
+    }
+
+    #[test]
+    fn lazy_matches_eager() {
+        let root = Game::checked_down(Street::Rive);
+        let eager = Exact::expand(Data::from((root, encoder(&root))), encoder);
+        let mut eager = Exact::try_from(eager).unwrap();
+        let mut lazy = Exact::lazy(Data::from((root, encoder(&root))), encoder, usize::MAX);
+        assert!(lazy.tree().all().len() == 1);
+        eager.solve(4).unwrap();
+        lazy.solve(4).unwrap();
+        assert!(lazy.tree().all().len() == eager.tree().all().len());
+        assert!(lazy.profile.keys().eq(eager.profile.keys()));
+        assert!((lazy.regret() - eager.regret()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn lazy_keeps_sizes() {
+        let root = Game::checked_down(Street::Rive);
+        let sizes = BetAbstraction::uniform(&[Odds(1, 1)]).with_limit(0);
+        let eager = Exact::expand_with(Data::from((root, encoder(&root))), encoder, sizes.clone());
+        let mut lazy = Exact::lazy_with(
+            Data::from((root, encoder(&root))),
+            encoder,
+            usize::MAX,
+            sizes,
+        );
+        lazy.solve(1).unwrap();
+        assert!(lazy.tree().all().len() == eager.all().len());
+        assert!(lazy.tree().sizes() == eager.sizes());
+    }
+}
@@ -0,0 +1,21 @@
+use super::player::Player;
+
+/// what sort of backup a Node calls for. decision nodes
+/// belong to a Player and get regret-matched, chance nodes
+/// average over the deal, and terminal nodes settle the pot.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum NodeKind {
+    Decision(Player),
+    Chance,
+    Terminal,
+}
+
+impl std::fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decision(player) => write!(f, "{}", player),
+            Self::Chance => write!(f, "??"),
+            Self::Terminal => write!(f, "END"),
+        }
+    }
+}
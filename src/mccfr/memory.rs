@@ -40,7 +40,7 @@ impl From<(f32, f32)> for Memory {
 impl Arbitrary for Memory {
     fn random() -> Self {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng();
         Self {
             regret: rng.gen(),
             policy: rng.gen(),
@@ -1,10 +1,15 @@
+pub mod average;
 pub mod blueprint;
 pub mod bucket;
+pub mod budget;
+pub mod convergence;
 pub mod counterfactual;
 pub mod data;
 pub mod discount;
 pub mod edge;
+pub mod exact;
 pub mod info;
+pub mod kind;
 pub mod memory;
 pub mod node;
 pub mod odds;
@@ -16,6 +21,8 @@ pub mod policy;
 pub mod profile;
 pub mod regret;
 pub mod sampler;
+pub mod server;
+pub mod sizing;
 pub mod spot;
 pub mod strategy;
 pub mod tree;
@@ -1,14 +1,15 @@
 use super::bucket::Bucket;
+use super::kind::NodeKind;
 use super::odds::Odds;
 use super::path::Path;
 use super::player::Player;
-use crate::cards::street::Street;
+use super::sizing::BetAbstraction;
 use crate::gameplay::action::Action;
 use crate::gameplay::game::Game;
 use crate::gameplay::ply::Ply;
 use crate::mccfr::data::Data;
 use crate::mccfr::edge::Edge;
-use crate::Chips;
+use crate::Probability;
 use crate::Utility;
 use petgraph::graph::DiGraph;
 use petgraph::graph::NodeIndex;
@@ -23,17 +24,24 @@ use petgraph::Direction::Outgoing;
 pub struct Node<'tree> {
     index: NodeIndex,
     graph: &'tree DiGraph<Data, Edge>,
+    sizes: &'tree BetAbstraction,
 }
 
-impl<'tree> From<(NodeIndex, &'tree DiGraph<Data, Edge>)> for Node<'tree> {
-    fn from((index, graph): (NodeIndex, &'tree DiGraph<Data, Edge>)) -> Self {
-        Self { index, graph }
+impl<'tree> From<(NodeIndex, &'tree DiGraph<Data, Edge>, &'tree BetAbstraction)> for Node<'tree> {
+    fn from(
+        (index, graph, sizes): (NodeIndex, &'tree DiGraph<Data, Edge>, &'tree BetAbstraction),
+    ) -> Self {
+        Self {
+            index,
+            graph,
+            sizes,
+        }
     }
 }
 
 impl<'tree> Node<'tree> {
     pub fn spawn(&self, index: NodeIndex) -> Node<'tree> {
-        Self::from((index, self.graph()))
+        Self::from((index, self.graph(), self.sizes))
     }
     pub fn data(&self) -> &Data {
         &self
@@ -50,6 +58,19 @@ impl<'tree> Node<'tree> {
     pub fn player(&self) -> Player {
         self.data().player()
     }
+    pub fn kind(&self) -> NodeKind {
+        match self.player() {
+            Player(Ply::Terminal) => NodeKind::Terminal,
+            Player(Ply::Chance) => NodeKind::Chance,
+            player => NodeKind::Decision(player),
+        }
+    }
+    pub fn is_terminal(&self) -> bool {
+        self.kind() == NodeKind::Terminal
+    }
+    pub fn is_chance(&self) -> bool {
+        self.kind() == NodeKind::Chance
+    }
     pub fn payoff(&self, player: &Player) -> Utility {
         match player {
             Player(Ply::Terminal) => unreachable!(),
@@ -63,6 +84,14 @@ impl<'tree> Node<'tree> {
                 .expect("player index in bounds"),
         }
     }
+    /// equity above which continuing is +EV against the bet faced here
+    pub fn continue_threshold(&self) -> Probability {
+        self.data().game().continue_threshold()
+    }
+    /// how often we must continue here so that bluffs don't profit
+    pub fn minimum_defense(&self) -> Probability {
+        self.data().game().minimum_defense()
+    }
 
     // Navigational methods
 
@@ -127,14 +156,26 @@ impl<'tree> Node<'tree> {
 
     /// determine the set of branches that could be taken from this node
     /// this determines what Bucket we end up in since Tree::attach()
-    /// uses this to assign Buckets to Data upon insertion
+    /// uses this to assign Buckets to Data upon insertion.
+    /// chance nodes enumerate every possible deal under Edge::Draw
     pub fn branches(&self) -> Vec<(Edge, Game)> {
+        if self.is_chance() {
+            return self.outcomes();
+        }
         self.stale_continuations()
             .into_iter()
             .map(|e| (e, self.actionization(&e)))
             .map(|(e, a)| (e.clone(), self.data().game().apply(a)))
             .collect()
     }
+    /// every card set the dealer could reveal from here
+    fn outcomes(&self) -> Vec<(Edge, Game)> {
+        let game = self.data().game();
+        game.draws()
+            .into_iter()
+            .map(|hand| (Edge::Draw, game.apply(Action::Draw(hand))))
+            .collect()
+    }
     /// what if we got the node continuations FROM the node data path bucket ?
     ///
     fn stale_continuations(&self) -> Vec<Edge> {
@@ -160,24 +201,7 @@ impl<'tree> Node<'tree> {
     /// within range of legal bet sizes, so sometimes Raise(5:1) yields
     /// an identical Game node as Raise(1:1) or Shove.
     fn actionization(&self, edge: &Edge) -> Action {
-        let game = self.data().game();
-        match &edge {
-            Edge::Check => Action::Check,
-            Edge::Fold => Action::Fold,
-            Edge::Draw => Action::Draw(game.draw()),
-            Edge::Call => Action::Call(game.to_call()),
-            Edge::Shove => Action::Shove(game.to_shove()),
-            Edge::Raise(o) => {
-                let min = game.to_raise();
-                let max = game.to_shove();
-                let bet = (game.pot() as Utility * Utility::from(*o)) as Chips;
-                match bet {
-                    bet if bet >= max => Action::Shove(max),
-                    bet if bet <= min => Action::Raise(min),
-                    _ => Action::Raise(bet),
-                }
-            }
-        }
+        edge.actionize(self.data().game())
     }
     /// generalization of mapping a concrete Action into a set of abstract Vec<Edge>
     /// this is mostly useful for enumerating a set of desired Raises
@@ -185,31 +209,19 @@ impl<'tree> Node<'tree> {
     /// the contract is that the Actions returned by Game are legal,
     /// but the Raise amount can take any value >= the minimum provided by Game.
     fn edgifications(&self, action: Action) -> Vec<Edge> {
-        if let Action::Raise(_) = action {
-            self.raises().into_iter().map(Edge::from).collect()
-        } else {
-            vec![Edge::from(action)]
+        match action {
+            Action::Raise(_) => self.raises().into_iter().map(Edge::from).collect(),
+            Action::Shove(_) if !self.sizes.shove() => vec![],
+            _ => vec![Edge::from(action)],
         }
     }
-    /// returns a set of possible raises given the current history
-    /// we truncate in a few cases:
-    /// - prevent N-betting explosion of raises
-    /// - allow for finer-grained exploration in early streets
-    /// - on the last street, restrict raise amounts so smaller grid
+    /// returns the set of possible raises given the current history,
+    /// as the Tree's BetAbstraction allows for this street and the
+    /// number of raises already made on it
     fn raises(&self) -> Vec<Odds> {
-        let n = self.subgame().iter().filter(|e| e.is_raise()).count();
-        if n > crate::N_RAISE {
-            vec![]
-        } else {
-            match self.data().game().board().street() {
-                Street::Pref => Odds::PREF_RAISES.to_vec(),
-                Street::Flop => Odds::FLOP_RAISES.to_vec(),
-                _ => match n {
-                    0 => Odds::LATE_RAISES.to_vec(),
-                    _ => Odds::LAST_RAISES.to_vec(),
-                },
-            }
-        }
+        let n = self.data().raises();
+        let street = self.data().game().board().street();
+        self.sizes.raises(street, n)
     }
     /// returns the subgame history of the current node
     /// within the same Street of action.
@@ -250,3 +262,45 @@ impl std::fmt::Display for Node<'_> {
         write!(f, "N{}", self.index().index())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::deck::Deck;
+    use crate::cards::street::Street;
+    use crate::clustering::abstraction::Abstraction;
+    use crate::mccfr::tree::Tree;
+
+    /// check and call until the dealer is due to reveal the turn
+    fn turn() -> Game {
+        let mut game = Game::checked_down(Street::Flop);
+        while game.player() != Ply::Chance {
+            game = game.apply(game.passive());
+        }
+        game
+    }
+    fn kind(game: Game) -> NodeKind {
+        let mut tree = Tree::empty(Player(Ply::Choice(0)));
+        tree.insert(Data::from((game, Abstraction::from(0.5))))
+            .kind()
+    }
+
+    #[test]
+    fn classification() {
+        let root = Game::root();
+        let fold = root.apply(Action::Fold);
+        assert!(kind(root) == NodeKind::Decision(Player(root.player())));
+        assert!(kind(turn()) == NodeKind::Chance);
+        assert!(kind(fold) == NodeKind::Terminal);
+    }
+
+    #[test]
+    fn chance_enumerates_deals() {
+        let mut tree = Tree::empty(Player(Ply::Choice(0)));
+        let node = tree.insert(Data::from((turn(), Abstraction::from(0.5))));
+        let branches = node.branches();
+        assert!(branches.len() == Deck::new().size() - 2 * crate::N - 3);
+        assert!(branches.iter().all(|(e, _)| e.is_chance()));
+        assert!(branches.iter().all(|(_, g)| g.street() == Street::Turn));
+    }
+}
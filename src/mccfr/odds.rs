@@ -74,7 +74,7 @@ impl Arbitrary for Odds {
     fn random() -> Self {
         use rand::seq::SliceRandom;
         Self::GRID
-            .choose(&mut rand::thread_rng())
+            .choose(&mut crate::rng())
             .copied()
             .expect("GRID is empty")
     }
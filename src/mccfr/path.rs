@@ -7,7 +7,7 @@ pub struct Path(u64);
 impl Arbitrary for Path {
     fn random() -> Self {
         use rand::Rng;
-        Self::from(rand::thread_rng().gen::<u64>())
+        Self::from(crate::rng().gen::<u64>())
     }
 }
 
@@ -21,7 +21,7 @@ impl From<BTreeMap<Edge, Probability>> for Policy {
 impl Arbitrary for Policy {
     fn random() -> Self {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng();
         let n = rng.gen_range(1..=8);
         Self::from(
             (0..n)
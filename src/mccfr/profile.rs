@@ -1,12 +1,18 @@
+use super::convergence::Convergence;
 use super::counterfactual::Counterfactual;
 use super::discount::Discount;
 use super::memory::Memory;
 use super::phase::Phase;
 use super::policy::Policy;
 use super::regret::Regret;
+use super::sampler::Encoding;
+use super::spot::Spot;
 use super::strategy::Strategy;
 use super::tree::Branch;
 use crate::cards::street::Street;
+use crate::clustering::store::Store;
+use crate::gameplay::action::Action;
+use crate::gameplay::game::Game;
 use crate::gameplay::ply::Ply;
 use crate::mccfr::bucket::Bucket;
 use crate::mccfr::edge::Edge;
@@ -18,7 +24,6 @@ use crate::Probability;
 use crate::Save;
 use crate::Utility;
 use rand::rngs::SmallRng;
-use rand::Rng;
 use rand::SeedableRng;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::BTreeMap;
@@ -34,10 +39,23 @@ use std::usize;
 /// - Minimizer: handles policy and regret updates by implementing some regret-minimzation subroutine
 /// - Profile: stores policy & regret values. used by reference for a lot of calculations,
 /// such as Reach, Utility, MinimizerRegretVector, MinimizerPolicyVector, SampleTree, etc.
-#[derive(Default)]
 pub struct Profile {
     iterations: usize,
     strategies: BTreeMap<Bucket, Strategy>,
+    convergence: Option<Convergence>,
+    discount: Discount,
+}
+
+/// nothing visited yet, accumulating as vanilla CFR
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            iterations: 0,
+            strategies: BTreeMap::default(),
+            convergence: None,
+            discount: Discount::vanilla(),
+        }
+    }
 }
 
 impl Profile {
@@ -45,6 +63,30 @@ impl Profile {
     pub fn size(&self) -> usize {
         self.strategies.len()
     }
+    /// opt in to keeping the last `window` regret deltas at
+    /// each Bucket for Profile::convergence. zero keeps none.
+    pub fn with_convergence(mut self, window: usize) -> Self {
+        self.convergence = match window {
+            0 => None,
+            n => Some(Convergence::new(n)),
+        };
+        self
+    }
+    /// discount regrets and strategy sums between epochs.
+    /// regrets are only discounted during Phase::Discount.
+    pub fn with_discount(mut self, discount: Discount) -> Self {
+        self.discount = discount;
+        self
+    }
+    /// variance of recent regret deltas at each Bucket.
+    /// low values have converged, high values are still
+    /// learning. empty unless tracking was opted into.
+    pub fn convergence(&self) -> Vec<(Bucket, f32)> {
+        self.convergence
+            .as_ref()
+            .map(Convergence::variances)
+            .unwrap_or_default()
+    }
     /// increment Epoch counter
     /// and return current count
     pub fn next(&mut self) -> usize {
@@ -144,7 +186,7 @@ impl Profile {
         log::trace!("update regret @ {}", bucket);
         let t = self.epochs();
         let phase = self.phase();
-        let discount = Discount::default();
+        let discount = self.discount;
         let strategy = self
             .strategies
             .get_mut(bucket)
@@ -159,12 +201,16 @@ impl Profile {
             decision.add_regret(discount, regret);
             log::trace!("{} : {}", action, decision.regret());
         }
+        if let Some(convergence) = self.convergence.as_mut() {
+            let delta = regrets.inner().values().map(|r| r.abs()).sum();
+            convergence.record(bucket, delta);
+        }
     }
     /// update policy vector for a given Bucket
     pub fn add_policy(&mut self, bucket: &Bucket, policys: &Policy) {
         log::trace!("update policy @ {}", bucket);
         let t = self.epochs();
-        let discount = Discount::default();
+        let discount = self.discount;
         let strategy = self
             .strategies
             .get_mut(bucket)
@@ -190,6 +236,18 @@ impl Profile {
     pub fn epochs(&self) -> usize {
         self.iterations
     }
+    /// largest accumulated regret at each Bucket, summed and
+    /// averaged over Epochs. it should fall as training goes on,
+    /// so it's a cheap convergence signal, but the regrets are
+    /// sampled and discounted so it is not exploitability. use
+    /// a best response traversal (see Exact) for that.
+    pub fn average_regret(&self) -> Utility {
+        self.strategies
+            .values()
+            .map(|strategy| strategy.values().map(|m| m.regret()).fold(0., Utility::max))
+            .sum::<Utility>()
+            / self.epochs().max(1) as Utility
+    }
     /// derive current phase from Epoch count
     pub fn phase(&self) -> Phase {
         Phase::from(self.epochs())
@@ -209,6 +267,29 @@ impl Profile {
             .expect("bucket must exist")
             .policy()
     }
+    /// resolve a live Spot, already translated onto abstract
+    /// actions, to its Bucket and serve the trained action
+    /// distribution there. an untrained Bucket is an error
+    /// rather than a silent uniform default.
+    pub fn lookup<S: Store>(
+        &self,
+        encoding: &Encoding<S>,
+        spot: &Spot,
+    ) -> Result<BTreeMap<Edge, Probability>, String> {
+        let ref bucket = encoding.bucket(spot);
+        self.strategies
+            .get(bucket)
+            .map(Strategy::distribution)
+            .ok_or_else(|| format!("no trained strategy for bucket {}", bucket))
+    }
+    /// normalized action distribution at every Bucket,
+    /// leaving regrets and training state behind
+    pub fn distributions(&self) -> BTreeMap<Bucket, BTreeMap<Edge, Probability>> {
+        self.strategies
+            .iter()
+            .map(|(bucket, strategy)| (bucket.clone(), strategy.distribution()))
+            .collect()
+    }
     /// absolute Probability. only used for Tree sampling in Monte Carlo Trainer.
     pub fn weight(&self, bucket: &Bucket, edge: &Edge) -> Probability {
         self.strategies
@@ -232,15 +313,14 @@ impl Profile {
             .inspect(|Branch(_, edge, _)| assert!(edge.is_choice()))
             .collect()
     }
-    /// uniform sampling of chance Edge
-    pub fn explore_any(&self, choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
-        let n = choices.len();
-        let mut choices = choices;
-        let ref mut rng = self.rng(head);
-        let choice = rng.gen_range(0..n);
-        let chosen = choices.remove(choice);
-        assert!(chosen.1.is_chance());
-        vec![chosen]
+    /// uniform sampling of chance Edge. we deal once from the
+    /// crate rng rather than enumerating every deal, and unlike
+    /// the choice samplers we don't seed by epoch and Bucket, so
+    /// that each Tree in a batch sees its own cards.
+    pub fn explore_any(&self, head: &Node) -> Vec<(Edge, Game)> {
+        assert!(head.player() == Player::chance());
+        let game = head.data().game();
+        vec![(Edge::Draw, game.apply(Action::Draw(game.draw())))]
     }
     /// Profile-weighted sampling of opponent Edge
     pub fn explore_one(&self, choices: Vec<Branch>, head: &Node) -> Vec<Branch> {
@@ -366,6 +446,68 @@ impl Profile {
         reward * probability / conditional
     }
 
+    /// equity realization
+    /// equity realization
+    /// equity realization
+
+    /// value of this Node to a player, backed up tabularly over
+    /// whatever subtree was sampled beneath it. assumes every
+    /// player follows the trained Profile: choice children are
+    /// weighted by policy, renormalized over the Edges that were
+    /// actually sampled, and sampled chance children are averaged.
+    pub fn value(&self, node: &Node, player: &Player) -> Utility {
+        let children = node.children();
+        if children.is_empty() {
+            return node.payoff(player);
+        }
+        let weights = children
+            .iter()
+            .map(|child| child.incoming().expect("child has incoming edge"))
+            .map(|edge| match node.player() {
+                p if p == Player::chance() => 1.,
+                _ => self.weight(node.bucket(), edge),
+            })
+            .collect::<Vec<Probability>>();
+        let total = weights.iter().sum::<Probability>();
+        if total == 0. {
+            // no policy mass on any sampled Edge, so weigh them evenly
+            return children
+                .iter()
+                .map(|child| self.value(child, player))
+                .sum::<Utility>()
+                / children.len() as Utility;
+        }
+        children
+            .iter()
+            .zip(weights.iter())
+            .map(|(child, weight)| self.value(child, player) * weight)
+            .sum::<Utility>()
+            / total
+    }
+    /// how much of its raw all-in equity a hand converts into
+    /// pot share when both players follow the trained Profile
+    /// from this Node onward:
+    ///
+    /// ```text
+    /// (EV + spent) / (equity * pot)
+    /// ```
+    ///
+    /// where EV is the net value backed up over the sampled
+    /// subtree and spent is what the player has already put
+    /// into the pot. equity is the hand's all-in equity, e.g.
+    /// from API::abs_equity for its preflop bucket. 1.0 means
+    /// exactly realizing equity; in position we expect around
+    /// 1.0 or above, out of position somewhat below.
+    pub fn realization(&self, root: &Node, player: &Player, equity: Probability) -> Utility {
+        let Player(Ply::Choice(position)) = player else {
+            unreachable!("only choice players realize equity")
+        };
+        let game = root.data().game();
+        let spent = game.spent(*position) as Utility;
+        let pot = game.pot() as Utility;
+        (self.value(root, player) + spent) / (equity * pot)
+    }
+
     /// reach calculations
     /// reach calculations
     /// reach calculations
@@ -498,6 +640,8 @@ impl Save for Profile {
         Self {
             iterations,
             strategies,
+            convergence: None,
+            discount: Discount::vanilla(),
         }
     }
     fn save(&self) {
@@ -544,6 +688,8 @@ impl Arbitrary for Profile {
             strategies: (0..100)
                 .map(|_| (Bucket::random(), Strategy::random()))
                 .collect(),
+            convergence: None,
+            discount: Discount::vanilla(),
         }
     }
 }
@@ -583,6 +729,123 @@ mod tests {
     use crate::Arbitrary;
     use crate::Save;
 
+    #[test]
+    fn realization_splits_pot() {
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::action::Action;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        use crate::mccfr::tree::Tree;
+        let game = Game::root().apply(Action::Fold);
+        let mut tree = Tree::empty(Player(Ply::Choice(0)));
+        let ref leaf = tree.insert(Data::from((game, Abstraction::random())));
+        let profile = Profile::default();
+        let players = (0..crate::N).map(|i| Player(Ply::Choice(i)));
+        assert!(players
+            .clone()
+            .all(|ref p| profile.value(leaf, p) == leaf.payoff(p)));
+        let total = players
+            .map(|ref p| profile.realization(leaf, p, 1.))
+            .sum::<Utility>();
+        assert!((total - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn realization_in_position_at_showdown() {
+        use crate::cards::hand::Hand;
+        use crate::cards::strength::Strength;
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::action::Action;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        use crate::mccfr::tree::Branch;
+        use crate::mccfr::tree::Tree;
+        use petgraph::graph::NodeIndex;
+        // a river where the player in position doesn't lose
+        let (game, position) = loop {
+            let game = Game::checked_down(Street::Rive);
+            let oop = game.actor().cards();
+            let ip = game.apply(Action::Check);
+            let board = Hand::from(game.board());
+            let oop = Strength::from(Hand::add(Hand::from(oop), board));
+            let ips = Strength::from(Hand::add(Hand::from(ip.actor().cards()), board));
+            if ips >= oop {
+                break (game, (ip.player(), if ips > oop { 1. } else { 0.5 }));
+            }
+        };
+        let (ip, equity) = position;
+        // check it down, witnessing every decision along the way
+        let mut profile = Profile::default();
+        let mut tree = Tree::empty(Player(Ply::Choice(0)));
+        let mut head = tree
+            .insert(Data::from((game, Abstraction::from(0.5))))
+            .index();
+        while !tree.at(head).is_terminal() {
+            let node = tree.at(head);
+            let branches = node
+                .branches()
+                .into_iter()
+                .map(|(e, g)| Branch(Data::from((g, Abstraction::from(0.5))), e, head))
+                .collect::<Vec<Branch>>();
+            profile.witness(&node, &branches);
+            let check = branches
+                .into_iter()
+                .find(|b| *b.edge() == Edge::Check)
+                .expect("check is legal");
+            head = tree.attach(check).index();
+        }
+        let ref root = tree.at(NodeIndex::new(0));
+        let realization = profile.realization(root, &Player(ip), equity);
+        assert!((realization - 1.).abs() < 1e-3, "{}", realization);
+    }
+
+    #[test]
+    fn chance_deals_vary_within_an_epoch() {
+        use crate::cards::hand::Hand;
+        use crate::clustering::abstraction::Abstraction;
+        use crate::gameplay::game::Game;
+        use crate::mccfr::data::Data;
+        use crate::mccfr::tree::Tree;
+        let mut game = Game::checked_down(Street::Flop);
+        while game.player() != Ply::Chance {
+            game = game.apply(game.passive());
+        }
+        let mut tree = Tree::empty(Player(Ply::Choice(0)));
+        let ref node = tree.insert(Data::from((game, Abstraction::from(0.5))));
+        let profile = Profile::default();
+        let boards = (0..16)
+            .map(|_| profile.explore_any(node))
+            .inspect(|deals| assert!(deals.len() == 1))
+            .map(|deals| deals[0].1.board())
+            .inspect(|board| assert!(board.street() == Street::Turn))
+            .map(|board| u64::from(Hand::from(board)))
+            .collect::<std::collections::BTreeSet<u64>>();
+        assert!(boards.len() > 1);
+    }
+
+    #[test]
+    fn vanilla_unless_discounted() {
+        let accumulate = |mut profile: Profile| {
+            let bucket = profile.strategies.keys().next().cloned().unwrap();
+            let before = profile.strategies[&bucket].clone();
+            let ones = before.keys().map(|e| (*e, 1.)).collect::<BTreeMap<_, _>>();
+            for _ in 0..2 {
+                profile.next();
+                profile.add_regret(&bucket, &Regret::from(ones.clone()));
+                profile.add_policy(&bucket, &Policy::from(ones.clone()));
+            }
+            let ref after = profile.strategies[&bucket];
+            before.iter().all(|(edge, memory)| {
+                let regret = after.get(edge).unwrap().regret() - memory.regret();
+                let policy = after.get(edge).unwrap().policy() - memory.policy();
+                (regret - 2.).abs() < 1e-4 && (policy - 2.).abs() < 1e-4
+            })
+        };
+        let dcfr = Discount::from((1.5, 0., 2.));
+        assert!(accumulate(Profile::random()));
+        assert!(!accumulate(Profile::random().with_discount(dcfr)));
+    }
+
     #[test]
     #[ignore]
     /// we don't run this test because we don't want to overwrite
@@ -1,23 +1,51 @@
 use super::bucket::Bucket;
 use super::data::Data;
+use super::edge::Edge;
 use super::node::Node;
+use super::player::Player;
+use super::sizing::BetAbstraction;
 use super::spot::Spot;
 use super::tree::Branch;
 use super::tree::Tree;
 use crate::cards::isomorphism::Isomorphism;
+use crate::cards::isomorphisms::IsomorphismIterator;
 use crate::cards::observation::Observation;
 use crate::cards::street::Street;
 use crate::clustering::abstraction::Abstraction;
 use crate::clustering::lookup::Lookup;
+use crate::clustering::store::Store;
 use crate::gameplay::game::Game;
 use crate::Arbitrary;
 use crate::Save;
 use std::collections::BTreeMap;
 
+/// maps Games to Abstractions through whichever Store holds
+/// the encoder. training loads everything into memory, while
+/// analysis tooling can read straight from files or Postgres.
+/// the BetAbstraction is the one every Tree we sample or
+/// replay branches on, so Buckets agree at training and query.
 #[derive(Default)]
-pub struct Encoding(BTreeMap<Isomorphism, Abstraction>);
+pub struct Encoding<S = BTreeMap<Isomorphism, Abstraction>>(S, BetAbstraction);
 
-impl Encoding {
+impl<S: Store> From<S> for Encoding<S> {
+    fn from(store: S) -> Self {
+        Self(store, BetAbstraction::default())
+    }
+}
+
+impl<S: Store> Encoding<S> {
+    /// branch on these raise sizes instead
+    pub fn with_sizes(mut self, sizes: BetAbstraction) -> Self {
+        self.1 = sizes;
+        self
+    }
+    pub fn sizes(&self) -> &BetAbstraction {
+        &self.1
+    }
+    /// an empty Tree that branches on our raise sizes
+    pub fn tree(&self, walker: Player) -> Tree {
+        Tree::empty(walker).with_sizes(self.1.clone())
+    }
     pub fn root(&self) -> Data {
         let game = Game::root();
         let info = self.abstraction(&game);
@@ -25,15 +53,29 @@ impl Encoding {
     }
     pub fn abstraction(&self, game: &Game) -> Abstraction {
         self.0
-            .get(&Isomorphism::from(Observation::from(game)))
-            .cloned()
+            .abstraction(&Isomorphism::from(Observation::from(game)))
             .expect(&format!("precomputed abstraction missing for {game}"))
     }
     pub fn replay(&self, _: &Spot) -> Tree {
         todo!()
     }
-    pub fn bucket(&self, _: &Spot) -> Bucket {
-        todo!();
+    /// the Bucket a Tree would assign to this Spot's Game.
+    /// Trees localize a node on insertion, before it is linked
+    /// to its parent, so the Bucket depends only on the Game and
+    /// the raises made on its street, and a single node Tree
+    /// reproduces it without a replay.
+    pub fn bucket(&self, spot: &Spot) -> Bucket {
+        let game = spot.game();
+        let data = Data::from((game, self.abstraction(&game))).with_raises(spot.raises());
+        *self.tree(Player::default()).insert(data).bucket()
+    }
+    /// the Edges a Tree would branch on from this Spot's Game
+    pub fn edges(&self, spot: &Spot) -> Vec<Edge> {
+        let game = spot.game();
+        let data = Data::from((game, self.abstraction(&game))).with_raises(spot.raises());
+        let mut tree = self.tree(Player::default());
+        let node = tree.insert(data);
+        node.branches().into_iter().map(|(edge, _)| edge).collect()
     }
 
     /// unfiltered set of possible children of a Node,
@@ -47,12 +89,52 @@ impl Encoding {
     pub fn branches(&self, node: &Node) -> Vec<Branch> {
         node.branches()
             .into_iter()
-            .map(|(e, g)| (e, g, self.abstraction(&g)))
-            .map(|(e, g, x)| (e, Data::from((g, x))))
-            .map(|(e, d)| (e, d, node.index()))
-            .map(|(e, d, n)| Branch(d, e, n))
+            .map(|(e, g)| self.branch(node, e, g))
             .collect()
     }
+    /// abstract a single (Edge, Game) continuation of a Node.
+    /// chance nodes sample before abstracting, since enumerating
+    /// every deal would cost a lookup per board.
+    pub fn branch(&self, node: &Node, edge: Edge, game: Game) -> Branch {
+        let abstraction = self.abstraction(&game);
+        let data = Data::from((game, abstraction));
+        Branch(data, edge, node.index())
+    }
+}
+
+impl Encoding {
+    /// confirm every isomorphism on every street has an abstraction,
+    /// so that a miss fails here rather than mid-training. keys are
+    /// distinct canonical isomorphisms, so a full count per street
+    /// proves it complete; only short streets get enumerated.
+    pub fn verify(&self) -> Result<(), Vec<Isomorphism>> {
+        let counts = self.0.keys().fold([0usize; 4], |mut counts, iso| {
+            counts[iso.0.street() as usize] += 1;
+            counts
+        });
+        let missing = Street::all()
+            .iter()
+            .copied()
+            .filter(|street| counts[*street as usize] != street.n_isomorphisms())
+            .flat_map(|street| self.missing(street))
+            .collect::<Vec<Isomorphism>>();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+    /// parallel scan of a street for isomorphisms with no abstraction
+    fn missing(&self, street: Street) -> Vec<Isomorphism> {
+        use rayon::iter::ParallelBridge;
+        use rayon::iter::ParallelIterator;
+        let mut missing = IsomorphismIterator::from(street)
+            .par_bridge()
+            .filter(|iso| !self.0.contains_key(iso))
+            .collect::<Vec<Isomorphism>>();
+        missing.sort();
+        missing
+    }
 }
 
 impl Save for Encoding {
@@ -83,6 +165,7 @@ impl Save for Encoding {
                     map
                 })
                 .into(),
+            BetAbstraction::default(),
         )
     }
 }
@@ -98,6 +181,57 @@ impl Arbitrary for Encoding {
                 .take(S)
                 .collect::<BTreeMap<_, _>>()
                 .into(),
+            BetAbstraction::default(),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mccfr::odds::Odds;
+
+    #[test]
+    fn bucket_matches_tree() {
+        for _ in 0..64 {
+            let spot = Spot::random();
+            let game = spot.game();
+            let iso = Isomorphism::from(Observation::from(&game));
+            let abs = Abstraction::from((game.street(), 0));
+            let encoding = Encoding::from(BTreeMap::from([(iso, abs)]));
+            let mut tree = Tree::default();
+            let root = Data::from((Game::root(), abs)).with_raises(spot.raises());
+            let root = tree.insert(root).index();
+            let data = Data::from((game, encoding.abstraction(&game)));
+            let leaf = tree.attach(Branch(data, Edge::Check, root));
+            assert!(encoding.bucket(&spot) == *leaf.bucket());
+        }
+    }
+
+    #[test]
+    fn edges_follow_sizes() {
+        let sizes = BetAbstraction::uniform(&[Odds(1, 1)]);
+        let spot = Spot::from(Game::root());
+        let game = spot.game();
+        let iso = Isomorphism::from(Observation::from(&game));
+        let abs = Abstraction::from((game.street(), 0));
+        let coarse = Encoding::from(BTreeMap::from([(iso, abs)])).with_sizes(sizes.clone());
+        let fine = Encoding::from(BTreeMap::from([(iso, abs)]));
+        let mut tree = Tree::default().with_sizes(sizes);
+        let node = tree.insert(Data::from((game, abs)));
+        let edges = node.branches().into_iter().map(|(e, _)| e);
+        assert!(coarse.edges(&spot) == edges.collect::<Vec<Edge>>());
+        assert!(coarse.edges(&spot).len() < fine.edges(&spot).len());
+        assert!(coarse.bucket(&spot) == *node.bucket());
+        assert!(coarse.bucket(&spot) != fine.bucket(&spot));
+    }
+
+    #[test]
+    fn verify_finds_missing() {
+        let mut encoding = Encoding::from(BTreeMap::from(Lookup::make(Street::Pref)));
+        assert!(encoding.missing(Street::Pref).is_empty());
+        let dropped = encoding.0.keys().next().copied().unwrap();
+        encoding.0.remove(&dropped);
+        assert!(encoding.missing(Street::Pref) == vec![dropped]);
+    }
+}
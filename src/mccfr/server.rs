@@ -0,0 +1,148 @@
+use super::average::Average;
+use super::bucket::Bucket;
+use super::edge::Edge;
+use super::sampler::Encoding;
+use super::spot::Spot;
+use crate::cards::isomorphism::Isomorphism;
+use crate::cards::street::Street;
+use crate::clustering::abstraction::Abstraction;
+use crate::clustering::store::Store;
+use crate::Arbitrary;
+use crate::Probability;
+use crate::Save;
+use std::collections::BTreeMap;
+
+/// inference-only counterpart to the Solver. keeps just the
+/// trained action distributions, keyed by Bucket, and the
+/// Encoding that maps a Spot onto one. it reads the exported
+/// Average rather than a Profile, so regrets never hit memory,
+/// and no trees are ever sampled.
+pub struct StrategyServer<S = BTreeMap<Isomorphism, Abstraction>> {
+    policies: BTreeMap<Bucket, BTreeMap<Edge, Probability>>,
+    encoding: Encoding<S>,
+}
+
+impl StrategyServer {
+    /// read the exported average strategy and encoder from disk
+    pub fn load() -> Self {
+        let street = Street::random();
+        Self::from((Average::load(street), Encoding::load(street)))
+    }
+}
+
+impl<S: Store> StrategyServer<S> {
+    /// action distribution to play at this Spot
+    pub fn query(&self, spot: &Spot) -> Result<&BTreeMap<Edge, Probability>, String> {
+        let ref bucket = self.encoding.bucket(spot);
+        self.policy(bucket)
+            .ok_or_else(|| format!("no trained strategy for bucket {}", bucket))
+    }
+    /// like query(), but never fails: a Bucket we never trained
+    /// falls back to uniform over the Edges a Tree would offer
+    pub fn recommend(&self, spot: &Spot) -> Vec<(Edge, Probability)> {
+        match self.query(spot) {
+            Ok(policy) => policy.iter().map(|(e, p)| (*e, *p)).collect(),
+            Err(_) => {
+                let edges = self.encoding.edges(spot);
+                let n = edges.len() as Probability;
+                edges.into_iter().map(|e| (e, 1. / n)).collect()
+            }
+        }
+    }
+    /// action distribution at an already-resolved Bucket
+    pub fn policy(&self, bucket: &Bucket) -> Option<&BTreeMap<Edge, Probability>> {
+        self.policies.get(bucket)
+    }
+    pub fn buckets(&self) -> impl Iterator<Item = &Bucket> {
+        self.policies.keys()
+    }
+    pub fn size(&self) -> usize {
+        self.policies.len()
+    }
+}
+
+impl<S: Store> From<(Average, Encoding<S>)> for StrategyServer<S> {
+    fn from((average, encoding): (Average, Encoding<S>)) -> Self {
+        Self {
+            policies: BTreeMap::<Bucket, Vec<(Edge, Probability)>>::from(average)
+                .into_iter()
+                .map(|(bucket, policy)| (bucket, policy.into_iter().collect()))
+                .collect(),
+            encoding,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::hand::Hand;
+    use crate::cards::observation::Observation;
+    use crate::cards::strength::Strength;
+    use crate::gameplay::action::Action;
+    use crate::gameplay::game::Game;
+    use crate::mccfr::data::Data;
+    use crate::mccfr::exact::Exact;
+    use crate::mccfr::profile::Profile;
+
+    fn encoder(game: &Game) -> Abstraction {
+        Abstraction::from(Observation::from(game).equity())
+    }
+    /// check and call down to a river where whoever acts first
+    /// loses at showdown. both hands are dealt, so with nothing
+    /// left to bluff at, equilibrium never puts more chips in.
+    fn losing_river() -> Game {
+        loop {
+            let game = Game::checked_down(Street::Rive);
+            let board = Hand::from(game.board());
+            let hero = Hand::from(game.actor().cards());
+            let hate = Hand::from(game.apply(Action::Check).actor().cards());
+            if Strength::from(Hand::add(hero, board)) < Strength::from(Hand::add(hate, board)) {
+                return game;
+            }
+        }
+    }
+
+    #[test]
+    fn recommends_checking_a_losing_hand() {
+        let root = losing_river();
+        let tree = Exact::expand(Data::from((root, encoder(&root))), encoder);
+        let mut exact = Exact::try_from(tree).unwrap();
+        exact.solve(64).unwrap();
+        let iso = Isomorphism::from(Observation::from(&root));
+        let encoding = Encoding::from(BTreeMap::from([(iso, encoder(&root))]));
+        let server = StrategyServer::from((Average::from(&exact), encoding));
+        let policy = server.recommend(&Spot::from(root));
+        let check = policy
+            .iter()
+            .find(|(edge, _)| *edge == Edge::Check)
+            .map(|(_, p)| *p)
+            .expect("check is legal first to act");
+        assert!(check > 0.9, "{:?}", policy);
+    }
+
+    #[test]
+    fn recommends_uniform_when_untrained() {
+        let root = losing_river();
+        let iso = Isomorphism::from(Observation::from(&root));
+        let encoding = Encoding::from(BTreeMap::from([(iso, encoder(&root))]));
+        let server = StrategyServer::from((Average::default(), encoding));
+        let policy = server.recommend(&Spot::from(root));
+        assert!(policy.len() > 1);
+        assert!(policy.iter().all(|(_, p)| *p == policy[0].1));
+        assert!((policy.iter().map(|(_, p)| p).sum::<Probability>() - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn serves_normalized_policies() {
+        let profile = Profile::random();
+        let n = profile.size();
+        let server = StrategyServer::from((Average::from(&profile), Encoding::random()));
+        assert!(server.size() == n);
+        assert!(server
+            .buckets()
+            .filter_map(|bucket| server.policy(bucket))
+            .map(|policy| policy.values().sum::<Probability>())
+            .all(|total| (total - 1.).abs() < 1e-4));
+    }
+}
@@ -0,0 +1,190 @@
+use super::odds::Odds;
+use crate::cards::street::Street;
+
+/// which raise sizes a Tree branches on, as fractions of the pot.
+///
+/// every size becomes an Edge::Raise, so it must sit on the Odds
+/// grid that Paths know how to encode. opens are the sizes for the
+/// first raise on a street, reraises for any raise after that, and
+/// nobody may raise again once limit reraises have gone in. all-in is
+/// legal regardless of stack depth, so it is a switch rather than
+/// a size. fewer sizes make for smaller Trees and faster CFR; more
+/// sizes let the blueprint tell finer bets apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BetAbstraction {
+    opens: [Vec<Odds>; 4],
+    reraises: [Vec<Odds>; 4],
+    limit: usize,
+    shove: bool,
+}
+
+impl BetAbstraction {
+    /// the same sizes for every raise on every street
+    pub fn uniform(sizes: &[Odds]) -> Self {
+        Self::from([sizes, sizes, sizes, sizes]).with_reraises([sizes, sizes, sizes, sizes])
+    }
+    /// sizes for raises after the first, per street
+    pub fn with_reraises(mut self, reraises: [&[Odds]; 4]) -> Self {
+        self.reraises = reraises.map(Self::validated);
+        self
+    }
+    /// most reraises allowed within a single street, on top of
+    /// the opening raise. zero leaves only the opens.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+    /// whether all-in is offered alongside the sized raises
+    pub fn with_shove(mut self, shove: bool) -> Self {
+        self.shove = shove;
+        self
+    }
+    pub fn shove(&self) -> bool {
+        self.shove
+    }
+    /// raise sizes on this street after n raises already went in
+    pub fn raises(&self, street: Street, n: usize) -> Vec<Odds> {
+        if n > self.limit {
+            vec![]
+        } else if n == 0 {
+            self.opens[street as usize].clone()
+        } else {
+            self.reraises[street as usize].clone()
+        }
+    }
+    fn validated(sizes: &[Odds]) -> Vec<Odds> {
+        for odds in sizes {
+            assert!(
+                Odds::GRID.contains(odds),
+                "{} is not on the Odds grid",
+                odds
+            );
+        }
+        sizes.to_vec()
+    }
+}
+
+/// opening sizes per street, reraising with the same sizes
+impl From<[&[Odds]; 4]> for BetAbstraction {
+    fn from(opens: [&[Odds]; 4]) -> Self {
+        Self {
+            opens: opens.map(Self::validated),
+            reraises: opens.map(Self::validated),
+            limit: crate::N_RAISE,
+            shove: true,
+        }
+    }
+}
+
+/// the fine grid preflop, coarser on the flop, and down to
+/// a pot-sized raise once the turn has seen a bet
+impl Default for BetAbstraction {
+    fn default() -> Self {
+        Self::from([
+            &Odds::PREF_RAISES[..],
+            &Odds::FLOP_RAISES[..],
+            &Odds::LATE_RAISES[..],
+            &Odds::LATE_RAISES[..],
+        ])
+        .with_reraises([
+            &Odds::PREF_RAISES[..],
+            &Odds::FLOP_RAISES[..],
+            &Odds::LAST_RAISES[..],
+            &Odds::LAST_RAISES[..],
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::observation::Observation;
+    use crate::clustering::abstraction::Abstraction;
+    use crate::gameplay::game::Game;
+    use crate::gameplay::ply::Ply;
+    use crate::mccfr::data::Data;
+    use crate::mccfr::edge::Edge;
+    use crate::mccfr::exact::Exact;
+    use crate::mccfr::player::Player;
+    use crate::mccfr::tree::Tree;
+    use std::collections::BTreeSet;
+
+    fn edges(sizes: BetAbstraction) -> BTreeSet<Edge> {
+        let mut tree = Tree::empty(Player(Ply::Choice(0))).with_sizes(sizes);
+        let root = tree.insert(Data::from((Game::root(), Abstraction::from(0.5))));
+        root.branches().into_iter().map(|(edge, _)| edge).collect()
+    }
+
+    #[test]
+    fn root_raises_follow_sizes() {
+        let sizes = [Odds(1, 2), Odds(1, 1), Odds(2, 1)];
+        let raises = edges(BetAbstraction::uniform(&sizes))
+            .into_iter()
+            .filter(|e| e.is_aggro())
+            .collect::<BTreeSet<Edge>>();
+        let expected = sizes
+            .map(Edge::Raise)
+            .into_iter()
+            .chain(std::iter::once(Edge::Shove))
+            .collect::<BTreeSet<Edge>>();
+        assert!(raises == expected, "{:?}", raises);
+    }
+
+    #[test]
+    fn shove_can_be_left_out() {
+        let sizes = BetAbstraction::uniform(&[Odds(1, 1)]).with_shove(false);
+        let aggro = edges(sizes).into_iter().filter(|e| e.is_aggro());
+        assert!(aggro.collect::<Vec<Edge>>() == vec![Edge::Raise(Odds(1, 1))]);
+    }
+
+    #[test]
+    fn default_matches_the_preflop_grid() {
+        let raises = edges(BetAbstraction::default())
+            .into_iter()
+            .filter(|e| e.is_raise())
+            .collect::<BTreeSet<Edge>>();
+        assert!(raises == Odds::PREF_RAISES.map(Edge::Raise).into_iter().collect());
+    }
+
+    #[test]
+    fn fewer_sizes_shrink_the_tree() {
+        let encoder = |game: &Game| Abstraction::from(Observation::from(game).equity());
+        let game = Game::checked_down(Street::Rive);
+        let root = || Data::from((game, encoder(&game)));
+        let small = BetAbstraction::uniform(&[Odds(1, 1)]).with_limit(1);
+        let small = Exact::expand_with(root(), encoder, small);
+        let large = Exact::expand(root(), encoder);
+        assert!(small.graph().node_count() < large.graph().node_count());
+    }
+
+    /// river Tree branching on pot sized raises, up to a limit
+    fn river(limit: usize) -> Tree {
+        let encoder = |game: &Game| Abstraction::from(Observation::from(game).equity());
+        let game = Game::checked_down(Street::Rive);
+        let sizes = BetAbstraction::uniform(&[Odds(1, 1)]).with_limit(limit);
+        Exact::expand_with(Data::from((game, encoder(&game))), encoder, sizes)
+    }
+
+    #[test]
+    fn limit_caps_reraises() {
+        let tree = river(0);
+        let raised = tree.all().into_iter().filter(|n| n.data().raises() > 0);
+        let reraises = raised.flat_map(|n| n.outgoing()).filter(|e| e.is_raise());
+        assert!(reraises.count() == 0);
+        let opens = tree.all().into_iter().flat_map(|n| n.outgoing());
+        assert!(opens.filter(|e| e.is_raise()).count() > 0);
+    }
+
+    #[test]
+    fn limit_grows_the_tree() {
+        let counts = [0, 1, 3].map(|limit| river(limit).graph().node_count());
+        assert!(counts[0] < counts[1], "{:?}", counts);
+        assert!(counts[1] < counts[2], "{:?}", counts);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sizes_must_be_on_the_grid() {
+        BetAbstraction::uniform(&[Odds(5, 7)]);
+    }
+}
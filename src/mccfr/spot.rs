@@ -4,6 +4,8 @@ use super::policy::Policy;
 use crate::cards::hole::Hole;
 use crate::gameplay::action::Action;
 use crate::gameplay::game::Game;
+use crate::gameplay::ply::Ply;
+use crate::Arbitrary;
 
 pub struct Spot {
     root: Game, // only used for starting stacks (hopefully)
@@ -14,7 +16,70 @@ impl Spot {
     pub fn root(&self) -> &Game {
         &self.root
     }
+    /// the Game reached by replaying every past Action
+    pub fn game(&self) -> Game {
+        Self::replay(&self.root, &self.past)
+    }
+    /// raises made on the current street, as a Tree would count
+    /// them toward its BetAbstraction's limit
+    pub fn raises(&self) -> usize {
+        self.past
+            .iter()
+            .rev()
+            .take_while(|action| !action.is_chance())
+            .filter(|action| action.is_raise())
+            .count()
+    }
     pub fn coalesce(&self, policy: Policy) -> Policy {
         todo!()
     }
+    fn replay(root: &Game, past: &[Action]) -> Game {
+        past.iter().fold(*root, |game, action| game.apply(*action))
+    }
+}
+
+/// a decision point at this Game itself, with no history
+/// behind it, as seen by whoever is to act
+impl From<Game> for Spot {
+    fn from(game: Game) -> Self {
+        Self {
+            root: game,
+            past: vec![],
+            hole: game.actor().cards(),
+        }
+    }
+}
+
+/// a decision point reached by random legal play from the root,
+/// seen through the eyes of whoever is to act there
+impl Arbitrary for Spot {
+    fn random() -> Self {
+        use rand::seq::SliceRandom;
+        let root = Game::root();
+        let actions = root.rollout();
+        let depth = (0..actions.len())
+            .filter(|&i| Self::replay(&root, &actions[..i]).player() != Ply::Chance)
+            .collect::<Vec<usize>>()
+            .choose(&mut crate::rng())
+            .copied()
+            .expect("root is a decision");
+        let past = actions[..depth].to_vec();
+        let hole = Self::replay(&root, &past).actor().cards();
+        Self { root, past, hole }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_is_decision() {
+        for _ in 0..64 {
+            let spot = Spot::random();
+            let game = Spot::replay(spot.root(), &spot.past);
+            assert!(matches!(game.player(), Ply::Choice(_)));
+            assert!(game.actor().cards() == spot.hole);
+        }
+    }
 }
@@ -22,6 +22,20 @@ impl Strategy {
         let numer = self.0.get(edge).expect("edge in infoset").policy();
         numer / denom
     }
+    /// normalized action distribution, as served to the play loop.
+    /// with no policy mass at all we can't prefer any Edge, so
+    /// every one is equally likely rather than NaN.
+    pub fn distribution(&self) -> BTreeMap<Edge, Probability> {
+        let mass = self.0.values().map(|m| m.policy()).sum::<Probability>();
+        let n = self.0.len() as Probability;
+        self.0
+            .keys()
+            .map(|edge| match mass {
+                0. => (edge.clone(), 1. / n),
+                _ => (edge.clone(), self.weight(edge)),
+            })
+            .collect()
+    }
     pub fn get(&self, edge: &Edge) -> Option<&Memory> {
         self.0.get(edge)
     }
@@ -46,8 +60,33 @@ impl Strategy {
 impl Arbitrary for Strategy {
     fn random() -> Self {
         use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng();
         let n = rng.gen_range(1..=8);
         Self((0..n).map(|_| (Edge::random(), Memory::random())).collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribution_normalized() {
+        let strategy = Strategy::random();
+        let distribution = strategy.distribution();
+        assert!(distribution.len() == strategy.keys().count());
+        assert!((distribution.values().sum::<Probability>() - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn distribution_uniform_without_mass() {
+        let mut strategy = Strategy::random();
+        let edges = strategy.keys().cloned().collect::<Vec<Edge>>();
+        for edge in edges.iter() {
+            strategy.get_mut(edge).unwrap().set_policy(0.);
+        }
+        let n = edges.len() as Probability;
+        let distribution = strategy.distribution();
+        assert!(distribution.values().all(|p| *p == 1. / n));
+    }
+}
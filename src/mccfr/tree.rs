@@ -1,5 +1,6 @@
 use super::data::Data;
 use super::player::Player;
+use super::sizing::BetAbstraction;
 use crate::mccfr::edge::Edge;
 use crate::mccfr::node::Node;
 use petgraph::graph::DiGraph;
@@ -19,18 +20,34 @@ impl Branch {
 /// The `Tree` struct contains two main components:
 /// 1. A directed graph (`DiGraph`) representing the game tree, where nodes are game states and edges are actions.
 /// 2. A mapping from `Bucket`s to `Info`sets, which groups similar game states together.
+///
+/// the BetAbstraction decides which raise sizes each Node branches on.
 #[derive(Debug, Default)]
-pub struct Tree(DiGraph<Data, Edge>, Player);
+pub struct Tree(DiGraph<Data, Edge>, Player, BetAbstraction);
 
 impl Tree {
     pub fn all(&self) -> Vec<Node> {
         self.0.node_indices().map(|n| self.at(n)).collect()
     }
     pub fn at(&self, index: NodeIndex) -> Node {
-        Node::from((index, &self.0))
+        Node::from((index, &self.0, &self.2))
     }
     pub fn empty(player: Player) -> Self {
-        Self(DiGraph::with_capacity(0, 0), player)
+        Self(
+            DiGraph::with_capacity(0, 0),
+            player,
+            BetAbstraction::default(),
+        )
+    }
+    /// branch on these raise sizes instead. set this before
+    /// inserting anything, since Buckets are fixed on insertion.
+    pub fn with_sizes(mut self, sizes: BetAbstraction) -> Self {
+        assert!(self.0.node_count() == 0, "tree already has nodes");
+        self.2 = sizes;
+        self
+    }
+    pub fn sizes(&self) -> &BetAbstraction {
+        &self.2
     }
     pub fn walker(&self) -> Player {
         self.1
@@ -47,13 +64,45 @@ impl Tree {
             .expect("node index in tree");
         self.at(index)
     }
+    /// a new street starts the raise count over, since
+    /// BetAbstraction limits raises per street
     pub fn attach(&mut self, branch: Branch) -> Node {
-        let leaf = self.insert(branch.0).index();
         let edge = branch.1;
         let root = branch.2;
+        let raises = match edge {
+            Edge::Draw => 0,
+            edge => self.at(root).data().raises() + edge.is_raise() as usize,
+        };
+        let leaf = self.insert(branch.0.with_raises(raises)).index();
         self.0.add_edge(root, leaf, edge);
         self.at(leaf)
     }
+    /// attach every child of a Node at once, the first time
+    /// it is visited. attach() alone leaves the parent open,
+    /// since a sampled Tree only holds some of its children.
+    /// Buckets are still assigned on insertion,
+    /// so a lazily expanded Tree partitions identically
+    /// to one that was built eagerly.
+    pub fn expand(&mut self, index: NodeIndex, branches: Vec<Branch>) -> Vec<Node<'_>> {
+        if !self.at(index).data().expanded() {
+            for branch in branches {
+                assert!(branch.2 == index, "branch from a different node");
+                self.attach(branch);
+            }
+            self.0
+                .node_weight_mut(index)
+                .map(|data| data.expand())
+                .expect("node index in tree");
+        }
+        self.at(index).children()
+    }
+    /// Nodes whose children have not been generated yet.
+    pub fn frontier(&self) -> Vec<Node<'_>> {
+        self.all()
+            .into_iter()
+            .filter(|node| !node.data().expanded())
+            .collect()
+    }
     pub fn draw(&self, f: &mut Formatter, index: NodeIndex, prefix: &str) -> Result {
         if index == NodeIndex::new(0) {
             writeln!(f, "\nROOT   {}", self.at(index).bucket())?;
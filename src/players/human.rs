@@ -18,7 +18,7 @@ impl Human {
 
     fn random(game: &Game) -> Action {
         use rand::seq::SliceRandom;
-        let ref mut rng = rand::thread_rng();
+        let ref mut rng = crate::rng();
         game.legal()
             .choose(rng)
             .copied()
@@ -36,7 +36,7 @@ impl Human {
                 if input < game.to_raise() {
                     return Err("Raise too small");
                 }
-                if input > game.to_shove() {
+                if input > game.to_max_raise() {
                     return Err("Raise too large");
                 }
                 Ok(())